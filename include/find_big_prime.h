@@ -0,0 +1,43 @@
+/* C interface to find-big-prime, built with
+ * `cargo rustc --lib --release --features ffi --crate-type cdylib`. */
+#ifndef FIND_BIG_PRIME_H
+#define FIND_BIG_PRIME_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum {
+    FBP_OK = 0,
+    FBP_NULL_POINTER = 1,
+    FBP_BITS_TOO_SMALL = 2,
+    FBP_BITS_TOO_LARGE = 3,
+    FBP_BUFFER_TOO_SMALL = 4,
+    FBP_CANCELLED = 5,
+    FBP_LIMIT_REACHED = 6,
+    FBP_WEIGHT_OUT_OF_RANGE = 7,
+    FBP_PANIC = 255
+} fbp_status;
+
+/* Write a big-endian probable prime of `bits` bits to `out`.
+ * `out_len` must be at least (bits + 7) / 8; a shorter buffer or a null
+ * pointer is reported before any work is done. */
+fbp_status fbp_generate_prime(size_t bits, size_t rounds,
+                              uint8_t *out, size_t out_len, size_t *written);
+
+/* Same as fbp_generate_prime, producing a safe prime p = 2q + 1. */
+fbp_status fbp_generate_safe_prime(size_t bits, size_t rounds,
+                                   uint8_t *out, size_t out_len, size_t *written);
+
+/* Set *is_prime to 1 if the big-endian integer n[0..n_len] is a probable prime. */
+fbp_status fbp_is_probable_prime(const uint8_t *n, size_t n_len,
+                                 size_t rounds, int *is_prime);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FIND_BIG_PRIME_H */
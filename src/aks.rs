@@ -0,0 +1,185 @@
+//! The Agrawal–Kayal–Saxena deterministic primality test.
+//!
+//! AKS proves primality in polynomial time, but the polynomial is of such
+//! high degree that numbers beyond a few dozen bits take minutes to hours.
+//! It is here for teaching and for cross-checking the fast tests on small
+//! inputs. Like [`crate::core`] this needs only `alloc`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use num_integer::{Integer, Roots};
+use num_traits::{One, ToPrimitive, Zero};
+
+/// Whether `n > 1` is m^k for some integers m and k >= 2.
+fn is_perfect_power(n: &BigUint) -> bool {
+    (2..=n.bits()).any(|k| {
+        let k = k as u32;
+        n.nth_root(k).pow(k) == *n
+    })
+}
+
+/// The smallest r coprime to `n` such that the multiplicative order of
+/// `n` mod r exceeds `limit`.
+fn order_modulus(n: &BigUint, limit: u64) -> u64 {
+    (2u64..)
+        .find(|&r| {
+            let residue = (n % r).to_u64().expect("residue is below r");
+            if residue.gcd(&r) != 1 {
+                return false;
+            }
+            let mut power = 1u64;
+            (1..=limit).all(|_| {
+                power = (u128::from(power) * u128::from(residue) % u128::from(r)) as u64;
+                power != 1
+            })
+        })
+        .expect("some r below log2(n)^5 qualifies")
+}
+
+/// Euler's totient of `r`.
+fn totient(mut r: u64) -> u64 {
+    let mut phi = r;
+    let mut p = 2;
+    while p * p <= r {
+        if r.is_multiple_of(p) {
+            while r.is_multiple_of(p) {
+                r /= p;
+            }
+            phi -= phi / p;
+        }
+        p += 1;
+    }
+    if r > 1 {
+        phi -= phi / r;
+    }
+    phi
+}
+
+/// Product of two polynomials modulo (X^r - 1, n), each given by its r
+/// coefficients.
+fn mul(a: &[BigUint], b: &[BigUint], n: &BigUint) -> Vec<BigUint> {
+    let r = a.len();
+    let mut product = vec![BigUint::zero(); r];
+    for (i, x) in a.iter().enumerate().filter(|(_, x)| !x.is_zero()) {
+        for (j, y) in b.iter().enumerate() {
+            product[(i + j) % r] += x * y;
+        }
+    }
+    for coefficient in &mut product {
+        *coefficient %= n;
+    }
+    product
+}
+
+/// [`mul`] for `n` below 2^64, on machine words.
+fn mul_u64(a: &[u64], b: &[u64], n: u64) -> Vec<u64> {
+    let r = a.len();
+    let n = u128::from(n);
+    let mut product = vec![0u128; r];
+    for (i, &x) in a.iter().enumerate().filter(|(_, x)| **x != 0) {
+        for (j, &y) in b.iter().enumerate() {
+            let k = if i + j >= r { i + j - r } else { i + j };
+            let term = u128::from(x) * u128::from(y);
+            product[k] = product[k]
+                .checked_add(term)
+                .unwrap_or_else(|| product[k] % n + term);
+        }
+    }
+    product.into_iter().map(|c| (c % n) as u64).collect()
+}
+
+/// Whether (X + a)^n = X^n + a modulo (X^r - 1, n), for `n > r` coprime to r
+/// and `a < r`.
+fn satisfies_congruence(n: &BigUint, r: usize, a: u64) -> bool {
+    let shift = (n % r).to_usize().expect("residue is below r");
+    match n.to_u64() {
+        Some(m) => congruence(n, r, shift, a, 1, |x, y| mul_u64(x, y, m)),
+        None => congruence(n, r, shift, a.into(), BigUint::one(), |x, y| mul(x, y, n)),
+    }
+}
+
+/// [`satisfies_congruence`] with coefficients of type `T`, multiplied by `mul`.
+fn congruence<T: Clone + Default + PartialEq>(
+    n: &BigUint,
+    r: usize,
+    shift: usize,
+    a: T,
+    one: T,
+    mul: impl Fn(&[T], &[T]) -> Vec<T>,
+) -> bool {
+    let mut base = vec![T::default(); r];
+    base[0] = a.clone();
+    base[1] = one.clone();
+
+    let mut power = vec![T::default(); r];
+    power[0] = one.clone();
+    for bit in (0..n.bits()).rev() {
+        power = mul(&power, &power);
+        if n.bit(bit) {
+            power = mul(&power, &base);
+        }
+    }
+
+    // gcd(n, r) = 1 and r > 1, so `shift` is never 0.
+    let mut expected = vec![T::default(); r];
+    expected[0] = a;
+    expected[shift] = one;
+    power == expected
+}
+
+/// The AKS test: `true` exactly when `n` is prime.
+///
+/// This is a proof, with no probability of error, but it is far too slow
+/// for cryptographic sizes; prefer [`crate::BailliePsw`] or Miller–Rabin
+/// there.
+pub fn is_prime_aks(n: &BigUint) -> bool {
+    if *n < BigUint::from(2u32) || is_perfect_power(n) {
+        return false;
+    }
+    // The bit length is at least log2(n), which only makes the bounds stricter.
+    let log = n.bits();
+    let r = order_modulus(n, log * log);
+    for a in 2..=r {
+        let a = BigUint::from(a);
+        if a >= *n {
+            break;
+        }
+        if !a.gcd(n).is_one() {
+            return false;
+        }
+    }
+    if *n <= BigUint::from(r) {
+        return true;
+    }
+
+    let phi = totient(r);
+    let root = phi.sqrt() + u64::from(phi.sqrt() * phi.sqrt() < phi);
+    let r = usize::try_from(r).expect("r is polynomial in the bit length");
+    (1..=root * log).all(|a| satisfies_congruence(n, r, a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::is_prime_deterministic;
+    use crate::lucas::baillie_psw;
+
+    fn trial_division(n: u32) -> bool {
+        n >= 2
+            && (2..)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn agrees_with_the_other_tests() {
+        for n in (0..2048).chain([1729, 2465, 2821, 6601, 8911]) {
+            let expected = trial_division(n);
+            let n = BigUint::from(n);
+            assert_eq!(is_prime_aks(&n), expected, "{n}");
+            assert_eq!(is_prime_deterministic(&n), Some(expected), "{n}");
+            assert_eq!(baillie_psw(&n), expected, "{n}");
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! Async wrappers that run the search on tokio's blocking pool.
+//!
+//! Enabled with the `tokio` feature. The futures can be awaited or raced
+//! against `tokio::time::timeout`; dropping the future does not stop the
+//! search thread, which runs to completion in the background.
+
+use num_bigint::BigUint;
+use tokio::task;
+
+use crate::{PrimeGenerator, Result, generate_probable_prime, generate_safe_prime};
+
+/// Async version of [`generate_probable_prime`].
+pub async fn generate_probable_prime_async(bits: usize, rounds: usize) -> Result<BigUint> {
+    run_blocking(move || generate_probable_prime(bits, rounds)).await
+}
+
+/// Async version of [`generate_safe_prime`].
+pub async fn generate_safe_prime_async(bits: usize, rounds: usize) -> Result<BigUint> {
+    run_blocking(move || generate_safe_prime(bits, rounds)).await
+}
+
+impl PrimeGenerator {
+    /// Async version of [`PrimeGenerator::generate`].
+    pub async fn generate_async(&self) -> Result<BigUint> {
+        let generator = self.clone();
+        run_blocking(move || generator.generate()).await
+    }
+}
+
+async fn run_blocking<F>(f: F) -> Result<BigUint>
+where
+    F: FnOnce() -> Result<BigUint> + Send + 'static,
+{
+    match task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(err) => std::panic::resume_unwind(err.into_panic()),
+    }
+}
@@ -0,0 +1,31 @@
+//! Cooperative cancellation for long-running searches.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A cloneable flag checked by the generation loop between candidates.
+///
+/// Cancelling any clone stops every search that holds the token; the
+/// search then returns [`PrimeGenError::Cancelled`](crate::PrimeGenError::Cancelled)
+/// with the statistics gathered so far.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    flag: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation.
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,184 @@
+//! Primality certificates that a third party can check far faster than
+//! the search that produced the prime.
+//!
+//! Like [`crate::core`] this needs only `alloc`.
+
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use crate::factor::factorize;
+
+/// Witnesses [`pocklington_certificate`] and [`pratt_certificate`] try,
+/// from 2 up, before giving up.
+const MAX_WITNESS: u32 = 1000;
+
+/// A Pocklington certificate: `n` is prime if each listed factor q is, given
+/// a^(n - 1) = 1 and gcd(a^((n - 1)/q) - 1, n) = 1 modulo `n` for every q,
+/// and the part F of n − 1 made up of those primes exceeds √n.
+///
+/// Every prime divisor of `n` is then 1 modulo F, so `n` has none below √n.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PocklingtonCertificate {
+    /// The number proven prime.
+    pub n: BigUint,
+    /// Distinct prime factors of n − 1, taken with their full multiplicity.
+    pub factors: Vec<BigUint>,
+    /// The witness a.
+    pub witness: BigUint,
+}
+
+impl PocklingtonCertificate {
+    /// Whether the certificate proves `n` prime, taking the primality of
+    /// the listed factors on trust.
+    pub fn verify(&self) -> bool {
+        let n = &self.n;
+        if *n < BigUint::from(3u32) || self.witness < BigUint::from(2u32) || self.witness >= *n {
+            return false;
+        }
+        let n_minus_one = n - 1u32;
+        let Some(f) = factored_part(&n_minus_one, &self.factors) else {
+            return false;
+        };
+        if &f * &f <= *n || !self.witness.modpow(&n_minus_one, n).is_one() {
+            return false;
+        }
+        self.factors.iter().all(|q| {
+            let b = self.witness.modpow(&(&n_minus_one / q), n);
+            !b.is_zero() && (b - 1u32).gcd(n).is_one()
+        })
+    }
+}
+
+/// A Pocklington certificate for `n` from prime factors of n − 1 whose
+/// part of n − 1 exceeds √n, with the smallest witness that works.
+///
+/// `None` if the factors do not divide n − 1, fall short of √n, or no
+/// witness below 1000 works, which for a prime `n` essentially never
+/// happens.
+pub fn pocklington_certificate(n: &BigUint, factors: &[BigUint]) -> Option<PocklingtonCertificate> {
+    let mut factors = factors.to_vec();
+    factors.sort();
+    factors.dedup();
+    if *n < BigUint::from(3u32) {
+        return None;
+    }
+    let f = factored_part(&(n - 1u32), &factors)?;
+    if &f * &f <= *n {
+        return None;
+    }
+    (2..MAX_WITNESS)
+        .map(|a| PocklingtonCertificate {
+            n: n.clone(),
+            factors: factors.clone(),
+            witness: BigUint::from(a),
+        })
+        .take_while(|certificate| certificate.witness < *n)
+        .find(PocklingtonCertificate::verify)
+}
+
+/// A Pratt certificate: `n` is prime because the witness a has order n − 1
+/// modulo `n`, that is a^(n - 1) = 1 and a^((n - 1)/q) ≠ 1 for every prime q
+/// dividing n − 1, and each of those q is certified in turn, down to 2.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrattCertificate {
+    /// The number proven prime.
+    pub n: BigUint,
+    /// The witness a, a primitive root modulo `n`; 1 for `n` = 2.
+    pub witness: BigUint,
+    /// Certificates for the distinct prime factors of n − 1, in increasing
+    /// order; empty for `n` = 2.
+    pub factors: Vec<PrattCertificate>,
+}
+
+impl PrattCertificate {
+    /// Whether the certificate proves `n` prime, checking every certificate
+    /// below it as well.
+    pub fn verify(&self) -> bool {
+        let n = &self.n;
+        if *n == BigUint::from(2u32) {
+            return self.factors.is_empty();
+        }
+        if *n < BigUint::from(3u32) || self.witness < BigUint::from(2u32) || self.witness >= *n {
+            return false;
+        }
+        let n_minus_one = n - 1u32;
+        let primes: Vec<BigUint> = self.factors.iter().map(|f| f.n.clone()).collect();
+        if factored_part(&n_minus_one, &primes).as_ref() != Some(&n_minus_one)
+            || !self.witness.modpow(&n_minus_one, n).is_one()
+        {
+            return false;
+        }
+        primes
+            .iter()
+            .all(|q| !self.witness.modpow(&(&n_minus_one / q), n).is_one())
+            && self.factors.iter().all(PrattCertificate::verify)
+    }
+}
+
+/// A Pratt certificate for `n`, factoring n − 1 and each prime below it by
+/// [`factorize`] with `max_iterations` steps of Pollard's rho.
+///
+/// `None` if `n` is composite, if some n − 1 down the tree does not factor
+/// completely within the budget, or if no witness below 1000 is a primitive
+/// root. That makes it practical up to a hundred bits or so, further for
+/// primes whose n − 1 is smooth.
+pub fn pratt_certificate(n: &BigUint, max_iterations: u64) -> Option<PrattCertificate> {
+    let two = BigUint::from(2u32);
+    if *n <= two {
+        return (*n == two).then(|| PrattCertificate {
+            n: two,
+            witness: BigUint::one(),
+            factors: Vec::new(),
+        });
+    }
+    let n_minus_one = n - 1u32;
+    let factorization = factorize(&n_minus_one, max_iterations);
+    if factorization.cofactor.is_some() {
+        return None;
+    }
+    let cofactors: Vec<BigUint> = factorization
+        .primes
+        .iter()
+        .map(|(q, _)| &n_minus_one / q)
+        .collect();
+    let witness = (2..MAX_WITNESS).map(BigUint::from).find(|a| {
+        a < n
+            && a.modpow(&n_minus_one, n).is_one()
+            && cofactors.iter().all(|e| !a.modpow(e, n).is_one())
+    })?;
+    let factors = factorization
+        .primes
+        .iter()
+        .map(|(q, _)| pratt_certificate(q, max_iterations))
+        .collect::<Option<_>>()?;
+    Some(PrattCertificate {
+        n: n.clone(),
+        witness,
+        factors,
+    })
+}
+
+/// The product of the full powers of `factors` in `m`, or `None` if one of
+/// them is below 2, repeated, or does not divide `m`.
+fn factored_part(m: &BigUint, factors: &[BigUint]) -> Option<BigUint> {
+    let mut f = BigUint::one();
+    let mut rest = m.clone();
+    for q in factors {
+        if *q < BigUint::from(2u32) {
+            return None;
+        }
+        let (quotient, remainder) = rest.div_rem(q);
+        if !remainder.is_zero() {
+            return None;
+        }
+        rest = quotient;
+        f *= q;
+        while rest.is_multiple_of(q) {
+            rest /= q;
+            f *= q;
+        }
+    }
+    Some(f)
+}
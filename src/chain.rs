@@ -0,0 +1,66 @@
+//! Cunningham and bi-twin chains: primes each twice the one before, plus
+//! or minus one.
+//!
+//! A chain of the first kind runs p, 2p + 1, 4p + 3, …, one of the second
+//! kind p, 2p − 1, 4p − 3, …; a safe prime and its q are a chain of the
+//! first kind of length two. Writing the i-th member as 2^i·n ∓ 1 with
+//! n = p ± 1 puts every member on the same base, which is how the search
+//! sieves them together. A bi-twin chain on n has both: n ± 1, 2n ± 1, …,
+//! one of each kind side by side, and a twin pair is one link of it. Like
+//! [`crate::core`] this needs only `alloc`.
+
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use num_traits::One;
+
+/// Which way a Cunningham chain doubles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChainKind {
+    /// p, 2p + 1, 4p + 3, …
+    First,
+    /// p, 2p − 1, 4p − 3, …
+    Second,
+}
+
+impl ChainKind {
+    /// The member after `p`: 2p + 1 or 2p − 1.
+    pub fn next(self, p: &BigUint) -> BigUint {
+        match self {
+            ChainKind::First => (p << 1u32) + 1u32,
+            ChainKind::Second => (p << 1u32) - 1u32,
+        }
+    }
+
+    /// The `length` members of the chain starting at `first`.
+    pub fn members(self, first: &BigUint, length: usize) -> Vec<BigUint> {
+        let mut members: Vec<BigUint> = Vec::with_capacity(length);
+        let mut p = first.clone();
+        for _ in 0..length {
+            let next = self.next(&p);
+            members.push(p);
+            p = next;
+        }
+        members
+    }
+
+    /// The base n = p + 1 or p − 1 whose doublings, less or plus one, give
+    /// the members after a first member `p`.
+    pub fn base(self, p: &BigUint) -> BigUint {
+        match self {
+            ChainKind::First => p + 1u32,
+            ChainKind::Second => p - BigUint::one(),
+        }
+    }
+}
+
+/// The 2·`links` members n − 1, n + 1, 2n − 1, 2n + 1, … of the bi-twin
+/// chain on the base `n`.
+pub fn bi_twin_members(n: &BigUint, links: usize) -> Vec<BigUint> {
+    let mut members = Vec::with_capacity(2 * links);
+    for i in 0..links {
+        let doubled = n << i;
+        members.push(&doubled - BigUint::one());
+        members.push(doubled + 1u32);
+    }
+    members
+}
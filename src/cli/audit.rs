@@ -0,0 +1,256 @@
+//! `--audit-log`: an append-only record of a generation run, for
+//! key-ceremony paperwork.
+//!
+//! Each line is one JSON object with a UTC `"ts"` and an `"event"`:
+//! `start` (version, command line, RNG and seed handling), `search` (the
+//! settings of each size), `candidate` (how every drawn candidate ended and
+//! the rounds it got), `constructed` (a provable or phrase-derived prime)
+//! and `end`. Primes
+//! and seeds appear only as SHA-256 fingerprints, so the log can be filed
+//! without the key material.
+
+use find_big_prime::{CandidateEvent, SearchStats};
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::output::json_string;
+
+/// Options whose value is key material, left out of the logged command line.
+const SECRET_OPTIONS: [&str; 1] = ["--seed"];
+
+/// An open audit log, shared by the search threads.
+pub struct AuditLog {
+    state: Mutex<State>,
+}
+
+struct State {
+    out: BufWriter<File>,
+    /// Small numbers for the worker threads, in order of first appearance.
+    threads: HashMap<ThreadId, usize>,
+    /// Rounds run so far on each candidate still being tested, by thread.
+    rounds: HashMap<(usize, BigUint), usize>,
+    /// Primes accepted or constructed so far.
+    primes: u64,
+    /// The first write that failed; reported by [`AuditLog::finish`].
+    error: Option<io::Error>,
+}
+
+impl AuditLog {
+    /// Open `path` for appending, creating it (on Unix readable only by its
+    /// owner) if needed.
+    pub fn open(path: &Path) -> io::Result<AuditLog> {
+        let mut options = OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        Ok(AuditLog {
+            state: Mutex::new(State {
+                out: BufWriter::new(options.open(path)?),
+                threads: HashMap::new(),
+                rounds: HashMap::new(),
+                primes: 0,
+                error: None,
+            }),
+        })
+    }
+
+    /// Append one `event` line with further JSON `members`.
+    pub fn record(&self, event: &str, members: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        state.write(event, members);
+    }
+
+    /// The `start` line: the command line with secret values left out, and
+    /// where randomness comes from. `seed` is a JSON object saying how seed
+    /// material is handled; see [`seed`].
+    pub fn start(&self, seed: &str) {
+        let mut args = Vec::new();
+        let mut hide_next = false;
+        for arg in std::env::args() {
+            let arg = if hide_next {
+                "<omitted>".to_owned()
+            } else {
+                match SECRET_OPTIONS.iter().find(|option| {
+                    arg.strip_prefix(**option)
+                        .is_some_and(|rest| rest.starts_with('='))
+                }) {
+                    Some(option) => format!("{option}=<omitted>"),
+                    None => arg,
+                }
+            };
+            hide_next = SECRET_OPTIONS.contains(&arg.as_str());
+            args.push(json_string(&arg));
+        }
+        self.record(
+            "start",
+            &format!(
+                "\"version\":\"{}\",\"argv\":[{}],\"pid\":{},\"rng\":\"OsRng (operating-system CSPRNG via getrandom)\",\"seed\":{seed}",
+                env!("CARGO_PKG_VERSION"),
+                args.join(","),
+                std::process::id()
+            ),
+        );
+    }
+
+    /// Log how a candidate ended, with the rounds it got, or count a round
+    /// towards it.
+    pub fn observe(&self, event: &CandidateEvent<'_>) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        let next = state.threads.len();
+        let thread = *state.threads.entry(thread::current().id()).or_insert(next);
+        let (candidate, result) = match *event {
+            CandidateEvent::Drawn(_) => {
+                // Whatever this thread tested before has been dealt with,
+                // such as the q of a safe prime whose p failed.
+                state.rounds.retain(|(owner, _), _| *owner != thread);
+                return;
+            }
+            CandidateEvent::Round { candidate, .. } => {
+                *state.rounds.entry((thread, candidate.clone())).or_default() += 1;
+                return;
+            }
+            CandidateEvent::SieveRejected(candidate) => (candidate, "sieve_rejected"),
+            CandidateEvent::FermatRejected(candidate) => (candidate, "fermat_rejected"),
+            CandidateEvent::TestRejected(candidate) => (candidate, "test_rejected"),
+            CandidateEvent::Accepted(candidate) => (candidate, "accepted"),
+            _ => return,
+        };
+        let rounds = state
+            .rounds
+            .remove(&(thread, candidate.clone()))
+            .unwrap_or(0);
+        let mut members = format!(
+            "\"thread\":{thread},\"bits\":{},\"result\":\"{result}\",\"rounds\":{rounds}",
+            candidate.bits()
+        );
+        if result == "accepted" {
+            state.primes += 1;
+            members.push_str(&format!(",\"sha256\":\"{}\"", fingerprint(candidate)));
+        }
+        state.write("candidate", &members);
+    }
+
+    /// The `constructed` line for a provable or phrase-derived prime.
+    pub fn constructed(&self, method: &str, prime: &BigUint) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        state.primes += 1;
+        state.write(
+            "constructed",
+            &format!(
+                "\"method\":\"{method}\",\"bits\":{},\"sha256\":\"{}\"",
+                prime.bits(),
+                fingerprint(prime)
+            ),
+        );
+    }
+
+    /// The `end` line with how the run ended and, for a search, its totals;
+    /// then flush, reporting the first write that failed.
+    pub fn finish(
+        &self,
+        status: &str,
+        stats: Option<&SearchStats>,
+        elapsed: Duration,
+    ) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        let mut members = format!(
+            "\"status\":{},\"primes\":{}",
+            json_string(status),
+            state.primes
+        );
+        if let Some(stats) = stats {
+            members.push_str(&format!(
+                ",\"candidates\":{},\"sieved_out\":{},\"fermat_rejected\":{},\"test_rejected\":{},\"rounds\":{}",
+                stats.candidates,
+                stats.sieved_out,
+                stats.fermat_rejected,
+                stats.test_rejected,
+                stats.rounds
+            ));
+        }
+        members.push_str(&format!(",\"elapsed_ms\":{}", elapsed.as_millis()));
+        state.write("end", &members);
+        if let Some(err) = state.error.take() {
+            return Err(err);
+        }
+        state.out.flush()?;
+        state.out.get_ref().sync_all()
+    }
+}
+
+impl State {
+    fn write(&mut self, event: &str, members: &str) {
+        if self.error.is_some() {
+            return;
+        }
+        let line = format!(
+            "{{\"ts\":\"{}\",\"event\":\"{event}\",{members}}}\n",
+            timestamp()
+        );
+        if let Err(err) = self.out.write_all(line.as_bytes()) {
+            self.error = Some(err);
+        }
+    }
+}
+
+/// The `"seed"` object of the `start` line: where seed material comes
+/// from and, if there is any, its length and fingerprint.
+pub fn seed(source: &str, material: Option<&[u8]>) -> String {
+    match material {
+        Some(bytes) => format!(
+            "{{\"source\":{},\"bytes\":{},\"sha256\":\"{}\"}}",
+            json_string(source),
+            bytes.len(),
+            hex_sha256(bytes)
+        ),
+        None => format!("{{\"source\":{}}}", json_string(source)),
+    }
+}
+
+/// The SHA-256 of `n`'s big-endian bytes, in hex.
+fn fingerprint(n: &BigUint) -> String {
+    hex_sha256(&n.to_bytes_be())
+}
+
+fn hex_sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The current time as RFC 3339 UTC with milliseconds.
+fn timestamp() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = now.as_secs();
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        rest / 3600,
+        rest / 60 % 60,
+        rest % 60,
+        now.subsec_millis()
+    )
+}
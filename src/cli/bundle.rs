@@ -0,0 +1,312 @@
+//! Reproducibility bundles: one JSON file with everything a `generate` run
+//! needs to be redone or rechecked, read back by `reproduce`.
+//!
+//! The object has `"format"` set to [`FORMAT`], a `"version"`, the
+//! `"crate_version"` that wrote it, the `"mode"` (`search`,
+//! `shawe-taylor`, `maurer` or `phrase`), the generation settings, the
+//! `"seed"` or `"phrase"` of the seeded modes, and the `"primes"` in the
+//! order they were printed, all as decimal strings. With
+//! `"sophie_germain"` set, `"bits"` sizes q and the primes are still the
+//! safe primes p = 2q + 1.
+
+use clap::ValueEnum;
+use find_big_prime::{ChainKind, FixedBases, Format, ResidueClass};
+use num_bigint::BigUint;
+
+use super::json::Json;
+use super::output::json_string;
+use super::primality::{self, Rounds, TestKind};
+use super::provable::{Seed, parse_seed};
+
+/// The `"format"` of a bundle.
+pub const FORMAT: &str = "find-big-prime-bundle";
+
+/// The `"version"` this build writes and the newest it reads.
+pub const VERSION: u64 = 1;
+
+/// How the primes were made, with what re-derives them.
+pub enum Mode {
+    /// A search drawing from the OS RNG, which can only be rechecked.
+    Search,
+    /// `--provable shawe-taylor` from this seed, chaining to each next prime.
+    ShaweTaylor(Seed),
+    /// `--provable maurer`, drawing from the OS RNG.
+    Maurer,
+    /// `--phrase`, from this phrase.
+    Phrase(String),
+}
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Search => "search",
+            Mode::ShaweTaylor(_) => "shawe-taylor",
+            Mode::Maurer => "maurer",
+            Mode::Phrase(_) => "phrase",
+        }
+    }
+}
+
+/// A `generate` run: its settings and results.
+pub struct Bundle {
+    /// The version of the crate that wrote the bundle.
+    pub crate_version: String,
+    pub mode: Mode,
+    pub bits: Vec<usize>,
+    /// Primes of each size.
+    pub count: u64,
+    pub safe: bool,
+    /// `--sophie-germain`, which sizes q instead of p.
+    pub sophie_germain: bool,
+    /// `--twin`: each prime p also has p + 2 prime.
+    pub twin: bool,
+    /// `--chain` and `--kind`: each prime starts a Cunningham chain.
+    pub chain: Option<(ChainKind, usize)>,
+    /// `--bi-twin`: each prime is n − 1 for a bi-twin chain of these links.
+    pub bi_twin: Option<usize>,
+    /// `--strong`, with the auxiliary prime size given, if one was.
+    pub strong: Option<Option<usize>>,
+    /// `--blum`: each prime is 3 mod 4.
+    pub blum: bool,
+    /// `--congruent` and `--suffix`, with `--blum` if given: each prime is
+    /// in this class, which combines them all.
+    pub congruence: Option<ResidueClass>,
+    /// `--top-bits`: each prime has this many top bits set.
+    pub top_bits: usize,
+    /// `--prefix`: each prime starts with these bits.
+    pub prefix: Option<BigUint>,
+    /// `--hamming-weight`: each prime has this many bits set.
+    pub hamming_weight: Option<usize>,
+    pub test: Vec<TestKind>,
+    pub rounds: Option<usize>,
+    pub error_bound: Option<u32>,
+    pub witnesses: Option<FixedBases>,
+    pub screen: Option<usize>,
+    pub primes: Vec<BigUint>,
+}
+
+impl Bundle {
+    /// The rounds the tests ran, as `--rounds` or `--error-bound` set them.
+    pub fn rounds(&self) -> Rounds {
+        match (self.rounds, self.error_bound) {
+            (Some(rounds), _) => Rounds::Fixed(rounds),
+            (None, Some(bound)) => Rounds::ErrorBound(bound),
+            (None, None) => Rounds::Default,
+        }
+    }
+
+    /// The bundle as a JSON object, with a trailing newline.
+    pub fn to_json(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "null".to_owned());
+        let tests: Vec<String> = self
+            .test
+            .iter()
+            .filter_map(|test| test.to_possible_value())
+            .map(|value| format!("\"{}\"", value.get_name()))
+            .collect();
+        let bits: Vec<String> = self.bits.iter().map(usize::to_string).collect();
+        let (seed, phrase) = match &self.mode {
+            Mode::ShaweTaylor(seed) => (Some(format!("\"{}\"", seed.hex())), None),
+            Mode::Phrase(phrase) => (None, Some(json_string(phrase))),
+            Mode::Search | Mode::Maurer => (None, None),
+        };
+        format!(
+            "{{\"format\":\"{FORMAT}\",\"version\":{VERSION},\"crate_version\":{},\"mode\":\"{}\",\"bits\":[{}],\"count\":{},\"safe\":{},\"sophie_germain\":{},\"twin\":{},\"chain\":{},\"bi_twin\":{},\"strong\":{},\"blum\":{},\"congruent\":{},\"top_bits\":{},\"prefix\":{},\"hamming_weight\":{},\"test\":[{}],\"rounds\":{},\"error_bound\":{},\"witnesses\":{},\"screen\":{},\"seed\":{},\"phrase\":{},\"primes\":{}}}\n",
+            json_string(&self.crate_version),
+            self.mode.name(),
+            bits.join(","),
+            self.count,
+            self.safe,
+            self.sophie_germain,
+            self.twin,
+            optional(self.chain.map(|(kind, length)| {
+                let kind = match kind {
+                    ChainKind::First => "1st",
+                    ChainKind::Second => "2nd",
+                };
+                format!("{{\"kind\":\"{kind}\",\"length\":{length}}}")
+            })),
+            optional(self.bi_twin.map(|links| links.to_string())),
+            match self.strong {
+                None => "false".to_owned(),
+                Some(None) => "true".to_owned(),
+                Some(Some(bits)) => bits.to_string(),
+            },
+            self.blum,
+            optional(self.congruence.as_ref().map(|class| {
+                format!(
+                    "{{\"residue\":\"{}\",\"modulus\":\"{}\"}}",
+                    class.residue(),
+                    class.modulus()
+                )
+            })),
+            self.top_bits,
+            optional(self.prefix.as_ref().map(|prefix| format!("\"{prefix}\""))),
+            optional(self.hamming_weight.map(|weight| weight.to_string())),
+            tests.join(","),
+            optional(self.rounds.map(|rounds| rounds.to_string())),
+            optional(self.error_bound.map(|bound| bound.to_string())),
+            optional(
+                self.witnesses
+                    .as_ref()
+                    .map(|witnesses| list(witnesses.bases()))
+            ),
+            optional(self.screen.map(|screen| screen.to_string())),
+            optional(seed),
+            optional(phrase),
+            list(&self.primes),
+        )
+    }
+
+    /// Read a bundle of any version up to [`VERSION`].
+    pub fn from_json(value: &Json) -> Result<Bundle, String> {
+        if value.get("format").and_then(Json::as_str) != Some(FORMAT) {
+            return Err("not a find-big-prime bundle".to_owned());
+        }
+        let version = small(value, "version")?;
+        if version > VERSION {
+            return Err(format!(
+                "version {version} is newer than this build reads ({VERSION})"
+            ));
+        }
+        let string = |name: &str| {
+            value
+                .get(name)
+                .and_then(Json::as_str)
+                .ok_or_else(|| format!("no \"{name}\""))
+        };
+        let mode = match string("mode")? {
+            "search" => Mode::Search,
+            "shawe-taylor" => Mode::ShaweTaylor(parse_seed(string("seed")?)?),
+            "maurer" => Mode::Maurer,
+            "phrase" => Mode::Phrase(string("phrase")?.to_owned()),
+            other => return Err(format!("unknown mode `{other}`")),
+        };
+        let test = array(value, "test")?
+            .iter()
+            .map(|test| {
+                let name = test.as_str().ok_or("a \"test\" that is not a string")?;
+                TestKind::from_str(name, false).map_err(|_| format!("unknown test `{name}`"))
+            })
+            .collect::<Result<_, String>>()?;
+        let witnesses = match value.get("witnesses") {
+            None | Some(Json::Null) => None,
+            Some(_) => {
+                let bases: Vec<String> = decimals(value, "witnesses")?
+                    .iter()
+                    .map(BigUint::to_string)
+                    .collect();
+                Some(primality::parse_witnesses(&bases.join(","))?)
+            }
+        };
+        Ok(Bundle {
+            crate_version: string("crate_version")?.to_owned(),
+            mode,
+            bits: array(value, "bits")?
+                .iter()
+                .map(|bits| {
+                    bits.as_number()
+                        .and_then(|bits| bits.parse().ok())
+                        .ok_or("a \"bits\" that is not a small number")
+                })
+                .collect::<Result<_, _>>()?,
+            count: small(value, "count")?,
+            safe: matches!(value.get("safe"), Some(Json::Bool(true))),
+            sophie_germain: matches!(value.get("sophie_germain"), Some(Json::Bool(true))),
+            twin: matches!(value.get("twin"), Some(Json::Bool(true))),
+            chain: match value.get("chain") {
+                None | Some(Json::Null) => None,
+                Some(chain) => {
+                    let kind = match chain.get("kind").and_then(Json::as_str) {
+                        Some("1st") => ChainKind::First,
+                        Some("2nd") => ChainKind::Second,
+                        _ => return Err("a \"chain\" of no known kind".to_owned()),
+                    };
+                    Some((kind, small(chain, "length")? as usize))
+                }
+            },
+            bi_twin: optional_small(value, "bi_twin")?.map(|links| links as usize),
+            strong: match value.get("strong") {
+                None | Some(Json::Null) | Some(Json::Bool(false)) => None,
+                Some(Json::Bool(true)) => Some(None),
+                Some(_) => Some(Some(small(value, "strong")? as usize)),
+            },
+            blum: matches!(value.get("blum"), Some(Json::Bool(true))),
+            congruence: match value.get("congruent") {
+                None | Some(Json::Null) => None,
+                Some(class) => {
+                    let part = |name: &str| {
+                        class
+                            .get(name)
+                            .and_then(Json::as_number)
+                            .and_then(|text| Format::Decimal.parse(text).ok())
+                            .ok_or_else(|| format!("a \"congruent\" with no decimal \"{name}\""))
+                    };
+                    Some(
+                        ResidueClass::new(part("residue")?, part("modulus")?)
+                            .ok_or("a \"congruent\" class that holds no primes")?,
+                    )
+                }
+            },
+            top_bits: optional_small(value, "top_bits")?.map_or(1, |top| top as usize),
+            prefix: match value.get("prefix") {
+                None | Some(Json::Null) => None,
+                Some(prefix) => Some(
+                    prefix
+                        .as_number()
+                        .and_then(|text| Format::Decimal.parse(text).ok())
+                        .ok_or("a \"prefix\" that is not a decimal")?,
+                ),
+            },
+            hamming_weight: optional_small(value, "hamming_weight")?.map(|weight| weight as usize),
+            test,
+            rounds: optional_small(value, "rounds")?.map(|rounds| rounds as usize),
+            error_bound: optional_small(value, "error_bound")?
+                .map(|bound| u32::try_from(bound).map_err(|_| "\"error_bound\" is too large"))
+                .transpose()?,
+            witnesses,
+            screen: optional_small(value, "screen")?.map(|screen| screen as usize),
+            primes: decimals(value, "primes")?,
+        })
+    }
+}
+
+/// `items` as a JSON array of decimal strings.
+fn list(items: &[BigUint]) -> String {
+    let items: Vec<String> = items.iter().map(|item| format!("\"{item}\"")).collect();
+    format!("[{}]", items.join(","))
+}
+
+fn array<'a>(value: &'a Json, name: &str) -> Result<&'a [Json], String> {
+    value
+        .get(name)
+        .and_then(Json::as_array)
+        .ok_or_else(|| format!("no \"{name}\" array"))
+}
+
+fn decimals(value: &Json, name: &str) -> Result<Vec<BigUint>, String> {
+    array(value, name)?
+        .iter()
+        .map(|item| {
+            item.as_number()
+                .and_then(|text| Format::Decimal.parse(text).ok())
+                .ok_or_else(|| format!("\"{name}\" holds something other than a decimal"))
+        })
+        .collect()
+}
+
+fn small(value: &Json, name: &str) -> Result<u64, String> {
+    optional_small(value, name)?.ok_or_else(|| format!("no \"{name}\""))
+}
+
+/// A small number, or `None` when absent or `null`.
+fn optional_small(value: &Json, name: &str) -> Result<Option<u64>, String> {
+    match value.get(name) {
+        None | Some(Json::Null) => Ok(None),
+        Some(item) => item
+            .as_number()
+            .and_then(|text| text.parse().ok())
+            .map(Some)
+            .ok_or_else(|| format!("\"{name}\" is not a small number")),
+    }
+}
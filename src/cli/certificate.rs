@@ -0,0 +1,347 @@
+//! Every kind of certificate `verify-cert` checks, and their portable JSON
+//! form.
+//!
+//! A certificate document is one JSON object with `"format"` set to
+//! [`FORMAT`], a `"version"`, a `"type"` and the number `"n"` it proves
+//! prime, followed by the members of its type. Every integer is a decimal
+//! string. Readers ignore members they do not know, so later versions may
+//! add some; a change that breaks older readers bumps [`VERSION`]. The
+//! schema is `docs/certificate.schema.json`.
+
+use find_big_prime::{
+    EcppCertificate, EcppStep, Format, MaurerPrime, PocklingtonCertificate, PocklingtonStep,
+    PrattCertificate, shawe_taylor,
+};
+use num_bigint::BigUint;
+use std::collections::BTreeMap;
+
+use super::json::Json;
+use super::primo::PrimoCertificate;
+use super::provable::{Seed, parse_seed};
+use super::verify_cert::{self, CertificateKind};
+
+/// The `"format"` of a certificate document.
+pub const FORMAT: &str = "find-big-prime-certificate";
+
+/// The `"version"` this build writes and the newest it reads.
+pub const VERSION: u64 = 1;
+
+/// A certificate of any kind.
+pub enum Certificate {
+    /// A Pocklington certificate, whose factors are taken on trust.
+    Pocklington(PocklingtonCertificate),
+    Pratt(PrattCertificate),
+    Ecpp(EcppCertificate),
+    /// A Shawe-Taylor prime, checked by regenerating it from its seed.
+    ShaweTaylor {
+        n: BigUint,
+        seed: Seed,
+        counter: u64,
+    },
+    Maurer(MaurerPrime),
+    /// A certificate from Primo, which has no JSON form.
+    Primo(PrimoCertificate),
+}
+
+/// How a certificate came out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The certificate proves the number prime.
+    Prime,
+    /// A Pocklington certificate holds, taking its factors' primality on trust.
+    PrimeGivenFactors,
+    /// The certificate does not hold.
+    Invalid,
+}
+
+impl Verdict {
+    fn new(valid: bool) -> Self {
+        if valid {
+            Verdict::Prime
+        } else {
+            Verdict::Invalid
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Verdict::Prime => "prime",
+            Verdict::PrimeGivenFactors => "prime_given_factors",
+            Verdict::Invalid => "invalid",
+        }
+    }
+}
+
+impl Certificate {
+    /// The `"type"` of the certificate, as `verify-cert` reports it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Certificate::Pocklington(_) => "pocklington",
+            Certificate::Pratt(_) => CertificateKind::Pratt.name(),
+            Certificate::Ecpp(_) => CertificateKind::Ecpp.name(),
+            Certificate::ShaweTaylor { .. } => "shawe-taylor",
+            Certificate::Maurer(_) => "maurer",
+            Certificate::Primo(_) => "primo",
+        }
+    }
+
+    /// The number the certificate proves prime.
+    pub fn n(&self) -> &BigUint {
+        match self {
+            Certificate::Pocklington(certificate) => &certificate.n,
+            Certificate::Pratt(certificate) => &certificate.n,
+            Certificate::Ecpp(certificate) => &certificate.n,
+            Certificate::ShaweTaylor { n, .. } => n,
+            Certificate::Maurer(certificate) => &certificate.prime,
+            Certificate::Primo(certificate) => &certificate.n,
+        }
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        match self {
+            Certificate::Pocklington(certificate) => {
+                if certificate.verify() {
+                    Verdict::PrimeGivenFactors
+                } else {
+                    Verdict::Invalid
+                }
+            }
+            Certificate::Pratt(certificate) => Verdict::new(certificate.verify()),
+            Certificate::Ecpp(certificate) => Verdict::new(certificate.verify()),
+            Certificate::ShaweTaylor { n, seed, counter } => Verdict::new(
+                shawe_taylor(n.bits(), &seed.0)
+                    .is_some_and(|built| built.prime == *n && built.prime_gen_counter == *counter),
+            ),
+            Certificate::Maurer(certificate) => Verdict::new(certificate.verify()),
+            Certificate::Primo(certificate) => Verdict::new(certificate.verify()),
+        }
+    }
+
+    /// The certificate as a one-line document, or `None` for Primo's, which
+    /// `verify-cert --json` refuses up front.
+    pub fn to_json(&self) -> Option<String> {
+        let members = match self {
+            Certificate::Pocklington(certificate) => format!(
+                "\"factors\":{},\"witness\":\"{}\"",
+                list(&certificate.factors),
+                certificate.witness
+            ),
+            Certificate::Pratt(certificate) => {
+                let steps: Vec<String> = verify_cert::pratt_steps(certificate)
+                    .iter()
+                    .map(|step| {
+                        let factors: Vec<BigUint> =
+                            step.factors.iter().map(|q| q.n.clone()).collect();
+                        format!(
+                            "{{\"p\":\"{}\",\"witness\":\"{}\",\"factors\":{}}}",
+                            step.n,
+                            step.witness,
+                            list(&factors)
+                        )
+                    })
+                    .collect();
+                format!("\"steps\":[{}]", steps.join(","))
+            }
+            Certificate::Ecpp(certificate) => {
+                let steps: Vec<String> = certificate
+                    .steps
+                    .iter()
+                    .map(|step| {
+                        format!(
+                            "{{\"n\":\"{}\",\"a\":\"{}\",\"b\":\"{}\",\"m\":\"{}\",\"q\":\"{}\",\"x\":\"{}\",\"y\":\"{}\"}}",
+                            step.n, step.a, step.b, step.m, step.q, step.x, step.y
+                        )
+                    })
+                    .collect();
+                format!("\"steps\":[{}]", steps.join(","))
+            }
+            Certificate::ShaweTaylor { seed, counter, .. } => {
+                format!(
+                    "\"seed\":\"{}\",\"prime_gen_counter\":{counter}",
+                    seed.hex()
+                )
+            }
+            Certificate::Maurer(certificate) => {
+                let steps: Vec<String> = certificate
+                    .steps
+                    .iter()
+                    .map(|step| {
+                        format!("{{\"r\":\"{}\",\"witness\":\"{}\"}}", step.r, step.witness)
+                    })
+                    .collect();
+                format!(
+                    "\"base\":\"{}\",\"steps\":[{}]",
+                    certificate.base,
+                    steps.join(",")
+                )
+            }
+            Certificate::Primo(_) => return None,
+        };
+        Some(format!(
+            "{{\"format\":\"{FORMAT}\",\"version\":{VERSION},\"type\":\"{}\",\"n\":\"{}\",{members}}}",
+            self.name(),
+            self.n()
+        ))
+    }
+
+    /// Read a certificate document, or a record `generate --json` wrote.
+    pub fn from_json(value: &Json) -> Result<Certificate, String> {
+        match value.get("format").map(|format| format.as_str()) {
+            Some(Some(FORMAT)) => document(value),
+            Some(_) => Err("not a find-big-prime certificate".to_owned()),
+            None => record(value),
+        }
+    }
+}
+
+/// `items` as a JSON array of decimal strings.
+fn list(items: &[BigUint]) -> String {
+    let items: Vec<String> = items.iter().map(|item| format!("\"{item}\"")).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// A certificate document of any version up to [`VERSION`].
+fn document(value: &Json) -> Result<Certificate, String> {
+    let version: u64 = value
+        .get("version")
+        .and_then(Json::as_number)
+        .and_then(|version| version.parse().ok())
+        .ok_or("no \"version\"")?;
+    if version > VERSION {
+        return Err(format!(
+            "version {version} is newer than this build reads ({VERSION})"
+        ));
+    }
+    let n = decimal(value.get("n"), "n")?;
+    let kind = value
+        .get("type")
+        .and_then(Json::as_str)
+        .ok_or("no \"type\"")?;
+    let certificate = match kind {
+        "pocklington" => Certificate::Pocklington(PocklingtonCertificate {
+            n: n.clone(),
+            factors: decimals(value.get("factors"), "factors")?,
+            witness: decimal(value.get("witness"), "witness")?,
+        }),
+        "pratt" => {
+            let mut steps = BTreeMap::new();
+            for step in array(value.get("steps"), "steps")? {
+                let p = decimal(step.get("p"), "p")?;
+                let witness = decimal(step.get("witness"), "witness")?;
+                let factors = decimals(step.get("factors"), "factors")?;
+                if steps.insert(p.clone(), (witness, factors)).is_some() {
+                    return Err(format!("{p} is certified twice"));
+                }
+            }
+            Certificate::Pratt(verify_cert::build(&n, &steps)?)
+        }
+        "ecpp" => {
+            let steps = array(value.get("steps"), "steps")?
+                .iter()
+                .map(|step| {
+                    let field = |name| decimal(step.get(name), name);
+                    Ok(EcppStep {
+                        n: field("n")?,
+                        a: field("a")?,
+                        b: field("b")?,
+                        m: field("m")?,
+                        q: field("q")?,
+                        x: field("x")?,
+                        y: field("y")?,
+                    })
+                })
+                .collect::<Result<_, String>>()?;
+            Certificate::Ecpp(EcppCertificate {
+                n: n.clone(),
+                steps,
+            })
+        }
+        "shawe-taylor" => shawe_taylor_members(value, n.clone())?,
+        "maurer" => maurer_members(value, n.clone())?,
+        other => return Err(format!("unknown certificate type `{other}`")),
+    };
+    Ok(certificate)
+}
+
+/// The certificate in a `generate --json` record: the chain of a Maurer
+/// prime, the seed a Shawe-Taylor prime regenerates from, or the
+/// Pocklington certificate of a safe prime, whose factor is taken on trust.
+fn record(value: &Json) -> Result<Certificate, String> {
+    let prime = decimal(value.get("prime"), "prime")?;
+    match value.get("provable").and_then(Json::as_str) {
+        Some("maurer") => {
+            let certificate = value.get("certificate").ok_or("no \"certificate\"")?;
+            maurer_members(certificate, prime)
+        }
+        Some("shawe-taylor") => shawe_taylor_members(value, prime),
+        Some(method) => Err(format!("unknown provable method `{method}`")),
+        None => {
+            let pocklington = value
+                .get("pocklington")
+                .ok_or("no certificate in the record")?;
+            Ok(Certificate::Pocklington(PocklingtonCertificate {
+                n: prime,
+                factors: decimals(pocklington.get("factors"), "factors")?,
+                witness: decimal(pocklington.get("witness"), "witness")?,
+            }))
+        }
+    }
+}
+
+fn shawe_taylor_members(value: &Json, n: BigUint) -> Result<Certificate, String> {
+    let seed = value
+        .get("seed")
+        .and_then(Json::as_str)
+        .ok_or("no \"seed\"")?;
+    let counter = value
+        .get("prime_gen_counter")
+        .and_then(Json::as_number)
+        .and_then(|counter| counter.parse().ok())
+        .ok_or("no \"prime_gen_counter\"")?;
+    Ok(Certificate::ShaweTaylor {
+        n,
+        seed: parse_seed(seed)?,
+        counter,
+    })
+}
+
+fn maurer_members(value: &Json, prime: BigUint) -> Result<Certificate, String> {
+    let steps = array(value.get("steps"), "steps")?
+        .iter()
+        .map(|step| {
+            Ok(PocklingtonStep {
+                r: decimal(step.get("r"), "r")?,
+                witness: decimal(step.get("witness"), "witness")?,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(Certificate::Maurer(MaurerPrime {
+        prime,
+        base: decimal(value.get("base"), "base")?,
+        steps,
+    }))
+}
+
+fn array<'a>(value: Option<&'a Json>, name: &str) -> Result<&'a [Json], String> {
+    value
+        .and_then(Json::as_array)
+        .ok_or_else(|| format!("no \"{name}\" array"))
+}
+
+/// A member holding a decimal integer. Records written with `--format hex`
+/// or `base64` are refused rather than misread.
+fn decimal(value: Option<&Json>, name: &str) -> Result<BigUint, String> {
+    let text = value
+        .and_then(Json::as_number)
+        .ok_or_else(|| format!("no \"{name}\""))?;
+    Format::Decimal
+        .parse(text)
+        .map_err(|_| format!("\"{name}\" is not decimal; write records with --format decimal"))
+}
+
+fn decimals(value: Option<&Json>, name: &str) -> Result<Vec<BigUint>, String> {
+    array(value, name)?
+        .iter()
+        .map(|item| decimal(Some(item), name))
+        .collect()
+}
@@ -0,0 +1,424 @@
+//! `check` subcommand.
+
+use clap::Args;
+use clap::builder::RangedU64ValueParser;
+use find_big_prime::{
+    Compositeness, DEFAULT_MR_ROUNDS, DETERMINISTIC_MR_BASES, Factorization, FixedBases,
+    ParallelMillerRabin, PrimalityTest, compositeness_witness, compositeness_witness_bases,
+    ecpp_certificate, factorize, fermat_index, is_prime_aks, is_prime_deterministic, lucas_lehmer,
+    lucas_lehmer_riesel, mersenne_exponent, parse_number, pepin, pratt_certificate, proth,
+    proth_form, riesel_form, trial_factor,
+};
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive};
+use rand::rngs::OsRng;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::certificate::Certificate;
+use super::primality::{self, Rounds, TestKind};
+use super::verify_cert::{self, CertificateKind};
+use super::{CliError, EXIT_COMPOSITE, EXIT_PARSE};
+
+/// Steps of Pollard's rho `--factor` spends on each composite part: enough
+/// for factors of about 60 bits.
+pub const RHO_ITERATIONS: u64 = 1 << 30;
+
+/// Steps of Pollard's rho `--certificate` spends on each composite part of
+/// n − 1 and the primes below it: enough for factors of about 45 bits.
+const PRATT_ITERATIONS: u64 = 1 << 22;
+
+/// Options for testing user-supplied numbers.
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    /// Number to test: decimal, 0x-prefixed hex, or base64. Reads stdin when omitted or `-`.
+    #[arg(conflicts_with = "file")]
+    number: Option<String>,
+
+    /// Test every line of FILE (blank lines and `#` comments are skipped).
+    #[arg(long = "file", value_name = "FILE")]
+    file: Option<PathBuf>,
+
+    /// Test stdin line by line, printing each verdict as soon as the line arrives.
+    #[arg(long = "stdin", conflicts_with_all = ["number", "file"])]
+    stdin: bool,
+
+    /// Miller–Rabin rounds to run when testing primality.
+    #[arg(long = "rounds", env = "FBP_ROUNDS", default_value_t = DEFAULT_MR_ROUNDS)]
+    rounds: usize,
+
+    /// Primality test to run, or several separated by commas that must all pass.
+    ///
+    /// Only Miller–Rabin on its own reports a witness for composites, and
+    /// `aks` on its own proves the verdict even for small numbers.
+    #[arg(long = "test", env = "FBP_TEST", value_enum, value_delimiter = ',', default_values_t = [TestKind::MillerRabin])]
+    test: Vec<TestKind>,
+
+    /// Run Miller–Rabin to these bases instead of random ones: `first-K-primes` or a list like `2,3,5,7`.
+    #[arg(long = "witnesses", value_name = "BASES", value_parser = primality::parse_witnesses, conflicts_with = "rounds")]
+    witnesses: Option<FixedBases>,
+
+    /// Split the Miller–Rabin rounds on each number across N threads.
+    ///
+    /// Worth it for numbers of several thousand bits, where one round takes a noticeable time.
+    #[arg(short = 'j', long = "threads", env = "FBP_THREADS", value_name = "N", default_value_t = 1, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    threads: usize,
+
+    /// Test the Mersenne number 2^P - 1 with Lucas–Lehmer.
+    ///
+    /// Inputs of that form are recognized and tested this way automatically.
+    #[arg(long = "mersenne", value_name = "P", conflicts_with_all = ["number", "file", "stdin"])]
+    mersenne: Option<u64>,
+
+    /// Factor composites of any size with Pollard's rho, not just those below 2^64.
+    ///
+    /// Finds prime factors of up to about 60 bits; what is left over is
+    /// reported as `cofactor=...`.
+    #[arg(long = "factor")]
+    factor: bool,
+
+    /// Print a certificate for a prime, for `verify-cert` to check: `pratt` (the default) or `ecpp`.
+    ///
+    /// Pratt needs n − 1, and each p − 1 below it, factored completely, so
+    /// it suits primes up to about a hundred bits, or larger ones whose
+    /// n − 1 is smooth. ECPP proves primes of any size, taking seconds at a
+    /// thousand bits and minutes at a few thousand.
+    #[arg(long = "certificate", value_name = "KIND", value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "pratt", conflicts_with_all = ["file", "stdin"])]
+    certificate: Option<CertificateKind>,
+
+    /// Print a prime's certificate as one portable JSON document instead of the usual lines.
+    #[arg(long = "json", requires = "certificate")]
+    json: bool,
+}
+
+impl CheckArgs {
+    /// Test `n`, and factor it if it is a composite below 2^64 or `--factor`
+    /// is given, or bound the error if it is a probable prime.
+    fn examine(&self, n: &BigUint) -> Outcome {
+        let mut outcome = self.verdict(n);
+        if outcome.prime && outcome.proof.is_none() {
+            // Numbers handed to `check` are not random, so only worst-case bounds apply.
+            outcome.error_log2 = primality::error_log2(
+                &self.test,
+                Rounds::Fixed(self.rounds),
+                self.witnesses.as_ref(),
+                n.bits() as usize,
+                false,
+            );
+        }
+        if !outcome.prime {
+            outcome.factors = if self.factor {
+                Some(factorize(n, RHO_ITERATIONS))
+            } else {
+                n.to_u64().map(small_factorization)
+            }
+            .filter(|f| *f != Factorization::default());
+        }
+        outcome
+    }
+
+    /// Test `n`, using an exact test when it is small or has a special form.
+    fn verdict(&self, n: &BigUint) -> Outcome {
+        if self.test == [TestKind::Aks] {
+            return Outcome::proven(is_prime_aks(n), "aks");
+        }
+        if let Some(prime) = is_prime_deterministic(n) {
+            return Outcome {
+                prime,
+                proof: Some("deterministic-mr"),
+                // The base that failed, so the witness can be checked again.
+                evidence: (!prime)
+                    .then(|| {
+                        compositeness_witness_bases(n, &DETERMINISTIC_MR_BASES.map(BigUint::from))
+                    })
+                    .flatten(),
+                factors: None,
+                error_log2: None,
+            };
+        }
+        if let Some(p) = mersenne_exponent(n) {
+            return Outcome::proven(lucas_lehmer(p), "lucas-lehmer");
+        }
+        if let Some((k, e)) = riesel_form(n) {
+            return Outcome::proven(lucas_lehmer_riesel(&k, e), "llr");
+        }
+        if let Some(m) = fermat_index(n) {
+            return Outcome::proven(pepin(m), "pepin");
+        }
+        if let Some((k, e)) = proth_form(n) {
+            return Outcome::proven(proth(&k, e), "proth");
+        }
+        if primality::is_default(&self.test) {
+            let evidence = match &self.witnesses {
+                Some(witnesses) => witnesses.compositeness_witness(n),
+                None if self.threads > 1 => ParallelMillerRabin::new(self.rounds, self.threads)
+                    .compositeness_witness(n, &mut OsRng),
+                None => compositeness_witness(n, self.rounds),
+            };
+            return Outcome {
+                prime: evidence.is_none(),
+                proof: None,
+                evidence,
+                factors: None,
+                error_log2: None,
+            };
+        }
+        Outcome {
+            prime: primality::stack(
+                &self.test,
+                Rounds::Fixed(self.rounds),
+                self.witnesses.as_ref(),
+                n.bits() as usize,
+            )
+            .is_probable_prime(n, &mut OsRng),
+            proof: None,
+            evidence: None,
+            factors: None,
+            error_log2: None,
+        }
+    }
+}
+
+/// What testing one number found.
+struct Outcome {
+    prime: bool,
+    /// The deterministic test that settled it, if any.
+    proof: Option<&'static str>,
+    evidence: Option<Compositeness>,
+    /// The factorization of a composite, if it was attempted.
+    factors: Option<Factorization>,
+    /// log2 of the bound on the chance a probable prime is composite.
+    error_log2: Option<f64>,
+}
+
+impl Outcome {
+    fn proven(prime: bool, test: &'static str) -> Self {
+        Outcome {
+            prime,
+            proof: Some(test),
+            evidence: None,
+            factors: None,
+            error_log2: None,
+        }
+    }
+
+    /// `verdict=...`, then `test=...` and any evidence as `key=value` fields.
+    fn fields(&self) -> Vec<String> {
+        let verdict = match (self.prime, self.proof) {
+            (false, _) => "composite",
+            (true, Some(_)) => "prime",
+            (true, None) => "probable_prime",
+        };
+        let mut fields = vec![format!("verdict={verdict}")];
+        fields.extend(self.proof.map(|test| format!("test={test}")));
+        fields.extend(self.evidence.as_ref().and_then(evidence_field));
+        fields.extend(self.factors.iter().flat_map(factorization_fields));
+        fields.extend(
+            self.error_log2
+                .map(|log2| format!("error_bound={}", primality::format_error(log2))),
+        );
+        fields
+    }
+}
+
+/// Exits 0 for a probable prime and 1 for a composite; batch modes exit 1 if
+/// any number is composite and with the parse-error code if any line is invalid.
+pub fn run(args: &CheckArgs) -> Result<ExitCode, CliError> {
+    if let Some(warning) = primality::speed_warning(&args.test) {
+        eprintln!("{warning}");
+    }
+    if let Some(path) = &args.file {
+        let reader = BufReader::new(File::open(path)?);
+        return check_lines(reader, args);
+    }
+    if args.stdin {
+        return check_lines(io::stdin().lock(), args);
+    }
+
+    let n = match (args.mersenne, args.number.as_deref()) {
+        (Some(p), _) => (BigUint::one() << p) - BigUint::one(),
+        (None, Some("-") | None) => {
+            let mut buf = String::new();
+            io::stdin().read_to_string(&mut buf)?;
+            parse_number(&buf)?
+        }
+        (None, Some(number)) => parse_number(number)?,
+    };
+
+    let outcome = args.examine(&n);
+    let certificate = args
+        .certificate
+        .filter(|_| outcome.prime)
+        .and_then(|kind| certify(&n, kind));
+    if let (true, Some(certificate)) = (args.json, &certificate) {
+        println!(
+            "{}",
+            certificate
+                .to_json()
+                .expect("check builds JSON-able certificates")
+        );
+    } else {
+        println!("bits={}", n.bits());
+        for field in outcome.fields() {
+            println!("{field}");
+        }
+        if let Some(certificate) = &certificate {
+            let fields = match certificate {
+                Certificate::Pratt(certificate) => verify_cert::pratt_fields(certificate),
+                Certificate::Ecpp(certificate) => verify_cert::ecpp_fields(certificate),
+                _ => unreachable!("check builds Pratt and ECPP certificates"),
+            };
+            for field in fields {
+                println!("{field}");
+            }
+        }
+    }
+    Ok(if outcome.prime {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_COMPOSITE)
+    })
+}
+
+/// A `kind` certificate for the prime `n`, or `None` after saying on
+/// stderr why there is none.
+fn certify(n: &BigUint, kind: CertificateKind) -> Option<Certificate> {
+    let certificate = match kind {
+        CertificateKind::Pratt => pratt_certificate(n, PRATT_ITERATIONS).map(Certificate::Pratt),
+        CertificateKind::Ecpp => ecpp_certificate(n).map(Certificate::Ecpp),
+    };
+    match &certificate {
+        Some(Certificate::Ecpp(ecpp)) if ecpp.steps.is_empty() => {
+            eprintln!("no ECPP certificate: n is small enough for test=deterministic-mr");
+            return None;
+        }
+        Some(_) => {}
+        None if kind == CertificateKind::Pratt => {
+            eprintln!("no Pratt certificate: n − 1 did not factor completely")
+        }
+        None => eprintln!("no ECPP certificate: no curve fit n or a prime below it"),
+    }
+    certificate
+}
+
+/// `divisor=p` or `witness=a` for composites, so the verdict can be
+/// confirmed independently with a division or one modexp.
+fn evidence_field(evidence: &Compositeness) -> Option<String> {
+    match evidence {
+        Compositeness::TooSmall => None,
+        Compositeness::Divisor(p) => Some(format!("divisor={p}")),
+        Compositeness::Witness(a) => Some(format!("witness={a}")),
+    }
+}
+
+/// [`trial_factor`] in the shape [`factorize`] returns, which it matches below 2^64.
+fn small_factorization(n: u64) -> Factorization {
+    Factorization {
+        primes: trial_factor(n)
+            .into_iter()
+            .map(|(p, e)| (BigUint::from(p), e))
+            .collect(),
+        cofactor: None,
+    }
+}
+
+/// `factors=2^3*5*7`, the factorization written as a product of prime
+/// powers, then `cofactor=...` for any part left unsplit.
+pub fn factorization_fields(factorization: &Factorization) -> Vec<String> {
+    let mut fields = Vec::new();
+    if !factorization.primes.is_empty() {
+        fields.push(factors_field(&factorization.primes));
+    }
+    fields.extend(
+        factorization
+            .cofactor
+            .as_ref()
+            .map(|c| format!("cofactor={c}")),
+    );
+    fields
+}
+
+fn factors_field(factors: &[(BigUint, u32)]) -> String {
+    let powers: Vec<String> = factors
+        .iter()
+        .map(|(p, e)| match e {
+            1 => p.to_string(),
+            _ => format!("{p}^{e}"),
+        })
+        .collect();
+    format!("factors={}", powers.join("*"))
+}
+
+/// Test one number per line, printing a verdict per line and a summary.
+///
+/// Stdout is line-buffered, so each verdict reaches a pipe as soon as it is printed.
+fn check_lines<R: BufRead>(reader: R, args: &CheckArgs) -> Result<ExitCode, CliError> {
+    let (mut primes, mut composites, mut invalid) = (0u64, 0u64, 0u64);
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let line_no = index + 1;
+        match parse_number(trimmed) {
+            Ok(n) => {
+                let outcome = args.examine(&n);
+                if outcome.prime {
+                    primes += 1;
+                } else {
+                    composites += 1;
+                }
+                println!(
+                    "line={line_no} bits={} {}",
+                    n.bits(),
+                    outcome.fields().join(" ")
+                );
+            }
+            Err(err) => {
+                invalid += 1;
+                println!("line={line_no} error=\"{err}\"");
+            }
+        }
+    }
+    println!(
+        "total={} probable_prime={primes} composite={composites} invalid={invalid}",
+        primes + composites + invalid
+    );
+    Ok(if invalid > 0 {
+        ExitCode::from(EXIT_PARSE)
+    } else if composites > 0 {
+        ExitCode::from(EXIT_COMPOSITE)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Command, FromArgMatches};
+
+    fn check(number: &str) -> Outcome {
+        let matches =
+            CheckArgs::augment_args(Command::new("check")).get_matches_from(["check", number]);
+        let args = CheckArgs::from_arg_matches(&matches).unwrap();
+        args.examine(&parse_number(number).unwrap())
+    }
+
+    #[test]
+    fn deterministic_mr_names_the_failing_base() {
+        // Strong pseudoprimes to the bases 2 to 7 and 2 to 23.
+        for (n, base) in [("3215031751", 11u32), ("3825123056546413051", 37)] {
+            let outcome = check(n);
+            assert!(!outcome.prime, "{n}");
+            assert_eq!(outcome.proof, Some("deterministic-mr"));
+            assert_eq!(
+                outcome.evidence,
+                Some(Compositeness::Witness(BigUint::from(base)))
+            );
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! `completions` subcommand.
+
+use clap::{Args, CommandFactory};
+use clap_complete::Shell;
+use std::io;
+use std::process::ExitCode;
+
+use super::{Cli, CliError};
+
+/// Options for printing a shell completion script.
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    /// Shell to generate completions for.
+    #[arg(value_enum)]
+    shell: Shell,
+}
+
+/// Write the completion script for `args.shell` to stdout.
+pub fn run(args: &CompletionsArgs) -> Result<ExitCode, CliError> {
+    let mut command = Cli::command();
+    let name = command.get_name().to_owned();
+    clap_complete::generate(args.shell, &mut command, name, &mut io::stdout());
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,88 @@
+//! `find-big-prime.toml` configuration files.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::CliError;
+use super::output::OutputFormat;
+use super::primality::TestKind;
+
+/// Config file read from the working directory when `--config` is not given.
+pub const DEFAULT_CONFIG: &str = "find-big-prime.toml";
+
+/// Defaults for `generate`; any flag given on the command line wins.
+///
+/// ```toml
+/// bits = 3072
+/// rounds = 64
+/// test = "bpsw"
+/// safe = true
+/// format = "hex"
+/// timeout = "10m"
+/// threads = 4
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub bits: Option<OneOrMany<usize>>,
+    pub rounds: Option<usize>,
+    pub error_bound: Option<String>,
+    pub test: Option<OneOrMany<TestKind>>,
+    pub safe: Option<bool>,
+    pub count: Option<u64>,
+    pub format: Option<OutputFormat>,
+    pub json: Option<bool>,
+    pub timeout: Option<String>,
+    pub max_attempts: Option<u64>,
+    pub threads: Option<usize>,
+    /// Where the config was read from, for error messages.
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+/// A single value or a list, e.g. `bits = 2048` or `bits = [2048, 3072, 4096]`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T: Clone> OneOrMany<T> {
+    pub fn to_vec(&self) -> Vec<T> {
+        match self {
+            OneOrMany::One(value) => vec![value.clone()],
+            OneOrMany::Many(values) => values.clone(),
+        }
+    }
+}
+
+impl Config {
+    /// Load `path`, or [`DEFAULT_CONFIG`] if it exists when `path` is `None`.
+    pub fn load(path: Option<&Path>) -> Result<Config, CliError> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(DEFAULT_CONFIG).exists() => Path::new(DEFAULT_CONFIG),
+            None => return Ok(Config::default()),
+        };
+        let text =
+            fs::read_to_string(path).map_err(|err| Config::error_at(path, err.to_string()))?;
+        let mut config: Config =
+            toml::from_str(&text).map_err(|err| Config::error_at(path, err.message()))?;
+        config.path = path.to_owned();
+        Ok(config)
+    }
+
+    /// A [`CliError::Config`] for an invalid value in this file.
+    pub fn error(&self, message: impl Into<String>) -> CliError {
+        Config::error_at(&self.path, message)
+    }
+
+    fn error_at(path: &Path, message: impl Into<String>) -> CliError {
+        CliError::Config {
+            path: path.to_owned(),
+            message: message.into(),
+        }
+    }
+}
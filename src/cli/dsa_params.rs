@@ -0,0 +1,105 @@
+//! `dsa-params` subcommand.
+
+use clap::Args;
+use clap::error::ErrorKind;
+use find_big_prime::{
+    DomainParameters, canonical_generator, generate_domain_parameters, is_approved_size,
+    primes_from_seed,
+};
+use rand::rngs::OsRng;
+use std::process::ExitCode;
+
+use super::CliError;
+use super::provable::{self, Seed};
+
+/// Options for generating verifiable DSA domain parameters.
+#[derive(Args, Debug)]
+pub struct DsaParamsArgs {
+    /// Bits of the modulus p (L).
+    #[arg(short = 'L', long = "p-bits", value_name = "L", default_value_t = 2048)]
+    p_bits: u64,
+
+    /// Bits of the subgroup order q (N); FIPS 186-4 approves (L, N) of (1024, 160), (2048, 224),
+    /// (2048, 256) and (3072, 256).
+    #[arg(short = 'N', long = "q-bits", value_name = "N", default_value_t = 256)]
+    q_bits: u64,
+
+    /// Bits of each fresh domain_parameter_seed; at least N (the default) and a multiple of 8.
+    #[arg(long = "seedlen", value_name = "BITS")]
+    seedlen: Option<u64>,
+
+    /// Derive p and q from this hex domain_parameter_seed instead of a fresh one.
+    ///
+    /// Fails if the seed's q is composite or no p turns up, as the standard
+    /// then calls for another seed.
+    #[arg(long = "seed", value_name = "HEX", value_parser = provable::parse_seed, conflicts_with = "seedlen")]
+    seed: Option<Seed>,
+
+    /// The index g is derived with (FIPS 186-4 A.2.3), telling apart generators for different uses.
+    #[arg(long = "index", value_name = "0-255", default_value_t = 1)]
+    index: u8,
+
+    /// Print one JSON object instead of `key=value` lines.
+    #[arg(long = "json")]
+    json: bool,
+}
+
+/// Print p, q and g with the domain_parameter_seed, counter and index that
+/// re-derive them by FIPS 186-4 A.1.1.2 and A.2.3.
+pub fn run(args: &DsaParamsArgs) -> Result<ExitCode, CliError> {
+    let (l, n) = (args.p_bits, args.q_bits);
+    if !is_approved_size(l, n) {
+        clap::Error::raw(
+            ErrorKind::ValueValidation,
+            format!(
+                "(L, N) = ({l}, {n}) is not approved; use (1024, 160), (2048, 224), (2048, 256) or (3072, 256)\n"
+            ),
+        )
+        .exit();
+    }
+    let seedlen = args
+        .seed
+        .as_ref()
+        .map_or(args.seedlen.unwrap_or(n), |seed| seed.0.len() as u64 * 8);
+    if seedlen < n || !seedlen.is_multiple_of(8) {
+        clap::Error::raw(
+            ErrorKind::ValueValidation,
+            format!("the seed must be a whole number of bytes of at least N = {n} bits\n"),
+        )
+        .exit();
+    }
+
+    let params = match &args.seed {
+        Some(seed) => {
+            let (p, q, counter) =
+                primes_from_seed(l, n, &seed.0, &mut OsRng).ok_or(CliError::SeedExhausted)?;
+            let g =
+                canonical_generator(&p, &q, &seed.0, args.index).ok_or(CliError::SeedExhausted)?;
+            DomainParameters {
+                p,
+                q,
+                g,
+                domain_parameter_seed: seed.0.clone(),
+                counter,
+                index: args.index,
+            }
+        }
+        None => generate_domain_parameters(l, n, seedlen, args.index, &mut OsRng)
+            .expect("sizes checked above"),
+    };
+    let seed = Seed(params.domain_parameter_seed).hex();
+    if args.json {
+        println!(
+            "{{\"p\":\"{}\",\"q\":\"{}\",\"g\":\"{}\",\"domain_parameter_seed\":\"{seed}\",\"counter\":{},\"index\":{}}}",
+            params.p, params.q, params.g, params.counter, params.index
+        );
+    } else {
+        println!("p={}", params.p);
+        println!("q={}", params.q);
+        println!("g={}", params.g);
+        println!("domain_parameter_seed={seed}");
+        println!("counter={}", params.counter);
+        println!("index={}", params.index);
+    }
+    Ok(ExitCode::SUCCESS)
+}
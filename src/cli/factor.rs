@@ -0,0 +1,75 @@
+//! `factor` subcommand.
+
+use clap::Args;
+use find_big_prime::{ecm, factorize, factorize_with, parse_number, pollard_p_minus_1};
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use super::CliError;
+use super::check::{RHO_ITERATIONS, factorization_fields};
+
+/// Default `--bound` for `--p1`.
+const P1_BOUND: u64 = 1_000_000;
+
+/// Default `--bound` for `--ecm`, suited to factors of about 25 digits.
+const ECM_BOUND: u64 = 50_000;
+
+/// Options for factoring a user-supplied number.
+#[derive(Args, Debug)]
+pub struct FactorArgs {
+    /// Number to factor: decimal, 0x-prefixed hex, or base64. Reads stdin when omitted or `-`.
+    number: Option<String>,
+
+    /// Split with Pollard's p − 1 instead of Pollard's rho.
+    ///
+    /// Finds the prime factors p whose p − 1 is `--bound`-smooth, however large p is.
+    #[arg(long = "p1", conflicts_with = "ecm")]
+    p1: bool,
+
+    /// Split with the elliptic-curve method instead of Pollard's rho.
+    ///
+    /// Digs out factors of 20–30 digits, e.g. from p − 1 or p + 1 of an
+    /// externally supplied prime; raise `--bound` and `--curves` for larger ones.
+    #[arg(long = "ecm")]
+    ecm: bool,
+
+    /// Smoothness bound for `--p1` (default 1000000) or `--ecm` (default 50000).
+    #[arg(long = "bound", value_name = "B")]
+    bound: Option<u64>,
+
+    /// Curves `--ecm` tries on each composite part before giving up.
+    #[arg(
+        long = "curves",
+        value_name = "N",
+        default_value_t = 500,
+        requires = "ecm"
+    )]
+    curves: u64,
+}
+
+/// Print `bits=`, then `factors=` and any `cofactor=` that could not be split.
+pub fn run(args: &FactorArgs) -> Result<ExitCode, CliError> {
+    let n = match args.number.as_deref() {
+        Some("-") | None => {
+            let mut buf = String::new();
+            io::stdin().read_to_string(&mut buf)?;
+            parse_number(&buf)?
+        }
+        Some(number) => parse_number(number)?,
+    };
+
+    let factorization = if args.p1 {
+        let bound = args.bound.unwrap_or(P1_BOUND);
+        factorize_with(&n, |part| pollard_p_minus_1(part, bound))
+    } else if args.ecm {
+        let bound = args.bound.unwrap_or(ECM_BOUND);
+        factorize_with(&n, |part| ecm(part, bound, args.curves))
+    } else {
+        factorize(&n, RHO_ITERATIONS)
+    };
+    println!("bits={}", n.bits());
+    for field in factorization_fields(&factorization) {
+        println!("{field}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
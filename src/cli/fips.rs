@@ -0,0 +1,147 @@
+//! `--fips`: power-on self-tests, run before any command as a validated
+//! module must run them, refusing to go on if one fails.
+//!
+//! The crate has no DRBG of its own; randomness comes straight from the
+//! operating system. So besides known answers for SHA-256, which drives
+//! the seeded constructions, and for the primality tests, the RNG gets the
+//! stuck-output health check of FIPS 140-2 4.9.2.
+
+use find_big_prime::{
+    BailliePsw, FixedBases, PrimalityTest, compositeness_witness_bases,
+    compositeness_witness_with_rng, is_prime_deterministic, is_strong_lucas_probable_prime,
+    pi_scaled, pocklington_certificate,
+};
+use num_bigint::BigUint;
+use num_traits::One;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use std::process::ExitCode;
+
+use super::EXIT_SELFTEST;
+use super::primality::{self, Rounds, TestKind};
+use super::selftest;
+
+/// SHA-256 of "abc" and of the empty message, from FIPS 180-4's examples.
+const SHA256_KATS: [(&[u8], &str); 2] = [
+    (
+        b"abc",
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    ),
+    (
+        b"",
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    ),
+];
+
+/// ⌊2^62 · π⌋, the first 64 bits of the RFC 2412 primes after the ones.
+const PI_62: u64 = 0xc90f_daa2_2168_c234;
+
+/// A self-test: its name, and whether it passed.
+type SelfTest = (&'static str, fn() -> bool);
+
+/// Every self-test, in the order they run.
+const TESTS: [SelfTest; 8] = [
+    ("sha256", sha256),
+    ("miller-rabin-bases", miller_rabin_bases),
+    ("miller-rabin-random", miller_rabin_random),
+    ("miller-rabin-corpus", miller_rabin_corpus),
+    ("lucas", lucas),
+    ("pocklington", pocklington),
+    ("pi", pi),
+    ("os-rng", os_rng),
+];
+
+/// Run every self-test, printing each failure to stderr. `Err` with the
+/// selftest exit code if any failed.
+pub fn power_on() -> Result<(), ExitCode> {
+    let failed: Vec<&str> = TESTS
+        .iter()
+        .filter(|(_, test)| !test())
+        .map(|&(name, _)| name)
+        .collect();
+    if failed.is_empty() {
+        tracing::info!(tests = TESTS.len(), "power-on self-tests passed");
+        return Ok(());
+    }
+    for name in &failed {
+        eprintln!("fips: power-on self-test {name} failed");
+    }
+    eprintln!("error: refusing to run after a failed self-test");
+    Err(ExitCode::from(EXIT_SELFTEST))
+}
+
+fn sha256() -> bool {
+    SHA256_KATS.iter().all(|&(message, digest)| {
+        let hex: String = Sha256::digest(message)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        hex == digest
+    })
+}
+
+/// 318665857834031151167461 is a strong pseudoprime to the 12 prime
+/// bases up to 37 but not to 41, which the deterministic test uses.
+fn miller_rabin_bases() -> bool {
+    let psp = BigUint::from(318_665_857_834_031_151_167_461u128);
+    let bases = FixedBases::first_primes(12);
+    compositeness_witness_bases(&psp, bases.bases()).is_none()
+        && compositeness_witness_bases(&psp, &[BigUint::from(41u32)]).is_some()
+        && is_prime_deterministic(&psp) == Some(false)
+}
+
+/// A Carmichael number is found composite and 2^127 − 1 passes, with
+/// random bases from the OS RNG.
+fn miller_rabin_random() -> bool {
+    let carmichael = BigUint::from(41_041u32);
+    let prime = (BigUint::one() << 127u32) - 1u32;
+    compositeness_witness_with_rng(&carmichael, 40, &mut OsRng).is_some()
+        && compositeness_witness_with_rng(&prime, 40, &mut OsRng).is_none()
+}
+
+/// The `selftest` corpus under the default Miller–Rabin.
+fn miller_rabin_corpus() -> bool {
+    let tests = [TestKind::MillerRabin];
+    selftest::corpora().iter().all(|corpus| {
+        let expected = corpus.expected(&tests);
+        corpus.numbers.iter().all(|n| {
+            primality::stack(&tests, Rounds::Default, None, n.bits() as usize)
+                .is_probable_prime(n, &mut OsRng)
+                == expected
+        })
+    })
+}
+
+/// 5459 is a strong Lucas pseudoprime that Baillie–PSW still rejects.
+fn lucas() -> bool {
+    let n = BigUint::from(5459u32);
+    let prime = (BigUint::one() << 89u32) - 1u32;
+    is_strong_lucas_probable_prime(&n)
+        && !BailliePsw.is_probable_prime(&n, &mut OsRng)
+        && BailliePsw.is_probable_prime(&prime, &mut OsRng)
+}
+
+/// The safe prime 1019 = 2 · 509 + 1 is certified by 509, and its
+/// certificate stops holding for 1021.
+fn pocklington() -> bool {
+    let q = BigUint::from(509u32);
+    pocklington_certificate(&BigUint::from(1019u32), &[q]).is_some_and(|mut certificate| {
+        let valid = certificate.verify();
+        certificate.n = BigUint::from(1021u32);
+        valid && !certificate.verify()
+    })
+}
+
+/// The series behind `modp` gives π's first 64 bits.
+fn pi() -> bool {
+    pi_scaled(62) == BigUint::from(PI_62)
+}
+
+/// Two draws of 32 bytes that are neither all zero nor equal.
+fn os_rng() -> bool {
+    let (mut first, mut second) = ([0u8; 32], [0u8; 32]);
+    OsRng.fill_bytes(&mut first);
+    OsRng.fill_bytes(&mut second);
+    first != second && first != [0; 32] && second != [0; 32]
+}
@@ -0,0 +1,1430 @@
+//! `generate` subcommand.
+
+use clap::builder::{BoolishValueParser, RangedU64ValueParser};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, ColorChoice, ValueEnum};
+use find_big_prime::generator::{DEFAULT_BITS, strong_factor_bits};
+use find_big_prime::{
+    ChainKind, FixedBases, GenerationReport, PocklingtonCertificate, PrimeGenError, PrimeGenerator,
+    ResidueClass, SearchStats, bi_twin_members, nums_prime, parse_number, pocklington_certificate,
+    proth_primes_between, pseudo_mersenne, solinas,
+};
+use num_bigint::BigUint;
+use num_traits::One;
+use rand::rngs::OsRng;
+use std::io::{self, ErrorKind, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::audit::{self, AuditLog};
+use super::bundle::{Bundle, Mode};
+use super::config::Config;
+use super::output::{self, OutputFormat};
+use super::primality::{self, Rounds, TestKind};
+use super::provable::{self, ProvableMethod, Seed};
+use super::{CliError, progress, summary};
+
+/// Smallest bit length the CLI will generate.
+const MIN_CLI_BITS: usize = 512;
+
+/// Options for prime generation.
+#[derive(Args, Debug)]
+pub struct GenerateArgs {
+    /// Number of bits for the generated prime (e.g. 2048, 3072, 4096).
+    ///
+    /// Repeat the flag or separate sizes with commas to generate each size in turn.
+    #[arg(short = 'b', long = "bits", env = "FBP_BITS", value_delimiter = ',', default_values_t = [DEFAULT_BITS])]
+    bits: Vec<usize>,
+
+    /// Permit sizes below 512 bits (for tests and toy protocols), with a warning.
+    #[arg(long = "allow-small", env = "FBP_ALLOW_SMALL", value_parser = BoolishValueParser::new())]
+    allow_small: bool,
+
+    /// Generate a safe prime p where p = 2q + 1 and q is also prime.
+    #[arg(long = "safe", env = "FBP_SAFE", value_parser = BoolishValueParser::new())]
+    safe: bool,
+
+    /// Like `--safe`, but `--bits` sizes the Sophie Germain prime q, which is
+    /// printed in place of p = 2q + 1.
+    #[arg(long = "sophie-germain", conflicts_with = "provable")]
+    sophie_germain: bool,
+
+    /// Generate the smaller prime p of a twin pair, where p + 2 is also
+    /// prime; p + 2 is printed as `twin=`.
+    #[arg(long = "twin", conflicts_with_all = ["safe", "sophie_germain", "provable", "phrase"])]
+    twin: bool,
+
+    /// Generate the first prime p of a Cunningham chain of LEN primes, each
+    /// twice the one before plus one (`--kind 1st`) or minus one (`2nd`).
+    ///
+    /// The other members are printed as `chain_2=`, `chain_3=` and so on.
+    #[arg(long = "chain", value_name = "LEN", value_parser = RangedU64ValueParser::<usize>::new().range(2..), conflicts_with_all = ["safe", "sophie_germain", "twin", "provable", "phrase"])]
+    chain: Option<usize>,
+
+    /// Generate the bottom prime p = n − 1 of a bi-twin chain of LINKS links:
+    /// n ± 1, 2n ± 1, … up to 2^(LINKS−1)·n ± 1, all prime.
+    ///
+    /// The other members are printed as `chain_2=`, `chain_3=` and so on,
+    /// in that order; one link is a twin pair.
+    #[arg(long = "bi-twin", value_name = "LINKS", value_parser = RangedU64ValueParser::<usize>::new().range(1..), conflicts_with_all = ["safe", "sophie_germain", "twin", "chain", "provable", "phrase"])]
+    bi_twin: Option<usize>,
+
+    /// Generate an ANSI X9.31 strong prime p by Gordon's algorithm: p − 1,
+    /// p + 1 and r − 1, for the large factor r of p − 1, each have a prime
+    /// factor of at least BITS bits.
+    ///
+    /// BITS defaults to the X9.31 minimum for a modulus of twice `--bits`:
+    /// 101 up to 512 bits, 141 up to 1024 and 171 above.
+    #[arg(long = "strong", value_name = "BITS", num_args = 0..=1, value_parser = RangedU64ValueParser::<usize>::new().range(2..), conflicts_with_all = ["safe", "sophie_germain", "twin", "chain", "bi_twin", "provable", "phrase"])]
+    strong: Option<Option<usize>>,
+
+    /// Generate a Blum prime, p ≡ 3 (mod 4), for Blum–Blum–Shub, Rabin and
+    /// similar; set on each candidate as it is drawn, at no extra testing.
+    #[arg(long = "blum", conflicts_with_all = ["safe", "sophie_germain", "twin", "chain", "bi_twin", "strong", "provable", "phrase"])]
+    blum: bool,
+
+    /// Generate a prime p ≡ R (mod M), e.g. `--congruent 1:4`, or `R` with
+    /// the modulus from `--modulus`; like `--blum`, each candidate is moved
+    /// into the class as it is drawn rather than tested and dropped.
+    ///
+    /// Repeat it for several classes, e.g. `--congruent 2:3 --congruent
+    /// 1:5`; they are combined into one by the Chinese remainder theorem,
+    /// along with `--blum`'s, so the search costs no more than for one.
+    #[arg(long = "congruent", value_name = "R[:M]", value_parser = parse_congruence, conflicts_with_all = ["safe", "sophie_germain", "twin", "chain", "bi_twin", "strong", "provable", "phrase"])]
+    congruent: Vec<(u64, Option<u64>)>,
+
+    /// Pin the low bits of every prime to SUFFIX, `0x`-prefixed hex or
+    /// `0b`-prefixed binary, all of whose digits count, leading zeros
+    /// included: `--suffix 0x0001` fixes the last 16 bits. It must be odd.
+    ///
+    /// It is a residue class modulo a power of two, combined with any
+    /// `--congruent` and `--blum`.
+    #[arg(long = "suffix", value_name = "SUFFIX", value_parser = parse_suffix, conflicts_with_all = ["safe", "sophie_germain", "twin", "chain", "bi_twin", "strong", "provable", "phrase"])]
+    suffix: Option<ResidueClass>,
+
+    /// The modulus of each `--congruent` given without one; R must be prime
+    /// to it.
+    #[arg(long = "modulus", value_name = "M", value_parser = RangedU64ValueParser::<u64>::new().range(2..), requires = "congruent")]
+    modulus: Option<u64>,
+
+    /// Set the top N bits of every candidate, not just the highest: with 2,
+    /// as OpenSSL does for RSA, the product of two primes of `--bits` bits
+    /// has exactly twice as many.
+    #[arg(long = "top-bits", value_name = "N", default_value_t = 1, value_parser = RangedU64ValueParser::<usize>::new().range(1..), conflicts_with_all = ["twin", "chain", "bi_twin", "strong", "provable", "phrase"])]
+    top_bits: usize,
+
+    /// Pin the leading bits of every prime to PREFIX, `0x`-prefixed hex or
+    /// decimal, from its leading one down: `--prefix 0xdeadbeef` gives
+    /// primes whose hex starts `deadbeef`. The rest are drawn at random.
+    #[arg(long = "prefix", value_name = "PREFIX", value_parser = parse_prefix, conflicts_with_all = ["top_bits", "twin", "chain", "bi_twin", "strong", "provable", "phrase"])]
+    prefix: Option<BigUint>,
+
+    /// Generate a prime with exactly W bits set: the top bit (or the
+    /// `--prefix`) and the lowest, and the rest at random positions between.
+    ///
+    /// Far from half the bits there are few such primes, or none; when a
+    /// few thousand candidates or fewer have the weight each is tried once,
+    /// otherwise pair it with `--timeout` or `--max-attempts`.
+    #[arg(long = "hamming-weight", value_name = "W", value_parser = RangedU64ValueParser::<usize>::new().range(2..), conflicts_with_all = ["safe", "sophie_germain", "twin", "chain", "bi_twin", "strong", "blum", "congruent", "suffix", "provable", "phrase"])]
+    hamming_weight: Option<usize>,
+
+    /// Find the pseudo-Mersenne prime 2^bits − c with the least c, for
+    /// fast modular reduction in field arithmetic; `--count` gives the next
+    /// ones by increasing c.
+    ///
+    /// The search is deterministic, and `--bits` may be below 512 without
+    /// `--allow-small`, since these primes are moduli rather than keys.
+    #[arg(long = "pseudo-mersenne", conflicts_with_all = ["safe", "sophie_germain", "twin", "chain", "bi_twin", "strong", "blum", "congruent", "suffix", "prefix", "top_bits", "hamming_weight", "provable", "phrase", "bundle"])]
+    pseudo_mersenne: bool,
+
+    /// Find generalized Mersenne (Solinas) primes 2^bits ± 2^e ± … ± 1 of
+    /// TERMS powers of two, 3 by default, printing the expression found.
+    ///
+    /// Like `--pseudo-mersenne` the search is deterministic: by the middle
+    /// exponents from the smallest up, then by signs.
+    #[arg(long = "solinas", value_name = "TERMS", num_args = 0..=1, default_missing_value = "3", value_parser = RangedU64ValueParser::<usize>::new().range(2..=8), conflicts_with_all = ["safe", "sophie_germain", "twin", "chain", "bi_twin", "strong", "blum", "congruent", "suffix", "prefix", "top_bits", "hamming_weight", "provable", "phrase", "bundle", "pseudo_mersenne"])]
+    solinas: Option<usize>,
+
+    /// Find every Proth prime k·2^N + 1 with odd k up to `--k-max`, for
+    /// NTT moduli and Fermat-divisor searches; each is proven by Proth's
+    /// theorem, and printed as it is found. `--bits` and `--count` do not
+    /// apply.
+    #[arg(long = "proth", requires_all = ["proth_n", "k_max"], conflicts_with_all = ["bits", "count", "continuous", "safe", "sophie_germain", "twin", "chain", "bi_twin", "strong", "blum", "congruent", "suffix", "prefix", "top_bits", "hamming_weight", "provable", "phrase", "bundle", "pseudo_mersenne", "solinas"])]
+    proth: bool,
+
+    /// The exponent N of `--proth`.
+    #[arg(long = "n", value_name = "N", value_parser = RangedU64ValueParser::<u64>::new().range(1..), requires = "proth")]
+    proth_n: Option<u64>,
+
+    /// The largest multiplier k `--proth` sieves, below 2^N.
+    #[arg(long = "k-max", value_name = "K", value_parser = RangedU64ValueParser::<u64>::new().range(1..), requires = "proth")]
+    k_max: Option<u64>,
+
+    /// Which kind of Cunningham chain `--chain` searches for.
+    #[arg(long = "kind", value_enum, default_value_t = ChainArg::First, requires = "chain")]
+    kind: ChainArg,
+
+    /// Miller–Rabin rounds to run when testing primality.
+    ///
+    /// Defaults to the fewest that keep the error below 2^-128 for random
+    /// candidates of each size (e.g. 3 at 2048 bits).
+    #[arg(long = "rounds", env = "FBP_ROUNDS", conflicts_with = "error_bound")]
+    rounds: Option<usize>,
+
+    /// Run enough rounds to keep the error probability below this, e.g. `2^-128` (or just `128`).
+    ///
+    /// Miller–Rabin uses average-case bounds for random candidates of each size;
+    /// Solovay–Strassen and Frobenius use their worst-case per-round bounds.
+    #[arg(long = "error-bound", env = "FBP_ERROR_BOUND", value_name = "2^-K", value_parser = parse_error_bound)]
+    error_bound: Option<u32>,
+
+    /// Run Miller–Rabin to these bases instead of random ones: `first-K-primes` or a list like `2,3,5,7`.
+    #[arg(long = "witnesses", value_name = "BASES", value_parser = primality::parse_witnesses, conflicts_with_all = ["rounds", "error_bound"])]
+    witnesses: Option<FixedBases>,
+
+    /// Primality test applied to each candidate.
+    ///
+    /// Separate several with commas (e.g. `miller-rabin,solovay`) to require all of them.
+    #[arg(long = "test", env = "FBP_TEST", value_enum, value_delimiter = ',', default_values_t = [TestKind::MillerRabin])]
+    test: Vec<TestKind>,
+
+    /// Screen candidates with only N Miller–Rabin rounds, then run the full test on the survivor.
+    ///
+    /// The result is still checked with every round and `--test`; this mainly speeds up `--safe`.
+    #[arg(long = "screen", env = "FBP_SCREEN", value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    screen: Option<usize>,
+
+    /// Number of primes to generate with the same settings.
+    #[arg(short = 'n', long = "count", env = "FBP_COUNT", default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    count: u64,
+
+    /// Keep generating until interrupted or stdout is closed.
+    #[arg(long = "continuous", conflicts_with = "count")]
+    continuous: bool,
+
+    /// Write the prime(s) to FILE instead of stdout.
+    ///
+    /// The file is replaced atomically and, on Unix, readable only by its owner.
+    #[arg(
+        short = 'o',
+        long = "out",
+        value_name = "FILE",
+        conflicts_with = "continuous"
+    )]
+    out: Option<PathBuf>,
+
+    /// Output encoding; `raw` writes fixed-width big-endian bytes with no header.
+    #[arg(short = 'f', long = "format", env = "FBP_FORMAT", value_enum, default_value_t = OutputFormat::Decimal)]
+    format: OutputFormat,
+
+    /// Print one JSON object per prime with its search statistics.
+    #[arg(long = "json", env = "FBP_JSON", value_parser = BoolishValueParser::new())]
+    json: bool,
+
+    /// Print only the prime(s): no header line and no hints. Errors still go to stderr.
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Show a progress bar with candidate rate and an ETA on stderr.
+    #[arg(long = "progress", conflicts_with = "quiet")]
+    progress: bool,
+
+    /// Give up if one prime takes longer than this (e.g. `90`, `30s`, `5m`, `1h`).
+    #[arg(long = "timeout", env = "FBP_TIMEOUT", value_name = "DURATION", value_parser = parse_duration)]
+    timeout: Option<Duration>,
+
+    /// Give up after drawing this many candidates for one prime.
+    #[arg(long = "max-attempts", env = "FBP_MAX_ATTEMPTS", value_name = "N")]
+    max_attempts: Option<u64>,
+
+    /// Worker threads for the search (default: all available cores).
+    #[arg(short = 'j', long = "threads", env = "FBP_THREADS", value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    threads: Option<usize>,
+
+    /// Print candidate, trial-division, Miller–Rabin and timing totals to stderr.
+    #[arg(long = "stats")]
+    stats: bool,
+
+    /// Build a provable prime from a seed instead of searching for a probable one.
+    ///
+    /// `shawe-taylor` (the default) is the FIPS 186-4 C.6 construction; the
+    /// seed printed with the prime regenerates it, and further primes
+    /// continue from the seed the previous one left. `maurer` draws nearly
+    /// uniform primes and prints the certificate that proves them.
+    #[arg(long = "provable", value_name = "METHOD", value_enum, num_args = 0..=1, default_missing_value = "shawe-taylor", conflicts_with_all = ["safe", "test", "rounds", "error_bound", "witnesses", "screen", "continuous"])]
+    provable: Option<ProvableMethod>,
+
+    /// Derive the prime from this public phrase instead of the RNG, so anyone can re-derive it.
+    ///
+    /// Candidates are SHA-256 of the phrase and an incrementing counter; the
+    /// first to pass Baillie–PSW (with its half for `--safe`) is printed
+    /// with the counter it turned up at. Further primes continue after it.
+    #[arg(long = "phrase", value_name = "TEXT", conflicts_with_all = ["provable", "test", "rounds", "error_bound", "witnesses", "screen", "continuous"])]
+    phrase: Option<String>,
+
+    /// Hex seed for `--provable` (default: 32 bytes from the OS RNG).
+    #[arg(long = "seed", value_name = "HEX", value_parser = provable::parse_seed, requires = "provable")]
+    seed: Option<Seed>,
+
+    /// Print a Pocklington certificate with each `--provable` prime: the
+    /// prime factors of p − 1 it rests on and a witness. `--safe` primes
+    /// always carry one.
+    #[arg(long = "certificate")]
+    certificate: bool,
+
+    /// Print a colored summary with the prime wrapped at 64 columns; `auto`
+    /// does so only when stdout is a terminal.
+    #[arg(long = "color", value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Append a JSON-lines record of the run to FILE: RNG and seed handling,
+    /// how every candidate was rejected, the rounds it got, and timestamps.
+    ///
+    /// Primes and seeds are logged only as SHA-256 fingerprints.
+    #[arg(long = "audit-log", value_name = "FILE")]
+    audit_log: Option<PathBuf>,
+
+    /// Also write a reproducibility bundle to FILE for `reproduce`: the
+    /// version, every setting, the seed or phrase of the seeded modes, and
+    /// the primes.
+    #[arg(long = "bundle", value_name = "FILE", conflicts_with = "continuous")]
+    bundle: Option<PathBuf>,
+}
+
+/// The kinds of Cunningham chain, as `--kind` names them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ChainArg {
+    /// p, 2p + 1, 4p + 3, …
+    #[value(name = "1st")]
+    First,
+    /// p, 2p − 1, 4p − 3, …
+    #[value(name = "2nd")]
+    Second,
+}
+
+impl ChainArg {
+    fn kind(self) -> ChainKind {
+        match self {
+            ChainArg::First => ChainKind::First,
+            ChainArg::Second => ChainKind::Second,
+        }
+    }
+}
+
+/// One worker per available core.
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Parse `2^-K` or a bare `K`, returning K.
+fn parse_error_bound(s: &str) -> Result<u32, String> {
+    let s = s.trim();
+    let k = s.strip_prefix("2^-").unwrap_or(s);
+    match k.parse() {
+        Ok(0) => Err("the error bound must be below 1".to_owned()),
+        Ok(k) => Ok(k),
+        Err(_) => Err(format!("`{s}` is not of the form 2^-K")),
+    }
+}
+
+/// Parse `R` or `R:M` for `--congruent`.
+fn parse_congruence(s: &str) -> Result<(u64, Option<u64>), String> {
+    let number = |text: &str| {
+        text.trim()
+            .parse::<u64>()
+            .map_err(|_| format!("`{text}` is not a number"))
+    };
+    match s.split_once(':') {
+        None => Ok((number(s)?, None)),
+        Some((residue, modulus)) => match number(modulus)? {
+            0 | 1 => Err(format!("the modulus in `{s}` must be at least 2")),
+            modulus => Ok((number(residue)?, Some(modulus))),
+        },
+    }
+}
+
+/// Parse `--prefix`: a nonzero number in hex or decimal.
+fn parse_prefix(s: &str) -> Result<BigUint, String> {
+    match parse_number(s) {
+        Ok(prefix) if prefix.bits() == 0 => Err("the prefix must not be zero".to_owned()),
+        Ok(prefix) => Ok(prefix),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Parse `--suffix`: odd hex or binary digits, each of which counts.
+fn parse_suffix(s: &str) -> Result<ResidueClass, String> {
+    let s = s.trim();
+    let (digits, radix, width) = if let Some(digits) = s.strip_prefix("0x") {
+        (digits, 16, 4)
+    } else if let Some(digits) = s.strip_prefix("0b") {
+        (digits, 2, 1)
+    } else {
+        return Err(format!("`{s}` is neither 0x hex nor 0b binary"));
+    };
+    let suffix = BigUint::parse_bytes(digits.as_bytes(), radix)
+        .ok_or_else(|| format!("`{s}` is not a number"))?;
+    ResidueClass::suffix(suffix, digits.len() * width)
+        .ok_or_else(|| format!("`{s}` is even, so no prime above 2 ends in it"))
+}
+
+/// Parse seconds, optionally suffixed with `ms`, `s`, `m` or `h`.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (digits, unit) = s.split_at(split);
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| format!("`{s}` is not a duration"))?;
+    let secs = match unit {
+        "ms" => return Ok(Duration::from_millis(value)),
+        "" | "s" => value,
+        "m" => value.saturating_mul(60),
+        "h" => value.saturating_mul(3600),
+        other => return Err(format!("unknown duration unit `{other}`")),
+    };
+    Ok(Duration::from_secs(secs))
+}
+
+impl GenerateArgs {
+    /// Take each setting from `config` unless it was given on the command line.
+    pub fn apply_config(&mut self, config: &Config, matches: &ArgMatches) -> Result<(), CliError> {
+        let unset = |id: &str| {
+            matches
+                .value_source(id)
+                .is_none_or(|source| source == ValueSource::DefaultValue)
+        };
+        if let Some(bits) = config.bits.as_ref().filter(|_| unset("bits")) {
+            self.bits = bits.to_vec();
+        }
+        let rounds_unset = unset("rounds") && unset("error_bound");
+        if let Some(rounds) = config.rounds.filter(|_| rounds_unset) {
+            self.rounds = Some(rounds);
+        }
+        if let Some(bound) = config.error_bound.as_deref().filter(|_| rounds_unset) {
+            self.error_bound = Some(parse_error_bound(bound).map_err(|err| config.error(err))?);
+        }
+        if let Some(test) = config.test.as_ref().filter(|_| unset("test")) {
+            self.test = test.to_vec();
+        }
+        if let Some(safe) = config.safe.filter(|_| unset("safe")) {
+            self.safe = safe;
+        }
+        if let Some(count) = config.count.filter(|_| unset("count")) {
+            if count == 0 {
+                return Err(config.error("count must be at least 1"));
+            }
+            self.count = count;
+        }
+        if let Some(format) = config.format.filter(|_| unset("format")) {
+            self.format = format;
+        }
+        if let Some(json) = config.json.filter(|_| unset("json")) {
+            self.json = json;
+        }
+        if let Some(timeout) = config.timeout.as_deref().filter(|_| unset("timeout")) {
+            self.timeout = Some(parse_duration(timeout).map_err(|err| config.error(err))?);
+        }
+        if let Some(max) = config.max_attempts.filter(|_| unset("max_attempts")) {
+            self.max_attempts = Some(max);
+        }
+        if let Some(threads) = config.threads.filter(|_| unset("threads")) {
+            if threads == 0 {
+                return Err(config.error("threads must be at least 1"));
+            }
+            self.threads = Some(threads);
+        }
+        Ok(())
+    }
+
+    /// Round counts from `--rounds` or `--error-bound`.
+    fn rounds(&self) -> Rounds {
+        match (self.rounds, self.error_bound) {
+            (Some(rounds), _) => Rounds::Fixed(rounds),
+            (None, Some(bound)) => Rounds::ErrorBound(bound),
+            (None, None) => Rounds::Default,
+        }
+    }
+
+    /// Whether the primes are safe, by `--safe` or `--sophie-germain`.
+    fn safe(&self) -> bool {
+        self.safe || self.sophie_germain
+    }
+
+    /// Bits of the prime searched for at a `--bits` of `bits`: one more
+    /// under `--sophie-germain`, where `bits` sizes q.
+    fn search_bits(&self, bits: usize) -> usize {
+        bits + usize::from(self.sophie_germain)
+    }
+
+    /// The prime printed for a found `p`: q = (p − 1)/2 under
+    /// `--sophie-germain`, else `p` itself.
+    fn shown(&self, p: &BigUint) -> BigUint {
+        if self.sophie_germain {
+            p >> 1u32
+        } else {
+            p.clone()
+        }
+    }
+
+    /// The one class every `--congruent`, `--suffix` and `--blum` asks for,
+    /// combined by the Chinese remainder theorem. Exits with a usage error
+    /// for a class with no modulus or at most one prime, or for classes
+    /// with no member in common.
+    fn congruence(&self) -> Option<ResidueClass> {
+        if self.congruent.is_empty() && self.suffix.is_none() {
+            return None;
+        }
+        let classes = self.congruent.iter().map(|&(residue, modulus)| {
+            let Some(modulus) = modulus.or(self.modulus) else {
+                clap::Error::raw(
+                    clap::error::ErrorKind::MissingRequiredArgument,
+                    format!("--congruent {residue} needs a modulus: {residue}:M or --modulus M\n"),
+                )
+                .exit();
+            };
+            ResidueClass::new(residue, modulus).unwrap_or_else(|| {
+                clap::Error::raw(
+                    clap::error::ErrorKind::ValueValidation,
+                    format!("--congruent {residue}:{modulus}: {residue} shares a factor with {modulus}\n"),
+                )
+                .exit()
+            })
+        });
+        self.blum
+            .then(ResidueClass::blum)
+            .into_iter()
+            .chain(self.suffix.clone())
+            .chain(classes)
+            .reduce(|class, next| {
+                class.combine(&next).unwrap_or_else(|| {
+                    clap::Error::raw(
+                        clap::error::ErrorKind::ArgumentConflict,
+                        format!(
+                            "no p is both {} (mod {}) and {} (mod {})\n",
+                            class.residue(),
+                            class.modulus(),
+                            next.residue(),
+                            next.modulus()
+                        ),
+                    )
+                    .exit()
+                })
+            })
+    }
+
+    /// The special form searched for deterministically, if one is.
+    fn special_form(&self) -> Option<&'static str> {
+        if self.pseudo_mersenne {
+            Some("pseudo-mersenne")
+        } else if self.solinas.is_some() {
+            Some("solinas")
+        } else if self.proth {
+            Some("proth")
+        } else {
+            None
+        }
+    }
+
+    /// The output record for `p`, a prime of the special form given by
+    /// `expression`.
+    fn form_record(&self, p: &BigUint, expression: &str) -> Vec<u8> {
+        let form = self.special_form().unwrap_or_default();
+        if self.json {
+            output::form_json_record(p, self.format, form, expression)
+        } else {
+            self.format.encode(p, p.bits() as usize)
+        }
+    }
+
+    /// Bits of the auxiliary primes of a `--strong` prime of `bits` bits.
+    fn strong_factor_bits(&self, bits: usize) -> Option<usize> {
+        self.strong
+            .map(|factor_bits| factor_bits.unwrap_or_else(|| strong_factor_bits(bits)))
+    }
+
+    /// The primes that go with a found `p`, each with its label: q as
+    /// `sophie_germain` under `--safe`, p as `safe_prime` under
+    /// `--sophie-germain`, p + 2 as `twin` under `--twin`, and the rest of
+    /// a `--chain` or `--bi-twin` chain as `chain_2`, `chain_3` and so on.
+    fn companions(&self, p: &BigUint) -> Vec<(String, BigUint)> {
+        let members = match (self.chain, self.bi_twin) {
+            (Some(length), _) => Some(self.kind.kind().members(p, length)),
+            (None, Some(links)) => Some(bi_twin_members(&(p + 1u32), links)),
+            (None, None) => None,
+        };
+        if let Some(members) = members {
+            return (2..)
+                .zip(members.into_iter().skip(1))
+                .map(|(i, member)| (format!("chain_{i}"), member))
+                .collect();
+        }
+        let companion = if self.sophie_germain {
+            ("safe_prime", p.clone())
+        } else if self.twin {
+            ("twin", p + 2u32)
+        } else if self.safe {
+            ("sophie_germain", p >> 1u32)
+        } else {
+            return Vec::new();
+        };
+        vec![(companion.0.to_owned(), companion.1)]
+    }
+
+    /// Label of the size line printed above a single prime.
+    fn bits_label(&self) -> &'static str {
+        if self.sophie_germain {
+            "sophie_germain_bits"
+        } else if self.strong.is_some() {
+            "strong_prime_bits"
+        } else if self.blum {
+            "blum_prime_bits"
+        } else if self.chain.is_some() {
+            "chain_prime_bits"
+        } else if self.bi_twin.is_some() {
+            "bi_twin_prime_bits"
+        } else if self.twin {
+            "twin_prime_bits"
+        } else if self.safe {
+            "safe_prime_bits"
+        } else {
+            "prime_bits"
+        }
+    }
+
+    /// The kind of prime named in the terminal summary.
+    fn form(&self) -> &'static str {
+        if self.sophie_germain {
+            "Sophie Germain prime"
+        } else if self.strong.is_some() {
+            "strong prime"
+        } else if self.blum {
+            "Blum prime"
+        } else if self.chain.is_some() {
+            match self.kind {
+                ChainArg::First => "Cunningham chain, 1st kind",
+                ChainArg::Second => "Cunningham chain, 2nd kind",
+            }
+        } else if self.bi_twin.is_some() {
+            "bi-twin chain"
+        } else if self.twin {
+            "twin prime"
+        } else if self.safe {
+            "safe prime"
+        } else {
+            "probable prime"
+        }
+    }
+
+    /// The [companions](Self::companions) of `p` as text in `--format`.
+    fn companion_texts(&self, p: &BigUint) -> Vec<(String, String)> {
+        self.companions(p)
+            .into_iter()
+            .map(|(label, prime)| (label, self.format.text(&prime).unwrap_or_default()))
+            .collect()
+    }
+
+    /// The companion lines printed above a single prime.
+    fn companion_fields(&self, p: &BigUint) -> Vec<String> {
+        self.companion_texts(p)
+            .into_iter()
+            .map(|(label, prime)| format!("{label}={prime}"))
+            .collect()
+    }
+
+    /// Whether hints and headers are suppressed.
+    pub fn quiet(&self) -> bool {
+        self.quiet
+    }
+
+    /// log2 of the bound on the error probability for `bits`-bit primes.
+    fn error_log2(&self, bits: usize) -> Option<f64> {
+        primality::error_log2(
+            &self.test,
+            self.rounds(),
+            self.witnesses.as_ref(),
+            bits,
+            true,
+        )
+    }
+
+    /// The Pocklington certificate printed with `p`: always for a safe prime
+    /// p = 2q + 1, resting on q, and with `--certificate` on `factor`, a
+    /// prime above √p dividing p − 1, with a warning when there is none.
+    fn certificate(&self, p: &BigUint, factor: Option<BigUint>) -> Option<PocklingtonCertificate> {
+        if self.safe() {
+            return pocklington_certificate(p, &[p >> 1u32]);
+        }
+        if !self.certificate {
+            return None;
+        }
+        let certificate = factor.and_then(|factor| pocklington_certificate(p, &[factor]));
+        if certificate.is_none() {
+            eprintln!("warning: no Pocklington certificate could be built for {p}");
+        }
+        certificate
+    }
+
+    /// `proof=` and the certificate lines printed under a single safe prime.
+    fn safe_proof_fields(&self, p: &BigUint) -> Vec<String> {
+        let Some(certificate) = self.certificate(p, None) else {
+            return Vec::new();
+        };
+        let mut fields = vec![format!("proof={}", output::SAFE_PRIME_PROOF)];
+        fields.extend(output::pocklington_fields(&certificate));
+        fields
+    }
+
+    /// Encode one result of `bits` bits from `generator` as an output record.
+    fn record(
+        &self,
+        report: &GenerationReport,
+        generator: &PrimeGenerator,
+        bits: usize,
+    ) -> Vec<u8> {
+        let shown = self.shown(&report.prime);
+        if self.json {
+            output::json_record(
+                &GenerationReport {
+                    prime: shown,
+                    ..report.clone()
+                },
+                self.format,
+                self.safe(),
+                generator.rounds(),
+                self.error_log2(bits),
+                self.certificate(&report.prime, None).as_ref(),
+                &self.companion_texts(&report.prime),
+            )
+        } else {
+            self.format.encode(&shown, bits)
+        }
+    }
+}
+
+pub fn run(args: &GenerateArgs) -> Result<ExitCode, CliError> {
+    let small = args.bits.iter().find(|&&bits| bits < MIN_CLI_BITS);
+    if let Some(&bits) = small.filter(|_| args.special_form().is_none()) {
+        if !args.allow_small {
+            return Err(PrimeGenError::BitsTooSmall {
+                bits,
+                min: MIN_CLI_BITS,
+            }
+            .into());
+        }
+        if !args.quiet {
+            eprintln!(
+                "warning: {bits}-bit primes are far too small for real keys or DH groups; \
+                 use them for tests only."
+            );
+        }
+    }
+    if let Some(warning) = primality::speed_warning(&args.test).filter(|_| !args.quiet) {
+        eprintln!("{warning}");
+    }
+    if args.json && !args.format.is_text() {
+        clap::Error::raw(
+            clap::error::ErrorKind::ArgumentConflict,
+            "--json needs a text --format, not raw\n",
+        )
+        .exit();
+    }
+    if args.certificate && !args.safe() && args.provable.is_none() {
+        clap::Error::raw(
+            clap::error::ErrorKind::MissingRequiredArgument,
+            "--certificate needs --safe or --provable\n",
+        )
+        .exit();
+    }
+    let congruence = args.congruence();
+    let audit = match &args.audit_log {
+        Some(path) => Some(Arc::new(AuditLog::open(path)?)),
+        None => None,
+    };
+    let start = Instant::now();
+    if let Some(method) = args.provable {
+        let result = write_provable(args, method, audit.as_deref());
+        return end_audit(audit.as_deref(), result, None, start.elapsed());
+    }
+    if let Some(phrase) = &args.phrase {
+        let result = write_phrase(args, phrase, audit.as_deref());
+        return end_audit(audit.as_deref(), result, None, start.elapsed());
+    }
+    if args.pseudo_mersenne {
+        let result = write_pseudo_mersenne(args, audit.as_deref());
+        return end_audit(audit.as_deref(), result, None, start.elapsed());
+    }
+    if let Some(terms) = args.solinas {
+        let result = write_solinas(args, terms, audit.as_deref());
+        return end_audit(audit.as_deref(), result, None, start.elapsed());
+    }
+    if let Some((n, k_max)) = args.proth_n.zip(args.k_max).filter(|_| args.proth) {
+        if n < 64 && k_max >> n != 0 {
+            clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                format!("--k-max {k_max} is not below 2^{n}, as Proth's theorem needs\n"),
+            )
+            .exit();
+        }
+        let result = match write_proth(args, n, k_max, audit.as_deref()) {
+            Err(CliError::Io(err)) if err.kind() == ErrorKind::BrokenPipe => Ok(ExitCode::SUCCESS),
+            result => result,
+        };
+        return end_audit(audit.as_deref(), result, None, start.elapsed());
+    }
+    if let Some(audit) = &audit {
+        audit.start(&audit::seed(
+            "none: candidates are drawn from the OS RNG and never stored",
+            None,
+        ));
+    }
+
+    let cancel = super::cancel_on_signal();
+    let mut totals = Totals {
+        kept: args.bundle.as_ref().map(|_| Vec::new()),
+        ..Totals::default()
+    };
+    let mut file = args.out.as_ref().map(|_| Vec::new());
+    let mut result = Ok(ExitCode::SUCCESS);
+    for &bits in &args.bits {
+        let mut builder = PrimeGenerator::builder()
+            .bits(args.search_bits(bits))
+            .safe(args.safe())
+            .twin(args.twin)
+            .blum(args.blum)
+            .top_bits(args.top_bits)
+            .threads(args.threads.unwrap_or_else(default_threads))
+            .cancel_token(cancel.clone());
+        if !primality::is_default(&args.test) || args.witnesses.is_some() {
+            let stack = primality::stack(&args.test, args.rounds(), args.witnesses.as_ref(), bits);
+            builder = builder.test(stack);
+        }
+        let mr_rounds = match &args.witnesses {
+            Some(witnesses) => Some(witnesses.bases().len()),
+            None => args.rounds().for_test(TestKind::MillerRabin, bits),
+        };
+        if let Some(rounds) = mr_rounds {
+            builder = builder.rounds(rounds);
+        }
+        if let Some(length) = args.chain {
+            builder = builder.chain(args.kind.kind(), length);
+        }
+        if let Some(links) = args.bi_twin {
+            builder = builder.bi_twin(links);
+        }
+        if let Some(factor_bits) = args.strong_factor_bits(bits) {
+            builder = builder.strong(factor_bits);
+        }
+        if let Some(class) = &congruence {
+            builder = builder.congruent(class.clone());
+        }
+        if let Some(prefix) = &args.prefix {
+            builder = builder.prefix(prefix.clone());
+        }
+        if let Some(weight) = args.hamming_weight {
+            builder = builder.hamming_weight(weight);
+        }
+        if let Some(screen) = args.screen {
+            builder = builder.screen_rounds(screen);
+        }
+        if let Some(timeout) = args.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(max) = args.max_attempts {
+            builder = builder.max_attempts(max);
+        }
+        let bar = args.progress.then(|| {
+            progress::bar(
+                args.search_bits(bits),
+                args.safe() || args.twin || args.chain.is_some() || args.bi_twin.is_some(),
+            )
+        });
+        if bar.is_some() || audit.is_some() {
+            let (bar, audit) = (bar.clone(), audit.clone());
+            builder = builder.on_candidate(move |event| {
+                if let Some(bar) = &bar {
+                    progress::observe(bar, event);
+                }
+                if let Some(audit) = &audit {
+                    audit.observe(event);
+                }
+            });
+        }
+        let generator = builder.build();
+        if let Some(audit) = &audit {
+            let tests: Vec<String> = args
+                .test
+                .iter()
+                .filter_map(|test| test.to_possible_value())
+                .map(|value| value.get_name().to_owned())
+                .collect();
+            audit.record(
+                "search",
+                &format!(
+                    "\"bits\":{bits},\"safe\":{},\"twin\":{},\"test\":\"{}\",\"rounds\":{},\"screen\":{},\"threads\":{}",
+                    args.safe(),
+                    args.twin,
+                    tests.join(","),
+                    generator.rounds(),
+                    args.screen.map_or("null".to_owned(), |screen| screen.to_string()),
+                    generator.threads()
+                ),
+            );
+        }
+        result = write_results(&generator, args, bits, &mut totals, file.as_mut());
+        if let Some(bar) = bar {
+            bar.finish_and_clear();
+        }
+        if result.is_err() {
+            break;
+        }
+    }
+    if let (Ok(_), Some(path), Some(contents)) = (&result, &args.out, &file) {
+        result = output::write_atomic(path, contents)
+            .map(|()| ExitCode::SUCCESS)
+            .map_err(CliError::from);
+    }
+    if let (Ok(_), Some(kept)) = (&result, totals.kept.take()) {
+        result = write_bundle(args, Mode::Search, kept).map(|()| ExitCode::SUCCESS);
+    }
+    // A reader closing the pipe (e.g. `| head -n 5`) ends the run cleanly.
+    if let Err(CliError::Io(err)) = &result
+        && err.kind() == ErrorKind::BrokenPipe
+    {
+        result = Ok(ExitCode::SUCCESS);
+    }
+
+    if let Err(CliError::Generate(PrimeGenError::Cancelled(stats))) = &result
+        && !args.quiet
+    {
+        eprintln!(
+            "interrupted after {:.1}s: candidates={} sieved_out={} rounds={}",
+            start.elapsed().as_secs_f64(),
+            stats.candidates,
+            stats.sieved_out,
+            stats.rounds
+        );
+    }
+    if args.stats {
+        totals.report(start.elapsed());
+    }
+    end_audit(
+        audit.as_deref(),
+        result,
+        Some(&totals.stats),
+        start.elapsed(),
+    )
+}
+
+/// Close the audit log, if any, with how the run ended. A log that could
+/// not be written fails a run that otherwise succeeded.
+fn end_audit(
+    audit: Option<&AuditLog>,
+    result: Result<ExitCode, CliError>,
+    stats: Option<&SearchStats>,
+    elapsed: Duration,
+) -> Result<ExitCode, CliError> {
+    let Some(audit) = audit else {
+        return result;
+    };
+    let status = match &result {
+        Ok(_) => "ok".to_owned(),
+        Err(err) => err.to_string(),
+    };
+    match audit.finish(&status, stats, elapsed) {
+        Err(err) if result.is_ok() => Err(err.into()),
+        _ => result,
+    }
+}
+
+/// Work done across every search in one invocation, for `--stats`.
+#[derive(Default)]
+struct Totals {
+    primes: u64,
+    stats: SearchStats,
+    /// The primes themselves, kept for `--bundle`.
+    kept: Option<Vec<BigUint>>,
+}
+
+impl Totals {
+    /// Generate one prime, adding its search (or the failed one) to the totals.
+    fn generate(&mut self, generator: &PrimeGenerator) -> Result<GenerationReport, CliError> {
+        match generator.generate_report() {
+            Ok(report) => {
+                self.primes += 1;
+                self.stats += report.stats;
+                if let Some(kept) = &mut self.kept {
+                    kept.push(report.prime.clone());
+                }
+                Ok(report)
+            }
+            Err(err) => {
+                if let PrimeGenError::Cancelled(stats)
+                | PrimeGenError::TimedOut(stats)
+                | PrimeGenError::AttemptsExhausted(stats) = &err
+                {
+                    self.stats += *stats;
+                }
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Print the totals to stderr.
+    fn report(&self, wall: Duration) {
+        let stats = &self.stats;
+        let avg_round_us = stats
+            .test_time
+            .as_micros()
+            .checked_div(u128::from(stats.rounds))
+            .unwrap_or(0);
+        eprintln!(
+            "stats: primes={} candidates={} sieved_out={} fermat_rejected={} test_rejected={} \
+             rounds={} avg_round_us={avg_round_us} wall_ms={}",
+            self.primes,
+            stats.candidates,
+            stats.sieved_out,
+            stats.fermat_rejected,
+            stats.test_rejected,
+            stats.rounds,
+            wall.as_millis()
+        );
+    }
+}
+
+/// Generate `args.count` primes of `bits` bits and emit them in the
+/// requested mode, appending to `file` when writing to `--out`.
+fn write_results(
+    generator: &PrimeGenerator,
+    args: &GenerateArgs,
+    bits: usize,
+    totals: &mut Totals,
+    file: Option<&mut Vec<u8>>,
+) -> Result<ExitCode, CliError> {
+    if let Some(contents) = file {
+        for _ in 0..args.count {
+            contents.extend(args.record(&totals.generate(generator)?, generator, bits));
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+    if !args.continuous && args.count == 1 && args.format.is_text() && !args.json {
+        let report = totals.generate(generator)?;
+        let p = &report.prime;
+        let shown = args.shown(p);
+        if !args.quiet && summary::styled(args.color) {
+            summary::print(
+                &report,
+                &shown,
+                args.form(),
+                args.error_log2(bits),
+                &args.format.text(&shown).unwrap_or_default(),
+                &args.companion_texts(p),
+            )?;
+            for field in args.safe_proof_fields(p) {
+                println!("{field}");
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        if !args.quiet {
+            println!("{}={}", args.bits_label(), shown.bits());
+            if let Some(log2) = args.error_log2(bits) {
+                println!("error_bound={}", primality::format_error(log2));
+            }
+            for field in args
+                .companion_fields(p)
+                .into_iter()
+                .chain(args.safe_proof_fields(p))
+            {
+                println!("{field}");
+            }
+        }
+        io::stdout().write_all(&args.format.encode(&shown, bits))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let count = if args.continuous {
+        u64::MAX
+    } else {
+        args.count
+    };
+    let mut out = io::stdout().lock();
+    // Stdout is line-buffered, so every text record reaches a pipe as soon
+    // as it is found; raw records are flushed explicitly.
+    for _ in 0..count {
+        out.write_all(&args.record(&totals.generate(generator)?, generator, bits))?;
+        out.flush()?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Build `args.count` provable primes of each size with `method`, chaining
+/// the seed from one to the next, and emit them like the searched ones.
+fn write_provable(
+    args: &GenerateArgs,
+    method: ProvableMethod,
+    audit: Option<&AuditLog>,
+) -> Result<ExitCode, CliError> {
+    if method != ProvableMethod::ShaweTaylor && args.seed.is_some() {
+        clap::Error::raw(
+            clap::error::ErrorKind::ArgumentConflict,
+            "--seed only applies to --provable shawe-taylor\n",
+        )
+        .exit();
+    }
+    let mut seed = args.seed.clone().unwrap_or_else(Seed::random);
+    let first = seed.clone();
+    if let Some(audit) = audit {
+        audit.start(&match (method, &args.seed) {
+            (ProvableMethod::Maurer, _) => audit::seed(
+                "none: Maurer draws from the OS RNG and stores nothing",
+                None,
+            ),
+            (_, Some(_)) => audit::seed("given with --seed", Some(&first.0)),
+            (_, None) => audit::seed("drawn from the OS RNG", Some(&first.0)),
+        });
+    }
+    let mut records = Vec::new();
+    let mut found = Vec::new();
+    let mut last = None;
+    for &bits in &args.bits {
+        for _ in 0..args.count {
+            let built =
+                provable::construct(method, bits, &mut seed).ok_or(CliError::SeedExhausted)?;
+            if let Some(audit) = audit {
+                audit.constructed(method.name(), &built.prime);
+            }
+            let certificate = args.certificate(&built.prime, built.factor.clone());
+            records.extend(if args.json {
+                let mut members = built.proof.json_members();
+                if let Some(certificate) = &certificate {
+                    members.push_str(&output::pocklington_member(certificate));
+                }
+                output::provable_json_record(&built.prime, args.format, method.name(), &members)
+            } else {
+                args.format.encode(&built.prime, bits)
+            });
+            found.push(built.prime.clone());
+            last = Some((built, certificate));
+        }
+    }
+
+    if let Some(path) = &args.out {
+        output::write_atomic(path, &records)?;
+    } else {
+        let single = args.bits.len() == 1 && args.count == 1;
+        if !args.quiet && !args.json && args.format.is_text() {
+            match last.filter(|_| single) {
+                Some((built, certificate)) => {
+                    println!("prime_bits={}", built.prime.bits());
+                    for field in built.proof.fields() {
+                        println!("{field}");
+                    }
+                    for field in certificate.iter().flat_map(output::pocklington_fields) {
+                        println!("{field}");
+                    }
+                }
+                None if method == ProvableMethod::ShaweTaylor => {
+                    eprintln!("seed={}", first.hex());
+                }
+                None => {}
+            }
+        }
+        io::stdout().write_all(&records)?;
+    }
+    if args.bundle.is_some() {
+        let mode = match method {
+            ProvableMethod::ShaweTaylor => Mode::ShaweTaylor(first),
+            ProvableMethod::Maurer => Mode::Maurer,
+        };
+        write_bundle(args, mode, found)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Derive `args.count` nothing-up-my-sleeve primes of each size from
+/// `phrase`, each from the counter after the one before, and emit them like
+/// the searched ones.
+fn write_phrase(
+    args: &GenerateArgs,
+    phrase: &str,
+    audit: Option<&AuditLog>,
+) -> Result<ExitCode, CliError> {
+    if let Some(audit) = audit {
+        audit.start(&audit::seed(
+            "the public phrase given with --phrase",
+            Some(phrase.as_bytes()),
+        ));
+    }
+    let mut records = Vec::new();
+    let mut found = Vec::new();
+    let mut last = None;
+    for &bits in &args.bits {
+        let mut from = 0;
+        for _ in 0..args.count {
+            let search_bits = args.search_bits(bits) as u64;
+            let derived = nums_prime(phrase.as_bytes(), search_bits, args.safe(), from).ok_or(
+                PrimeGenError::BitsTooSmall {
+                    bits,
+                    min: if args.safe() {
+                        3 - usize::from(args.sophie_germain)
+                    } else {
+                        2
+                    },
+                },
+            )?;
+            from = derived.counter + 1;
+            if let Some(audit) = audit {
+                audit.constructed("phrase", &derived.prime);
+            }
+            let shown = args.shown(&derived.prime);
+            records.extend(if args.json {
+                output::phrase_json_record(
+                    &shown,
+                    args.format,
+                    args.safe(),
+                    phrase,
+                    derived.counter,
+                    args.certificate(&derived.prime, None).as_ref(),
+                    &args.companion_texts(&derived.prime),
+                )
+            } else {
+                args.format.encode(&shown, bits)
+            });
+            found.push(derived.prime.clone());
+            last = Some(derived);
+        }
+    }
+
+    if let Some(path) = &args.out {
+        output::write_atomic(path, &records)?;
+    } else {
+        let single = args.bits.len() == 1 && args.count == 1;
+        let header = single && !args.quiet && !args.json && args.format.is_text();
+        if let Some(derived) = last.filter(|_| header) {
+            let p = &derived.prime;
+            println!("{}={}", args.bits_label(), args.shown(p).bits());
+            println!("phrase={phrase}");
+            println!("counter={}", derived.counter);
+            for field in args
+                .companion_fields(p)
+                .into_iter()
+                .chain(args.safe_proof_fields(p))
+            {
+                println!("{field}");
+            }
+        }
+        io::stdout().write_all(&records)?;
+    }
+    if args.bundle.is_some() {
+        write_bundle(args, Mode::Phrase(phrase.to_owned()), found)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `--pseudo-mersenne`: the first `--count` primes 2^bits − c of each size,
+/// by increasing c.
+fn write_pseudo_mersenne(
+    args: &GenerateArgs,
+    audit: Option<&AuditLog>,
+) -> Result<ExitCode, CliError> {
+    let mut found = Vec::new();
+    for &bits in &args.bits {
+        let test = primality::stack(&args.test, args.rounds(), args.witnesses.as_ref(), bits);
+        let power = BigUint::one() << bits;
+        let mut from = 1;
+        for index in 0..args.count {
+            if bits < 2 {
+                return Err(PrimeGenError::BitsTooSmall { bits, min: 2 }.into());
+            }
+            let Some(c) = pseudo_mersenne(bits as u64, from, &test, &mut OsRng) else {
+                eprintln!("warning: only {index} primes 2^{bits} − c have {bits} bits");
+                break;
+            };
+            from = c + 2;
+            found.push((&power - c, format!("2^{bits} - {c}")));
+        }
+    }
+    write_form(args, audit, found)
+}
+
+/// `--solinas`: the first `--count` sums of `terms` powers of two of each
+/// size that are prime.
+fn write_solinas(
+    args: &GenerateArgs,
+    terms: usize,
+    audit: Option<&AuditLog>,
+) -> Result<ExitCode, CliError> {
+    let mut found = Vec::new();
+    for &bits in &args.bits {
+        let test = primality::stack(&args.test, args.rounds(), args.witnesses.as_ref(), bits);
+        let before = found.len();
+        found.extend(
+            solinas(bits as u64, terms, &test, &mut OsRng)
+                .take(args.count as usize)
+                .map(|sum| (sum.value(), sum.to_string())),
+        );
+        let primes = found.len() - before;
+        if primes == 0 {
+            return Err(PrimeGenError::BitsTooSmall {
+                bits,
+                min: terms + 1,
+            }
+            .into());
+        }
+        if primes < args.count as usize {
+            eprintln!("warning: only {primes} sums of {terms} powers of two are {bits}-bit primes");
+        }
+    }
+    write_form(args, audit, found)
+}
+
+/// The span of k `--proth` covers between checks for Ctrl-C and
+/// `--timeout`.
+const PROTH_CHUNK: u64 = 1 << 12;
+
+/// Write the Proth primes k·2^`n` + 1 with odd k up to `k_max` as each is
+/// proven, checking for Ctrl-C and `--timeout` between ranges of k. With
+/// `--out` the file is written only once the search completes.
+fn write_proth(
+    args: &GenerateArgs,
+    n: u64,
+    k_max: u64,
+    audit: Option<&AuditLog>,
+) -> Result<ExitCode, CliError> {
+    let cancel = super::cancel_on_signal();
+    let deadline = args.timeout.map(|timeout| Instant::now() + timeout);
+    if let Some(audit) = audit {
+        audit.start(&audit::seed("none: the search is deterministic", None));
+    }
+    let mut stdout = io::stdout().lock();
+    let mut file = args.out.as_ref().map(|_| Vec::new());
+    // The first prime is held back until a second turns up, so that a lone
+    // one can be printed with headers as `write_form` does.
+    let mut first = None;
+    let mut found = 0u64;
+    let mut stopped = None;
+    let mut low = 1;
+    while low <= k_max {
+        // Every odd k below `low` has been decided.
+        let stats = SearchStats {
+            candidates: low / 2,
+            ..SearchStats::default()
+        };
+        if cancel.is_cancelled() {
+            stopped = Some(PrimeGenError::Cancelled(stats));
+            break;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            stopped = Some(PrimeGenError::TimedOut(stats));
+            break;
+        }
+        let high = low.saturating_add(PROTH_CHUNK - 1).min(k_max);
+        for k in proth_primes_between(n, low, high) {
+            let p = (BigUint::from(k) << n) + 1u32;
+            let expression = format!("{k}*2^{n} + 1");
+            if let Some(audit) = audit {
+                audit.constructed("proth", &p);
+            }
+            found += 1;
+            if let Some(contents) = &mut file {
+                contents.extend(args.form_record(&p, &expression));
+            } else if found == 1 {
+                first = Some((p, expression));
+            } else {
+                if let Some((p, expression)) = first.take() {
+                    stdout.write_all(&args.form_record(&p, &expression))?;
+                }
+                stdout.write_all(&args.form_record(&p, &expression))?;
+                stdout.flush()?;
+            }
+        }
+        if high == k_max {
+            break;
+        }
+        low = high + 1;
+    }
+    if let Some((p, expression)) = first {
+        if stopped.is_none() && !args.quiet && !args.json && args.format.is_text() {
+            writeln!(stdout, "proth_bits={}", p.bits())?;
+            writeln!(stdout, "expression={expression}")?;
+        }
+        stdout.write_all(&args.form_record(&p, &expression))?;
+    }
+    if let Some(err) = stopped {
+        return Err(err.into());
+    }
+    if found == 0 {
+        eprintln!("warning: no odd k up to {k_max} makes k·2^{n} + 1 prime");
+    }
+    if let (Some(path), Some(contents)) = (&args.out, &file) {
+        output::write_atomic(path, contents)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Write primes of the [special form](GenerateArgs::special_form), each
+/// with the expression that gives it.
+fn write_form(
+    args: &GenerateArgs,
+    audit: Option<&AuditLog>,
+    found: Vec<(BigUint, String)>,
+) -> Result<ExitCode, CliError> {
+    let form = args.special_form().unwrap_or_default();
+    if let Some(audit) = audit {
+        audit.start(&audit::seed("none: the search is deterministic", None));
+        for (p, _) in &found {
+            audit.constructed(form, p);
+        }
+    }
+    let records: Vec<u8> = found
+        .iter()
+        .flat_map(|(p, expression)| args.form_record(p, expression))
+        .collect();
+    if let Some(path) = &args.out {
+        output::write_atomic(path, &records)?;
+    } else {
+        let header = found.len() == 1 && !args.quiet && !args.json && args.format.is_text();
+        if let Some((p, expression)) = found.first().filter(|_| header) {
+            println!("{}_bits={}", form.replace('-', "_"), p.bits());
+            println!("expression={expression}");
+        }
+        io::stdout().write_all(&records)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Write the `--bundle` file, if asked for, for a run that made `primes` by
+/// `mode`.
+fn write_bundle(args: &GenerateArgs, mode: Mode, primes: Vec<BigUint>) -> Result<(), CliError> {
+    let Some(path) = &args.bundle else {
+        return Ok(());
+    };
+    let bundle = Bundle {
+        crate_version: env!("CARGO_PKG_VERSION").to_owned(),
+        mode,
+        bits: args.bits.clone(),
+        count: args.count,
+        safe: args.safe(),
+        sophie_germain: args.sophie_germain,
+        twin: args.twin,
+        chain: args.chain.map(|length| (args.kind.kind(), length)),
+        bi_twin: args.bi_twin,
+        strong: args.strong,
+        blum: args.blum,
+        congruence: args.congruence(),
+        top_bits: args.top_bits,
+        prefix: args.prefix.clone(),
+        hamming_weight: args.hamming_weight,
+        test: args.test.clone(),
+        rounds: args.rounds,
+        error_bound: args.error_bound,
+        witnesses: args.witnesses.clone(),
+        screen: args.screen,
+        primes,
+    };
+    output::write_atomic(path, bundle.to_json().as_bytes())?;
+    Ok(())
+}
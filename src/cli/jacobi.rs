@@ -0,0 +1,30 @@
+//! `jacobi` subcommand.
+
+use clap::Args;
+use clap::error::ErrorKind;
+use find_big_prime::{jacobi, parse_number};
+use num_integer::Integer;
+use std::process::ExitCode;
+
+use super::CliError;
+
+/// Options for computing a Jacobi symbol.
+#[derive(Args, Debug)]
+pub struct JacobiArgs {
+    /// The top of the symbol: decimal, 0x-prefixed hex, or base64.
+    a: String,
+
+    /// The odd modulus; when it is prime the result is the Legendre symbol.
+    n: String,
+}
+
+/// Print `jacobi=-1`, `0` or `1`.
+pub fn run(args: &JacobiArgs) -> Result<ExitCode, CliError> {
+    let a = parse_number(&args.a)?;
+    let n = parse_number(&args.n)?;
+    if n.is_even() {
+        clap::Error::raw(ErrorKind::ValueValidation, "the modulus N must be odd\n").exit();
+    }
+    println!("jacobi={}", jacobi(&a, &n));
+    Ok(ExitCode::SUCCESS)
+}
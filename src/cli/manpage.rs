@@ -0,0 +1,28 @@
+//! Hidden `--generate-manpage` flag.
+
+use clap::CommandFactory;
+use clap_mangen::Man;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use super::{Cli, CliError};
+
+/// Write a roff man page for the whole CLI to stdout.
+///
+/// The top-level page documents the default `generate` flags and lists the
+/// subcommands; each subcommand's own flags follow as "<NAME> OPTIONS".
+pub fn run() -> Result<ExitCode, CliError> {
+    let command = Cli::command();
+    let mut out = io::stdout().lock();
+    Man::new(command.clone()).render(&mut out)?;
+    for sub in command.get_subcommands() {
+        let heading = format!("{} options", sub.get_name());
+        let sub = sub
+            .clone()
+            .disable_help_flag(true)
+            .mut_args(|arg| arg.help_heading(heading.clone()));
+        Man::new(sub).render_options_section(&mut out)?;
+    }
+    out.flush()?;
+    Ok(ExitCode::SUCCESS)
+}
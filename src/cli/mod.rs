@@ -0,0 +1,255 @@
+//! Command-line interface definitions.
+
+use clap::{ArgMatches, Parser, Subcommand, ValueEnum};
+use find_big_prime::{CancellationToken, ParseNumberError, PrimeGenError};
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+use tracing_subscriber::EnvFilter;
+
+pub mod audit;
+pub mod bundle;
+pub mod certificate;
+pub mod check;
+pub mod completions;
+pub mod config;
+pub mod dsa_params;
+pub mod factor;
+pub mod fips;
+pub mod generate;
+pub mod jacobi;
+pub mod json;
+pub mod manpage;
+pub mod modp;
+pub mod output;
+pub mod primality;
+pub mod primo;
+pub mod progress;
+pub mod provable;
+pub mod reproduce;
+pub mod selftest;
+pub mod summary;
+pub mod validate_params;
+pub mod verify_cert;
+
+// Exit codes: 0 success / probable prime, 1 composite, 2 usage error (clap),
+// 3 and up for the failures in `CliError::exit_code`.
+
+/// `check` found a composite number.
+pub const EXIT_COMPOSITE: u8 = 1;
+/// An input number could not be parsed.
+pub const EXIT_PARSE: u8 = 6;
+/// A `--timeout` or `--max-attempts` limit ran out before a prime was found.
+pub const EXIT_LIMIT: u8 = 8;
+/// `selftest` got a wrong verdict on a known answer, or a `--fips`
+/// power-on self-test failed.
+pub const EXIT_SELFTEST: u8 = 9;
+/// `verify-cert` was given a certificate that does not prove its number
+/// prime, `validate-params` parameters that do not re-derive, or
+/// `reproduce` a bundle whose primes do not.
+pub const EXIT_CERTIFICATE: u8 = 10;
+
+// Running without a subcommand behaves like `generate`, so existing
+// invocations such as `find-big-prime --bits 4096 --safe` keep working.
+
+/// CLI arguments parsed via clap.
+#[derive(Parser, Debug)]
+#[command(
+    name = "find-big-prime",
+    about = "Generate large probable primes and safe primes",
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub generate: generate::GenerateArgs,
+
+    /// Log verbosity on stderr; overrides `RUST_LOG` (default: warn).
+    #[arg(long = "log-level", value_enum, global = true)]
+    pub log_level: Option<LogLevel>,
+
+    /// Read `generate` defaults from FILE (default: `./find-big-prime.toml` if present).
+    #[arg(
+        long = "config",
+        env = "FBP_CONFIG",
+        value_name = "FILE",
+        global = true
+    )]
+    pub config: Option<PathBuf>,
+
+    /// Run power-on self-tests (known answers for SHA-256, the primality tests and
+    /// the OS RNG) first, and refuse to go on if any fails.
+    #[arg(long = "fips", global = true)]
+    pub fips: bool,
+
+    /// Print a roff man page and exit (for packagers).
+    #[arg(long = "generate-manpage", hide = true)]
+    pub generate_manpage: bool,
+}
+
+/// Values accepted by `--log-level`.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Cli {
+    /// Send `tracing` events to stderr, filtered by `--log-level` or `RUST_LOG`.
+    pub fn init_logging(&self) {
+        let filter = match self.log_level {
+            Some(level) => EnvFilter::new(format!("{level:?}").to_lowercase()),
+            None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        };
+        tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(io::stderr)
+            .init();
+    }
+
+    /// Fill in `generate` options set neither on the command line nor through
+    /// `FBP_*` environment variables from the config file.
+    pub fn apply_config(&mut self, matches: &ArgMatches) -> Result<(), CliError> {
+        let config = config::Config::load(self.config.as_deref())?;
+        match &mut self.command {
+            Some(Command::Generate(args)) => {
+                let matches = matches.subcommand_matches("generate").unwrap_or(matches);
+                args.apply_config(&config, matches)
+            }
+            Some(
+                Command::Check(_)
+                | Command::Factor(_)
+                | Command::Jacobi(_)
+                | Command::Selftest(_)
+                | Command::VerifyCert(_)
+                | Command::DsaParams(_)
+                | Command::ValidateParams(_)
+                | Command::Modp(_)
+                | Command::Reproduce(_)
+                | Command::Completions(_),
+            ) => Ok(()),
+            None => self.generate.apply_config(&config, matches),
+        }
+    }
+
+    /// Whether `--quiet` was given to `generate`.
+    pub fn quiet(&self) -> bool {
+        match &self.command {
+            Some(Command::Generate(args)) => args.quiet(),
+            Some(
+                Command::Check(_)
+                | Command::Factor(_)
+                | Command::Jacobi(_)
+                | Command::Selftest(_)
+                | Command::VerifyCert(_)
+                | Command::DsaParams(_)
+                | Command::ValidateParams(_)
+                | Command::Modp(_)
+                | Command::Reproduce(_)
+                | Command::Completions(_),
+            ) => false,
+            None => self.generate.quiet(),
+        }
+    }
+}
+
+/// Subcommands.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate a probable prime or safe prime (the default).
+    Generate(Box<generate::GenerateArgs>),
+
+    /// Test whether a given number is a probable prime.
+    Check(check::CheckArgs),
+
+    /// Factor a number with Pollard's rho, Pollard's p − 1 (`--p1`) or ECM (`--ecm`).
+    Factor(factor::FactorArgs),
+
+    /// Print the Jacobi symbol (A/N), which is the Legendre symbol for prime N.
+    Jacobi(jacobi::JacobiArgs),
+
+    /// Run the primality tests against built-in pseudoprimes and primes.
+    Selftest(selftest::SelftestArgs),
+
+    /// Check a primality certificate without redoing any search.
+    VerifyCert(verify_cert::VerifyCertArgs),
+
+    /// Generate DSA domain parameters (p, q, g) that anyone can re-derive from their seed.
+    DsaParams(dsa_params::DsaParamsArgs),
+
+    /// Re-derive seeded DSA domain parameters and report whether they match.
+    ValidateParams(validate_params::ValidateParamsArgs),
+
+    /// Build an RFC 3526-style MODP safe prime from the binary expansion of π.
+    Modp(modp::ModpArgs),
+
+    /// Redo a generation from its `--bundle` file and confirm the primes match.
+    Reproduce(reproduce::ReproduceArgs),
+
+    /// Print a shell completion script (bash, zsh, fish, powershell, elvish).
+    Completions(completions::CompletionsArgs),
+}
+
+/// A token that is cancelled on SIGINT or SIGTERM, so searches stop cleanly.
+pub fn cancel_on_signal() -> CancellationToken {
+    let token = CancellationToken::new();
+    let handler_token = token.clone();
+    if let Err(err) = ctrlc::set_handler(move || handler_token.cancel()) {
+        tracing::warn!(%err, "could not install the interrupt handler");
+    }
+    token
+}
+
+/// Errors surfaced by any subcommand.
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error(transparent)]
+    Generate(#[from] PrimeGenError),
+
+    #[error(transparent)]
+    Parse(#[from] ParseNumberError),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("{}: {message}", path.display())]
+    Config { path: PathBuf, message: String },
+
+    #[error("the provable construction gave up on this seed; try another")]
+    SeedExhausted,
+
+    #[error("malformed certificate: {0}")]
+    Certificate(String),
+
+    #[error("malformed parameters: {0}")]
+    Params(String),
+
+    #[error("malformed bundle: {0}")]
+    Bundle(String),
+}
+
+impl CliError {
+    /// Process exit code for this error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Generate(
+                PrimeGenError::BitsTooSmall { .. } | PrimeGenError::WeightOutOfRange { .. },
+            ) => 3,
+            CliError::Generate(PrimeGenError::BitsTooLarge(_)) => 4,
+            CliError::Generate(PrimeGenError::Cancelled(_)) => 5,
+            CliError::Generate(
+                PrimeGenError::TimedOut(_) | PrimeGenError::AttemptsExhausted(_),
+            ) => EXIT_LIMIT,
+            CliError::Parse(_) => EXIT_PARSE,
+            CliError::Io(_) => 7,
+            CliError::Config { .. } => 2,
+            CliError::SeedExhausted => EXIT_LIMIT,
+            CliError::Certificate(_) | CliError::Params(_) | CliError::Bundle(_) => EXIT_PARSE,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! `modp` subcommand.
+
+use clap::Args;
+use clap::error::ErrorKind;
+use find_big_prime::modp::RFC_FIXED_BITS;
+use find_big_prime::modp_prime;
+use std::process::ExitCode;
+
+use super::CliError;
+use super::output::OutputFormat;
+
+/// Options for building a π-derived MODP prime.
+#[derive(Args, Debug)]
+pub struct ModpArgs {
+    /// Bits of the prime p, the exponent n in 2^n − 2^(n−k) − 1 + 2^k · (⌊2^(n−2k−2) · π⌋ + X).
+    #[arg(short = 'b', long = "bits", value_name = "N", default_value_t = 2048)]
+    bits: u64,
+
+    /// Bits k fixed to one at each end of p; RFC 2409 and RFC 3526 use 64.
+    #[arg(long = "fixed-bits", value_name = "K", default_value_t = RFC_FIXED_BITS)]
+    fixed_bits: u64,
+
+    /// Start the search for X here instead of at 0, e.g. to skip to a known offset.
+    #[arg(long = "offset", value_name = "X", default_value_t = 0)]
+    offset: u64,
+
+    /// Encoding of p; `raw` is not a text format and is refused.
+    #[arg(short = 'f', long = "format", value_enum, default_value_t = OutputFormat::Decimal)]
+    format: OutputFormat,
+
+    /// Print one JSON object instead of `key=value` lines.
+    #[arg(long = "json")]
+    json: bool,
+}
+
+/// Search offsets from `--offset` for the first that makes p a safe prime,
+/// and print p with the offset and the generator 2.
+pub fn run(args: &ModpArgs) -> Result<ExitCode, CliError> {
+    let (n, k) = (args.bits, args.fixed_bits);
+    if k == 0 || 2 * k + 2 >= n {
+        clap::Error::raw(
+            ErrorKind::ValueValidation,
+            format!("--bits must exceed 2 · --fixed-bits + 2 = {}, and --fixed-bits must be at least 1\n", 2 * k + 2),
+        )
+        .exit();
+    }
+    if !args.format.is_text() {
+        clap::Error::raw(
+            ErrorKind::ArgumentConflict,
+            "modp needs a text --format, not raw\n",
+        )
+        .exit();
+    }
+    let found = modp_prime(n, k, args.offset).ok_or(CliError::SeedExhausted)?;
+    let p = args.format.text(&found.prime).unwrap_or_default();
+    if args.json {
+        println!(
+            "{{\"bits\":{n},\"fixed_bits\":{k},\"offset\":{},\"generator\":2,\"p\":\"{p}\"}}",
+            found.offset
+        );
+    } else {
+        println!("bits={n}");
+        println!("fixed_bits={k}");
+        println!("offset={}", found.offset);
+        println!("generator=2");
+        println!("p={p}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
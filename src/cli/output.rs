@@ -0,0 +1,248 @@
+//! Encoding results and writing them to files.
+
+use clap::ValueEnum;
+use find_big_prime::{Format, GenerationReport, PocklingtonCertificate};
+use num_bigint::BigUint;
+use serde::Deserialize;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// How generated primes are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Base 10.
+    #[default]
+    Decimal,
+    /// Lowercase base 16, no prefix.
+    Hex,
+    /// Uppercase base 16, no prefix.
+    HexUpper,
+    /// Standard base64 of the big-endian bytes.
+    Base64,
+    /// Big-endian bytes, zero-padded to the requested bit length.
+    Raw,
+}
+
+impl OutputFormat {
+    /// Whether records are text lines rather than bytes.
+    pub fn is_text(self) -> bool {
+        self != OutputFormat::Raw
+    }
+
+    /// Render `n` as text, or `None` for the raw format.
+    pub fn text(self, n: &BigUint) -> Option<String> {
+        Some(match self {
+            OutputFormat::Decimal => Format::Decimal.display(n).to_string(),
+            OutputFormat::Hex => Format::Hex.display(n).to_string(),
+            OutputFormat::HexUpper => format!("{n:X}"),
+            OutputFormat::Base64 => Format::Base64.display(n).to_string(),
+            OutputFormat::Raw => return None,
+        })
+    }
+
+    /// Encode one prime of a `bits`-bit search as a self-delimiting record:
+    /// a line for the text formats, a fixed-width byte string for raw.
+    pub fn encode(self, n: &BigUint, bits: usize) -> Vec<u8> {
+        match self.text(n) {
+            Some(line) => {
+                let mut record = line.into_bytes();
+                record.push(b'\n');
+                record
+            }
+            None => {
+                let bytes = n.to_bytes_be();
+                let mut padded = vec![0u8; bits.div_ceil(8).saturating_sub(bytes.len())];
+                padded.extend_from_slice(&bytes);
+                padded
+            }
+        }
+    }
+}
+
+/// What the Pocklington certificate of a safe prime p = 2q + 1 proves: that
+/// p is prime provided q is, which the search only tested probabilistically.
+pub const SAFE_PRIME_PROOF: &str = "pocklington-given-q";
+
+/// One JSON object, newline-terminated, describing a generated prime.
+///
+/// `prime` is a string in `format`, which must be a text format, and
+/// `error_bound_log2` is `null` when the tests have no proven bound. A
+/// `certificate`, which only safe primes have, adds `proof` and
+/// `pocklington` members, and each of the `companions` of a safe or twin
+/// pair or a chain, the other primes in `format`, a member under its label.
+pub fn json_record(
+    report: &GenerationReport,
+    format: OutputFormat,
+    safe: bool,
+    rounds: usize,
+    error_log2: Option<f64>,
+    certificate: Option<&PocklingtonCertificate>,
+    companions: &[(String, String)],
+) -> Vec<u8> {
+    let prime = format.text(&report.prime).unwrap_or_default();
+    let error = error_log2.map_or("null".to_owned(), |log2| (log2.ceil() as i64).to_string());
+    let pocklington = certificate
+        .map(|certificate| {
+            format!(
+                ",\"proof\":\"{SAFE_PRIME_PROOF}\"{}",
+                pocklington_member(certificate)
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        "{{\"bits\":{},\"prime\":\"{}\",\"safe\":{safe}{},\"rounds\":{rounds},\"error_bound_log2\":{error},\"attempts\":{},\"elapsed_ms\":{}{pocklington}}}\n",
+        report.prime.bits(),
+        prime,
+        companion_members(companions),
+        report.stats.candidates,
+        report.elapsed.as_millis(),
+    )
+    .into_bytes()
+}
+
+/// One JSON object, newline-terminated, describing a prime derived from
+/// `phrase` at `counter`, with the certificate and companions of a safe one
+/// as in [`json_record`].
+pub fn phrase_json_record(
+    prime: &BigUint,
+    format: OutputFormat,
+    safe: bool,
+    phrase: &str,
+    counter: u64,
+    certificate: Option<&PocklingtonCertificate>,
+    companions: &[(String, String)],
+) -> Vec<u8> {
+    let pocklington = certificate
+        .map(|certificate| {
+            format!(
+                ",\"proof\":\"{SAFE_PRIME_PROOF}\"{}",
+                pocklington_member(certificate)
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        "{{\"bits\":{},\"prime\":\"{}\",\"safe\":{safe}{},\"phrase\":{},\"counter\":{counter}{pocklington}}}\n",
+        prime.bits(),
+        format.text(prime).unwrap_or_default(),
+        companion_members(companions),
+        json_string(phrase),
+    )
+    .into_bytes()
+}
+
+/// One JSON object, newline-terminated, describing a prime of a special
+/// `form` with the `expression` that gives it.
+pub fn form_json_record(
+    prime: &BigUint,
+    format: OutputFormat,
+    form: &str,
+    expression: &str,
+) -> Vec<u8> {
+    format!(
+        "{{\"bits\":{},\"prime\":\"{}\",\"form\":\"{form}\",\"expression\":\"{expression}\"}}\n",
+        prime.bits(),
+        format.text(prime).unwrap_or_default(),
+    )
+    .into_bytes()
+}
+
+/// `,"label":"prime"` for each of `companions`.
+fn companion_members(companions: &[(String, String)]) -> String {
+    companions
+        .iter()
+        .map(|(label, prime)| format!(",\"{label}\":\"{prime}\""))
+        .collect()
+}
+
+/// `s` as a JSON string.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// One JSON object, newline-terminated, describing a provable prime built
+/// by `method`, followed by the method's own `members`.
+pub fn provable_json_record(
+    prime: &BigUint,
+    format: OutputFormat,
+    method: &str,
+    members: &str,
+) -> Vec<u8> {
+    format!(
+        "{{\"bits\":{},\"prime\":\"{}\",\"provable\":\"{method}\",{members}}}\n",
+        prime.bits(),
+        format.text(prime).unwrap_or_default(),
+    )
+    .into_bytes()
+}
+
+/// `key=value` lines for a Pocklington certificate, printed under a prime.
+pub fn pocklington_fields(certificate: &PocklingtonCertificate) -> Vec<String> {
+    let factors: Vec<String> = certificate.factors.iter().map(BigUint::to_string).collect();
+    vec![
+        format!("pocklington_factors={}", factors.join(",")),
+        format!("pocklington_witness={}", certificate.witness),
+    ]
+}
+
+/// The same as a JSON object member, with its leading comma.
+pub fn pocklington_member(certificate: &PocklingtonCertificate) -> String {
+    let factors: Vec<String> = certificate
+        .factors
+        .iter()
+        .map(|q| format!("\"{q}\""))
+        .collect();
+    format!(
+        ",\"pocklington\":{{\"factors\":[{}],\"witness\":\"{}\"}}",
+        factors.join(","),
+        certificate.witness
+    )
+}
+
+/// Replace `path` with `contents` atomically.
+///
+/// The data goes to a temporary file in the same directory, is synced, and
+/// is then renamed over `path`, so readers never see a partial file. On Unix
+/// the file is created with mode 0600.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = temp_path(path);
+    let result = write_new(&tmp, contents).and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+fn write_new(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// `.NAME.PID.tmp` next to `path`.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.{}.tmp", std::process::id()))
+}
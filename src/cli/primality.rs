@@ -0,0 +1,156 @@
+//! Choosing the primality tests with `--test`.
+
+use clap::ValueEnum;
+use find_big_prime::{
+    Aks, BailliePsw, DEFAULT_FROBENIUS_ROUNDS, DEFAULT_MR_ROUNDS, FixedBases, Frobenius,
+    MillerRabin, SolovayStrassen, Stacked, StrongLucas, mr_error_log2, mr_rounds_for_error,
+    rounds_for_bits,
+};
+use num_bigint::BigUint;
+use serde::Deserialize;
+
+/// Tests accepted by `--test`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TestKind {
+    /// Miller–Rabin with `--rounds` random witnesses.
+    #[default]
+    MillerRabin,
+    /// Baillie–PSW: strong base-2 Miller–Rabin plus a strong Lucas test.
+    Bpsw,
+    /// Strong Lucas test with Selfridge parameters, on its own.
+    Lucas,
+    /// Solovay–Strassen with `--rounds` random bases.
+    Solovay,
+    /// Grantham's quadratic Frobenius test with `--rounds` rounds; 10 is plenty.
+    Frobenius,
+    /// AKS: a proof of primality, but far too slow beyond a few dozen bits.
+    Aks,
+}
+
+/// How many rounds the probabilistic tests run.
+#[derive(Clone, Copy, Debug)]
+pub enum Rounds {
+    /// Each test's own default.
+    Default,
+    /// `--rounds`.
+    Fixed(usize),
+    /// `--error-bound 2^-K`: enough rounds to push each test's bound below it.
+    ErrorBound(u32),
+}
+
+impl Rounds {
+    /// Rounds for `test` on `bits`-bit candidates, or `None` if it has none.
+    pub fn for_test(self, test: TestKind, bits: usize) -> Option<usize> {
+        let rounds = match (self, test) {
+            (_, TestKind::Bpsw | TestKind::Lucas | TestKind::Aks) => return None,
+            (Rounds::Fixed(rounds), _) => rounds,
+            (Rounds::Default, TestKind::MillerRabin) => rounds_for_bits(bits),
+            (Rounds::Default, TestKind::Solovay) => DEFAULT_MR_ROUNDS,
+            (Rounds::Default, TestKind::Frobenius) => DEFAULT_FROBENIUS_ROUNDS,
+            (Rounds::ErrorBound(k), TestKind::MillerRabin) => mr_rounds_for_error(bits, k),
+            // At most 1/2 per round.
+            (Rounds::ErrorBound(k), TestKind::Solovay) => k as usize,
+            // At most 1/7710 < 2^-12 per round.
+            (Rounds::ErrorBound(k), TestKind::Frobenius) => k.div_ceil(12) as usize,
+        };
+        Some(rounds.max(1))
+    }
+}
+
+/// Parse `--witnesses`: `first-K-primes` or a comma-separated list of bases.
+pub fn parse_witnesses(s: &str) -> Result<FixedBases, String> {
+    let s = s.trim();
+    if let Some(count) = s
+        .strip_prefix("first-")
+        .and_then(|rest| rest.strip_suffix("-primes"))
+    {
+        return match count.parse() {
+            Ok(0) | Err(_) => Err(format!("`{count}` is not a positive number of primes")),
+            Ok(count) => Ok(FixedBases::first_primes(count)),
+        };
+    }
+    let bases = s
+        .split(',')
+        .map(|base| {
+            base.trim()
+                .parse::<BigUint>()
+                .ok()
+                .filter(|base| *base >= BigUint::from(2u32))
+                .ok_or_else(|| format!("`{base}` is not a base of at least 2"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(FixedBases::new(bases))
+}
+
+/// Whether `tests` is just the default Miller–Rabin.
+pub fn is_default(tests: &[TestKind]) -> bool {
+    tests == [TestKind::MillerRabin]
+}
+
+/// `tests` run in order on `bits`-bit numbers, with Miller–Rabin to
+/// `witnesses` instead of random bases when given.
+pub fn stack(
+    tests: &[TestKind],
+    rounds: Rounds,
+    witnesses: Option<&FixedBases>,
+    bits: usize,
+) -> Stacked {
+    tests.iter().fold(Stacked::new(), |stack, &test| {
+        let rounds = rounds.for_test(test, bits).unwrap_or(0);
+        match test {
+            TestKind::MillerRabin => match witnesses {
+                Some(witnesses) => stack.then(witnesses.clone()),
+                None => stack.then(MillerRabin::new(rounds)),
+            },
+            TestKind::Bpsw => stack.then(BailliePsw),
+            TestKind::Lucas => stack.then(StrongLucas),
+            TestKind::Solovay => stack.then(SolovayStrassen::new(rounds)),
+            TestKind::Frobenius => stack.then(Frobenius::new(rounds)),
+            TestKind::Aks => stack.then(Aks),
+        }
+    })
+}
+
+/// log2 of the bound on the chance that a `bits`-bit composite passes all
+/// of `tests`, or `None` if none of them has one.
+///
+/// Miller–Rabin gets its average-case bound only for `random` candidates
+/// and the worst-case 4^-t otherwise. A number must pass every test, so the
+/// tightest bound wins. Baillie–PSW,
+/// Lucas and fixed `witnesses` have no proven bound, and AKS needs none.
+pub fn error_log2(
+    tests: &[TestKind],
+    rounds: Rounds,
+    witnesses: Option<&FixedBases>,
+    bits: usize,
+    random: bool,
+) -> Option<f64> {
+    tests
+        .iter()
+        .filter_map(|&test| {
+            let rounds = rounds.for_test(test, bits)? as f64;
+            match test {
+                TestKind::MillerRabin if witnesses.is_some() => None,
+                TestKind::MillerRabin if random => Some(mr_error_log2(bits, rounds as usize)),
+                TestKind::MillerRabin => Some(-2.0 * rounds),
+                TestKind::Solovay => Some(-rounds),
+                TestKind::Frobenius => Some(-rounds * 7710f64.log2()),
+                _ => None,
+            }
+        })
+        .reduce(f64::min)
+}
+
+/// `2^-N`, the smallest power of two at or above the bound `2^log2`.
+pub fn format_error(log2: f64) -> String {
+    format!("2^{}", log2.ceil() as i64)
+}
+
+/// A warning to print before running `tests`, if any of them is impractically slow.
+pub fn speed_warning(tests: &[TestKind]) -> Option<&'static str> {
+    tests.contains(&TestKind::Aks).then_some(
+        "warning: AKS takes minutes on numbers of a few dozen bits and will not finish \
+         on cryptographic sizes; use it to teach or cross-check, not to generate keys.",
+    )
+}
@@ -0,0 +1,223 @@
+//! Primo primality certificates, read into the steps `verify-cert` checks.
+//!
+//! A Primo certificate is an INI file: `[Candidate]` holds N, and sections
+//! `[1]`, `[2]`, … each prove the current N prime given a smaller R, which
+//! the next section proves in turn, until R is small enough to settle
+//! directly. Only the N − 1 and elliptic-curve tests are read; N + 1 tests
+//! are reported as unsupported.
+
+use find_big_prime::{EcppStep, PocklingtonCertificate, is_prime_deterministic};
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::Zero;
+
+/// The first line of every Primo certificate.
+const HEADER: &str = "[PRIMO - Primality Certificate]";
+
+/// Whether `text` looks like a Primo certificate.
+pub fn is_primo(text: &str) -> bool {
+    text.trim_start().starts_with(HEADER)
+}
+
+/// A Primo certificate as a chain of tests from `n` down.
+pub struct PrimoCertificate {
+    pub n: BigUint,
+    pub steps: Vec<PrimoStep>,
+}
+
+/// One section of a Primo certificate.
+pub enum PrimoStep {
+    /// Type 1: N − 1 = S·R with a witness B; the factor is R.
+    NMinusOne(PocklingtonCertificate),
+    /// Types 3 and 4: a point of order R on a curve over N.
+    Curve(EcppStep),
+}
+
+impl PrimoStep {
+    fn n(&self) -> &BigUint {
+        match self {
+            PrimoStep::NMinusOne(certificate) => &certificate.n,
+            PrimoStep::Curve(step) => &step.n,
+        }
+    }
+
+    /// The R the step rests on, which the next step must prove.
+    fn next(&self) -> &BigUint {
+        match self {
+            PrimoStep::NMinusOne(certificate) => &certificate.factors[0],
+            PrimoStep::Curve(step) => &step.q,
+        }
+    }
+
+    fn verify(&self) -> bool {
+        match self {
+            PrimoStep::NMinusOne(certificate) => certificate.verify(),
+            PrimoStep::Curve(step) => step.verify(),
+        }
+    }
+}
+
+impl PrimoCertificate {
+    /// Whether the chain proves `n` prime: every step holds, each rests on
+    /// the N of the next, and the last R is small enough to check directly.
+    pub fn verify(&self) -> bool {
+        let mut n = &self.n;
+        for step in &self.steps {
+            if step.n() != n || !step.verify() {
+                return false;
+            }
+            n = step.next();
+        }
+        is_prime_deterministic(n) == Some(true)
+    }
+}
+
+/// The sections of an INI file in order, each with its `key=value` pairs.
+type Sections<'a> = Vec<(&'a str, Vec<(&'a str, &'a str)>)>;
+
+/// Read a Primo certificate from `text`.
+pub fn parse(text: &str) -> Result<PrimoCertificate, String> {
+    let sections = sections(text)?;
+    let section = |name: &str| {
+        sections
+            .iter()
+            .find(|(section, _)| *section == name)
+            .map(|(_, pairs)| pairs)
+    };
+    let candidate = section("Candidate").ok_or("no [Candidate] section")?;
+    let n = natural(value(candidate, "N", "Candidate")?)?;
+
+    let mut steps = Vec::new();
+    let mut current = n.clone();
+    for index in 1.. {
+        let name = index.to_string();
+        let Some(pairs) = section(&name) else {
+            break;
+        };
+        let step = parse_step(&current, pairs, &name)?;
+        current = step.next().clone();
+        steps.push(step);
+    }
+    if steps.is_empty() {
+        return Err("no [1] section".to_owned());
+    }
+    Ok(PrimoCertificate { n, steps })
+}
+
+/// Split `text` into sections, skipping blank lines and `;` comments.
+fn sections(text: &str) -> Result<Sections<'_>, String> {
+    let mut sections: Sections<'_> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            sections.push((name.trim(), Vec::new()));
+        } else if let (Some((key, value)), Some((_, pairs))) =
+            (line.split_once('='), sections.last_mut())
+        {
+            pairs.push((key.trim(), value.trim()));
+        } else {
+            return Err(format!(
+                "line {}: expected [section] or key=value",
+                index + 1
+            ));
+        }
+    }
+    Ok(sections)
+}
+
+fn value<'a>(pairs: &[(&str, &'a str)], key: &str, section: &str) -> Result<&'a str, String> {
+    pairs
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| *v)
+        .ok_or_else(|| format!("[{section}] has no {key}="))
+}
+
+/// A Primo integer: decimal, or hex after `$` or `0x`, optionally negative.
+fn integer(s: &str) -> Result<BigInt, String> {
+    let (sign, digits) = match s.strip_prefix('-') {
+        Some(rest) => (Sign::Minus, rest),
+        None => (Sign::Plus, s),
+    };
+    let magnitude = match digits
+        .strip_prefix('$')
+        .or_else(|| digits.strip_prefix("0x"))
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::parse_bytes(digits.as_bytes(), 10),
+    };
+    magnitude
+        .map(|m| BigInt::from_biguint(sign, m))
+        .ok_or_else(|| format!("`{s}` is not a number"))
+}
+
+fn natural(s: &str) -> Result<BigUint, String> {
+    integer(s)?
+        .to_biguint()
+        .ok_or_else(|| format!("`{s}` is negative"))
+}
+
+/// The test in section `name`, proving `n` prime given a smaller prime.
+fn parse_step(n: &BigUint, pairs: &[(&str, &str)], name: &str) -> Result<PrimoStep, String> {
+    let has = |key: &str| pairs.iter().any(|(k, _)| *k == key);
+    let get = |key: &str| value(pairs, key, name);
+    let s = natural(get("S")?)?;
+    if s.is_zero() {
+        return Err(format!("[{name}] has S=0"));
+    }
+    if has("Q") {
+        return Err(format!("[{name}] is an N+1 test, which is not supported"));
+    }
+    if !has("W") {
+        let (r, remainder) = (n - 1u32).div_rem(&s);
+        if !remainder.is_zero() {
+            return Err(format!("[{name}]: S does not divide N - 1"));
+        }
+        return Ok(PrimoStep::NMinusOne(PocklingtonCertificate {
+            n: n.clone(),
+            factors: vec![r],
+            witness: natural(get("B")?)?,
+        }));
+    }
+
+    let modulus = BigInt::from_biguint(Sign::Plus, n.clone());
+    let reduce = |x: BigInt| x.mod_floor(&modulus);
+    let (a, b) = if has("J") {
+        // The curve with j-invariant J: A = 3J(1728 − J), B = 2J(1728 − J)².
+        let j = integer(get("J")?)?;
+        let k = reduce(BigInt::from(1728) - &j);
+        (reduce(3 * &j * &k), reduce(2 * &j * &k * &k))
+    } else {
+        (reduce(integer(get("A")?)?), reduce(integer(get("B")?)?))
+    };
+    let w = integer(get("W")?)?;
+    let m = (BigInt::from_biguint(Sign::Plus, n + 1u32) - w)
+        .to_biguint()
+        .ok_or_else(|| format!("[{name}]: N + 1 - W is negative"))?;
+    let (q, remainder) = m.div_rem(&s);
+    if !remainder.is_zero() {
+        return Err(format!("[{name}]: S does not divide N + 1 - W"));
+    }
+    // Primo gives only the abscissa T; scaling the curve by L = T³ + AT + B
+    // puts (TL, L²) on y² = x³ + AL²x + BL³.
+    let t = reduce(integer(get("T")?)?);
+    let l = reduce(&t * &t * &t + &a * &t + &b);
+    let l2 = reduce(&l * &l);
+    let to_natural = |x: BigInt| x.to_biguint().expect("reduced modulo a positive n");
+    Ok(PrimoStep::Curve(EcppStep {
+        n: n.clone(),
+        a: to_natural(reduce(&a * &l2)),
+        b: to_natural(reduce(&b * &l2 * &l)),
+        m,
+        q,
+        x: to_natural(reduce(&t * &l)),
+        y: to_natural(l2),
+    }))
+}
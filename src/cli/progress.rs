@@ -0,0 +1,46 @@
+//! Progress bar for long searches.
+
+use find_big_prime::CandidateEvent;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::f64::consts::LN_2;
+
+/// Twice the twin-prime constant: how much likelier 2q + 1 is to be prime
+/// for a prime q than a random odd number of the same size.
+const SAFE_PRIME_BIAS: f64 = 1.32;
+
+/// Expected number of candidates drawn before one is accepted.
+///
+/// A random odd `bits`-bit number is prime with probability about
+/// 2 / (bits · ln 2). Sieving changes how cheap rejections are, not how many
+/// draws it takes. A safe prime needs that many prime q's on top.
+pub fn expected_candidates(bits: usize, safe: bool) -> u64 {
+    let per_prime = |bits: usize| bits as f64 * LN_2 / 2.0;
+    let expected = if safe {
+        per_prime(bits - 1) * per_prime(bits) / SAFE_PRIME_BIAS
+    } else {
+        per_prime(bits)
+    };
+    expected.ceil() as u64
+}
+
+/// A stderr bar sized to the expected candidate count; hidden when stderr is
+/// not a terminal.
+pub fn bar(bits: usize, safe: bool) -> ProgressBar {
+    let bar = ProgressBar::new(expected_candidates(bits, safe));
+    bar.set_style(
+        ProgressStyle::with_template(
+            "{spinner} [{elapsed_precise}] {bar:30} {pos}/~{len} candidates ({per_sec}, eta {eta})",
+        )
+        .expect("valid progress template"),
+    );
+    bar
+}
+
+/// Advance `bar` for each drawn candidate and restart it once a prime is found.
+pub fn observe(bar: &ProgressBar, event: &CandidateEvent<'_>) {
+    match event {
+        CandidateEvent::Drawn(_) => bar.inc(1),
+        CandidateEvent::Accepted(_) => bar.reset(),
+        _ => {}
+    }
+}
@@ -0,0 +1,164 @@
+//! Provable primes for `generate --provable`.
+
+use clap::ValueEnum;
+use find_big_prime::{PocklingtonStep, maurer, shawe_taylor};
+use num_bigint::BigUint;
+use rand::RngCore;
+use rand::rngs::OsRng;
+
+/// Bytes of a seed drawn when `--seed` is not given: 256 bits.
+const SEED_BYTES: usize = 32;
+
+/// Constructions accepted by `--provable`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ProvableMethod {
+    /// FIPS 186-4 C.6: Shawe-Taylor from a SHA-256 seed, reproducible from the seed.
+    #[default]
+    ShaweTaylor,
+    /// Maurer's recursive algorithm: nearly uniform random primes, with a certificate.
+    Maurer,
+}
+
+impl ProvableMethod {
+    /// The name `--provable` takes.
+    pub fn name(self) -> &'static str {
+        match self {
+            ProvableMethod::ShaweTaylor => "shawe-taylor",
+            ProvableMethod::Maurer => "maurer",
+        }
+    }
+}
+
+/// A `--seed`, kept as bytes so leading zeros survive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Seed(pub Vec<u8>);
+
+impl Seed {
+    /// A fresh seed from the OS RNG.
+    pub fn random() -> Self {
+        let mut bytes = vec![0u8; SEED_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        Seed(bytes)
+    }
+
+    /// Lowercase hex, as `--seed` takes it.
+    pub fn hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+/// Parse `--seed`: an even number of hex digits, optionally `0x`-prefixed.
+pub fn parse_seed(s: &str) -> Result<Seed, String> {
+    let s = s.trim();
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    if digits.is_empty()
+        || !digits.len().is_multiple_of(2)
+        || !digits.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return Err(format!("`{s}` is not an even number of hex digits"));
+    }
+    let bytes = (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).expect("checked hex digits"))
+        .collect();
+    Ok(Seed(bytes))
+}
+
+/// A provable prime and what regenerates or certifies it.
+pub struct Built {
+    pub prime: BigUint,
+    pub proof: Proof,
+    /// A prime above √prime dividing prime − 1, if the construction has one.
+    pub factor: Option<BigUint>,
+}
+
+/// What each construction records alongside its prime.
+pub enum Proof {
+    /// The input seed and the FIPS 186-4 `prime_gen_counter`.
+    ShaweTaylor { seed: Seed, counter: u64 },
+    /// The base prime and one (r, a) pair per level of the recursion.
+    Maurer {
+        base: BigUint,
+        steps: Vec<PocklingtonStep>,
+    },
+}
+
+impl Proof {
+    /// `key=value` lines printed above a single prime.
+    pub fn fields(&self) -> Vec<String> {
+        match self {
+            Proof::ShaweTaylor { seed, counter } => vec![
+                format!("seed={}", seed.hex()),
+                format!("prime_gen_counter={counter}"),
+            ],
+            Proof::Maurer { base, steps } => {
+                let mut fields = vec![format!("certificate_base={base}")];
+                fields.extend(
+                    steps
+                        .iter()
+                        .map(|step| format!("certificate={}:{}", step.r, step.witness)),
+                );
+                fields
+            }
+        }
+    }
+
+    /// The same as JSON object members, without braces.
+    pub fn json_members(&self) -> String {
+        match self {
+            Proof::ShaweTaylor { seed, counter } => {
+                format!(
+                    "\"seed\":\"{}\",\"prime_gen_counter\":{counter}",
+                    seed.hex()
+                )
+            }
+            Proof::Maurer { base, steps } => {
+                let steps: Vec<String> = steps
+                    .iter()
+                    .map(|step| {
+                        format!("{{\"r\":\"{}\",\"witness\":\"{}\"}}", step.r, step.witness)
+                    })
+                    .collect();
+                format!(
+                    "\"certificate\":{{\"base\":\"{base}\",\"steps\":[{}]}}",
+                    steps.join(",")
+                )
+            }
+        }
+    }
+}
+
+/// Build a `bits`-bit prime with `method`, from `seed` for Shawe-Taylor,
+/// which then moves on to the seed the construction left. `None` if the
+/// construction gives up on this seed.
+pub fn construct(method: ProvableMethod, bits: usize, seed: &mut Seed) -> Option<Built> {
+    match method {
+        ProvableMethod::ShaweTaylor => {
+            let built = shawe_taylor(bits as u64, &seed.0)?;
+            let input = std::mem::replace(seed, Seed(built.prime_seed));
+            Some(Built {
+                prime: built.prime,
+                proof: Proof::ShaweTaylor {
+                    seed: input,
+                    counter: built.prime_gen_counter,
+                },
+                factor: built.factor,
+            })
+        }
+        ProvableMethod::Maurer => {
+            let built = maurer(bits as u64, &mut OsRng);
+            let factor = built
+                .steps
+                .last()
+                .map(|step| (&built.prime - 1u32) / (&step.r << 1u32));
+            Some(Built {
+                prime: built.prime,
+                proof: Proof::Maurer {
+                    base: built.base,
+                    steps: built.steps,
+                },
+                factor,
+            })
+        }
+    }
+}
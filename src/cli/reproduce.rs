@@ -0,0 +1,158 @@
+//! `reproduce` subcommand.
+
+use clap::Args;
+use find_big_prime::{PrimalityTest, bi_twin_members, nums_prime};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use std::fs;
+use std::io::{self, Read};
+use std::iter;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::bundle::{Bundle, Mode};
+use super::json::Json;
+use super::primality;
+use super::provable::{self, ProvableMethod};
+use super::{CliError, EXIT_CERTIFICATE};
+
+/// Options for redoing a generation from its bundle.
+#[derive(Args, Debug)]
+pub struct ReproduceArgs {
+    /// A bundle from `generate --bundle`; reads stdin when omitted or `-`.
+    file: Option<PathBuf>,
+}
+
+/// Re-derive the primes of a seeded bundle and compare them, or recheck
+/// those of a search, printing `verdict=match`, `rechecked` or `mismatch`
+/// with the certificate exit code for a mismatch.
+pub fn run(args: &ReproduceArgs) -> Result<ExitCode, CliError> {
+    let text = match args.file.as_deref() {
+        Some(path) if path.as_os_str() != "-" => fs::read_to_string(path)?,
+        _ => {
+            let mut buf = String::new();
+            io::stdin().read_to_string(&mut buf)?;
+            buf
+        }
+    };
+    let value = Json::parse(text.trim()).map_err(CliError::Bundle)?;
+    let bundle = Bundle::from_json(&value).map_err(CliError::Bundle)?;
+    let version = env!("CARGO_PKG_VERSION");
+    if bundle.crate_version != version {
+        eprintln!(
+            "warning: the bundle was written by version {}, this is {version}",
+            bundle.crate_version
+        );
+    }
+
+    // The size of each prime, in the order `generate` made them.
+    let extra = usize::from(bundle.sophie_germain);
+    let sizes: Vec<usize> = bundle
+        .bits
+        .iter()
+        .flat_map(|&bits| iter::repeat_n(bits + extra, bundle.count as usize))
+        .collect();
+    let rederived = match &bundle.mode {
+        Mode::ShaweTaylor(seed) => {
+            let mut seed = seed.clone();
+            Some(
+                sizes
+                    .iter()
+                    .map_while(|&bits| {
+                        provable::construct(ProvableMethod::ShaweTaylor, bits, &mut seed)
+                    })
+                    .map(|built| built.prime)
+                    .collect::<Vec<BigUint>>(),
+            )
+        }
+        Mode::Phrase(phrase) => {
+            let mut primes = Vec::new();
+            for &bits in &bundle.bits {
+                let bits = bits + extra;
+                let mut from = 0;
+                for _ in 0..bundle.count {
+                    let Some(derived) =
+                        nums_prime(phrase.as_bytes(), bits as u64, bundle.safe, from)
+                    else {
+                        break;
+                    };
+                    from = derived.counter + 1;
+                    primes.push(derived.prime);
+                }
+            }
+            Some(primes)
+        }
+        Mode::Search | Mode::Maurer => None,
+    };
+
+    println!("mode={}", bundle.mode.name());
+    println!("crate_version={}", bundle.crate_version);
+    println!("primes={}", bundle.primes.len());
+    let verdict = match rederived {
+        Some(primes) => {
+            println!("rederived=true");
+            for (index, (expected, found)) in primes.iter().zip(&bundle.primes).enumerate() {
+                if expected != found {
+                    println!("mismatch={index}");
+                }
+            }
+            if primes == bundle.primes {
+                "match"
+            } else {
+                "mismatch"
+            }
+        }
+        None => {
+            // Primes drawn from the OS RNG cannot be drawn again; run the
+            // bundle's tests on them instead.
+            println!("rederived=false");
+            let mut holds = bundle.primes.len() == sizes.len();
+            for (index, (p, &bits)) in bundle.primes.iter().zip(&sizes).enumerate() {
+                let test = primality::stack(
+                    &bundle.test,
+                    bundle.rounds(),
+                    bundle.witnesses.as_ref(),
+                    bits,
+                );
+                let passes = |n: &BigUint| test.is_probable_prime(n, &mut OsRng);
+                let ok = p.bits() == bits as u64
+                    && passes(p)
+                    && (!bundle.safe || passes(&(p >> 1u32)))
+                    && (!bundle.twin || passes(&(p + 2u32)))
+                    && (!bundle.blum || p % 4u32 == BigUint::from(3u32))
+                    && bundle
+                        .congruence
+                        .as_ref()
+                        .is_none_or(|class| class.contains(p))
+                    && bits >= bundle.top_bits
+                    && (p >> (bits - bundle.top_bits)).count_ones() == bundle.top_bits as u64
+                    && bundle
+                        .hamming_weight
+                        .is_none_or(|weight| p.count_ones() == weight as u64)
+                    && bundle.prefix.as_ref().is_none_or(|prefix| {
+                        p.bits() >= prefix.bits() && &(p >> (p.bits() - prefix.bits())) == prefix
+                    })
+                    && bundle.chain.is_none_or(|(kind, length)| {
+                        kind.members(p, length).iter().skip(1).all(passes)
+                    })
+                    && bundle.bi_twin.is_none_or(|links| {
+                        bi_twin_members(&(p + 1u32), links)
+                            .iter()
+                            .skip(1)
+                            .all(passes)
+                    });
+                if !ok {
+                    println!("mismatch={index}");
+                    holds = false;
+                }
+            }
+            if holds { "rechecked" } else { "mismatch" }
+        }
+    };
+    println!("verdict={verdict}");
+    Ok(if verdict == "mismatch" {
+        ExitCode::from(EXIT_CERTIFICATE)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
@@ -0,0 +1,176 @@
+//! `selftest` subcommand.
+
+use clap::Args;
+use find_big_prime::{FixedBases, PrimalityTest};
+use num_bigint::BigUint;
+use num_traits::One;
+use rand::rngs::OsRng;
+use std::process::ExitCode;
+
+use super::primality::{self, Rounds, TestKind};
+use super::{CliError, EXIT_SELFTEST};
+
+/// Carmichael numbers: Fermat pseudoprimes to every coprime base.
+const CARMICHAEL: [u128; 33] = [
+    561, 1105, 1729, 2465, 2821, 6601, 8911, 10585, 15841, 29341, 41041, 46657, 52633, 62745,
+    63973, 75361, 101101, 115921, 126217, 162401, 172081, 188461, 252601, 278545, 294409, 314821,
+    334153, 340561, 399001, 410041, 449065, 488881, 512461,
+];
+
+/// Strong pseudoprimes to base 2, then the least strong pseudoprime to all
+/// of the first 2, 3, 4, 5, 6, 7, 9, 12 and 13 prime bases.
+const STRONG_PSEUDOPRIMES: [u128; 14] = [
+    2047,
+    3277,
+    4033,
+    4681,
+    8321,
+    1_373_653,
+    25_326_001,
+    3_215_031_751,
+    2_152_302_898_747,
+    3_474_749_660_383,
+    341_550_071_728_321,
+    3_825_123_056_546_413_051,
+    318_665_857_834_031_151_167_461,
+    3_317_044_064_679_887_385_961_981,
+];
+
+/// Strong Lucas pseudoprimes with Selfridge parameters.
+const LUCAS_PSEUDOPRIMES: [u128; 10] = [
+    5459, 5777, 10877, 16109, 18971, 22499, 24569, 25199, 40309, 58519,
+];
+
+/// Small primes, including the Fermat prime 65537 and 2^31 − 1.
+const SMALL_PRIMES: [u128; 9] = [2, 3, 5, 7, 11, 13, 65537, 1_000_000_007, 2_147_483_647];
+
+/// AKS only sees corpus entries up to this size, so the run finishes.
+const AKS_MAX_BITS: u64 = 24;
+
+/// Options for running the primality tests against known answers.
+#[derive(Args, Debug)]
+pub struct SelftestArgs {
+    /// Primality test to validate, or several separated by commas that must all pass.
+    #[arg(long = "test", env = "FBP_TEST", value_enum, value_delimiter = ',', default_values_t = [TestKind::MillerRabin])]
+    test: Vec<TestKind>,
+
+    /// Rounds for the probabilistic tests (default: as `generate` picks them).
+    #[arg(long = "rounds", env = "FBP_ROUNDS")]
+    rounds: Option<usize>,
+
+    /// Run Miller–Rabin to these bases instead of random ones: `first-K-primes` or a list like `2,3,5,7`.
+    #[arg(long = "witnesses", value_name = "BASES", value_parser = primality::parse_witnesses, conflicts_with = "rounds")]
+    witnesses: Option<FixedBases>,
+}
+
+/// A named list of numbers that are all prime or all composite.
+pub struct Corpus {
+    pub name: &'static str,
+    pub prime: bool,
+    /// Tests that, correctly implemented, call these composites prime.
+    fools: &'static [TestKind],
+    pub numbers: Vec<BigUint>,
+}
+
+impl Corpus {
+    /// The verdict a correct build of `tests`, all of which must pass, gives.
+    pub fn expected(&self, tests: &[TestKind]) -> bool {
+        self.prime || tests.iter().all(|test| self.fools.contains(test))
+    }
+}
+
+/// The built-in known answers: pseudoprimes that weak tests let through,
+/// and primes, some of cryptographic size, that every test must accept.
+pub fn corpora() -> Vec<Corpus> {
+    let listed = |numbers: &[u128]| numbers.iter().map(|&n| BigUint::from(n)).collect();
+    let power = |e: u32| BigUint::one() << e;
+    let mersenne = |p: u32| power(p) - 1u32;
+    vec![
+        Corpus {
+            name: "carmichael",
+            prime: false,
+            fools: &[],
+            numbers: listed(&CARMICHAEL),
+        },
+        Corpus {
+            name: "strong-pseudoprime",
+            prime: false,
+            fools: &[],
+            numbers: listed(&STRONG_PSEUDOPRIMES),
+        },
+        Corpus {
+            name: "lucas-pseudoprime",
+            prime: false,
+            fools: &[TestKind::Lucas],
+            numbers: listed(&LUCAS_PSEUDOPRIMES),
+        },
+        Corpus {
+            name: "prime",
+            prime: true,
+            fools: &[],
+            numbers: [
+                listed(&SMALL_PRIMES),
+                vec![
+                    mersenne(61),
+                    mersenne(89),
+                    mersenne(127),
+                    power(130) - 5u32,
+                    power(255) - 19u32,
+                    power(448) - power(224) - 1u32,
+                    mersenne(521),
+                ],
+            ]
+            .concat(),
+        },
+    ]
+}
+
+/// Print a line per wrong verdict and a summary; exits with the selftest
+/// code if any verdict was wrong.
+pub fn run(args: &SelftestArgs) -> Result<ExitCode, CliError> {
+    if let Some(warning) = primality::speed_warning(&args.test) {
+        eprintln!("{warning}");
+    }
+    let rounds = args.rounds.map_or(Rounds::Default, Rounds::Fixed);
+    let (mut passed, mut failed, mut skipped) = (0u64, 0u64, 0u64);
+    for corpus in corpora() {
+        let expected = corpus.expected(&args.test);
+        for n in &corpus.numbers {
+            if args.test.contains(&TestKind::Aks) && n.bits() > AKS_MAX_BITS {
+                skipped += 1;
+                continue;
+            }
+            let stack = primality::stack(
+                &args.test,
+                rounds,
+                args.witnesses.as_ref(),
+                n.bits() as usize,
+            );
+            let prime = stack.is_probable_prime(n, &mut OsRng);
+            if prime == expected {
+                passed += 1;
+            } else {
+                failed += 1;
+                println!(
+                    "corpus={} n={n} expected={} got={}",
+                    corpus.name,
+                    verdict(expected),
+                    verdict(prime)
+                );
+            }
+        }
+    }
+    println!(
+        "total={} passed={passed} failed={failed} skipped={skipped}",
+        passed + failed + skipped
+    );
+    Ok(if failed > 0 {
+        ExitCode::from(EXIT_SELFTEST)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn verdict(prime: bool) -> &'static str {
+    if prime { "probable_prime" } else { "composite" }
+}
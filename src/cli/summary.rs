@@ -0,0 +1,66 @@
+//! Human-friendly summary for terminals.
+
+use anstyle::{AnsiColor, Style};
+use clap::ColorChoice;
+use find_big_prime::GenerationReport;
+use num_bigint::BigUint;
+use std::io::{self, IsTerminal, Write};
+
+use super::primality;
+
+/// Width the prime is wrapped to.
+const WRAP: usize = 64;
+
+/// Whether to print the styled summary instead of `key=value` lines.
+///
+/// `auto` styles only when stdout is a terminal and `NO_COLOR` is unset.
+pub fn styled(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+    }
+}
+
+/// Print the bits of `shown`, its `form`, any error bound, time and
+/// attempts, then `prime`, the text of `shown`, wrapped at [`WRAP`]
+/// columns, and likewise each labeled companion.
+pub fn print(
+    report: &GenerationReport,
+    shown: &BigUint,
+    form: &str,
+    error_log2: Option<f64>,
+    prime: &str,
+    companions: &[(String, String)],
+) -> io::Result<()> {
+    let label = Style::new().bold();
+    let value = Style::new().fg_color(Some(AnsiColor::Green.into()));
+    let error = error_log2.map(|log2| ("error", format!("< {}", primality::format_error(log2))));
+    let mut out = io::stdout().lock();
+    for (key, val) in [
+        Some(("bits", shown.bits().to_string())),
+        Some(("form", form.to_owned())),
+        error,
+        Some(("time", format!("{:.2?}", report.elapsed))),
+        Some(("attempts", report.stats.candidates.to_string())),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        writeln!(out, "{label}{key:>8}{label:#}  {value}{val}{value:#}")?;
+    }
+    writeln!(out)?;
+    for line in prime.as_bytes().chunks(WRAP) {
+        out.write_all(line)?;
+        writeln!(out)?;
+    }
+    for (key, other) in companions {
+        writeln!(out)?;
+        writeln!(out, "{label}{}{label:#}", key.replace('_', " "))?;
+        for line in other.as_bytes().chunks(WRAP) {
+            out.write_all(line)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
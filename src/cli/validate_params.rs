@@ -0,0 +1,161 @@
+//! `validate-params` subcommand.
+
+use clap::Args;
+use find_big_prime::{
+    is_subgroup_generator, parse_number, validate_canonical_generator, validate_primes,
+};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::json::Json;
+use super::provable::{self, Seed};
+use super::{CliError, EXIT_CERTIFICATE};
+
+/// Options for validating seeded DSA domain parameters.
+#[derive(Args, Debug)]
+pub struct ValidateParamsArgs {
+    /// Parameters as `dsa-params` prints them, as `key=value` lines or JSON. Reads stdin when
+    /// omitted or `-`, unless they are given with `--p` and the options that go with it.
+    file: Option<PathBuf>,
+
+    /// The modulus p: decimal, 0x-prefixed hex, or base64.
+    #[arg(long = "p", value_name = "P", requires_all = ["q", "g", "seed", "counter"], conflicts_with = "file")]
+    p: Option<String>,
+
+    /// The subgroup order q.
+    #[arg(long = "q", value_name = "Q", requires = "p")]
+    q: Option<String>,
+
+    /// The generator g.
+    #[arg(long = "g", value_name = "G", requires = "p")]
+    g: Option<String>,
+
+    /// The hex domain_parameter_seed.
+    #[arg(long = "seed", value_name = "HEX", value_parser = provable::parse_seed, requires = "p")]
+    seed: Option<Seed>,
+
+    /// The counter at which p was found.
+    #[arg(long = "counter", value_name = "N", requires = "p")]
+    counter: Option<u64>,
+
+    /// The index g was derived with; without one, g only gets the partial check that it
+    /// generates the subgroup of order q.
+    #[arg(long = "index", value_name = "0-255")]
+    index: Option<u8>,
+}
+
+/// Seeded domain parameters to check.
+struct Params {
+    p: BigUint,
+    q: BigUint,
+    g: BigUint,
+    seed: Seed,
+    counter: u64,
+    index: Option<u8>,
+}
+
+/// Re-derive p and q from the seed and counter by FIPS 186-4 A.1.1.3, and
+/// g from the seed and index by A.2.4, printing `primes=`, `generator=`
+/// and `verdict=valid` or `verdict=invalid` with the certificate exit code.
+pub fn run(args: &ValidateParamsArgs) -> Result<ExitCode, CliError> {
+    let params = match (&args.p, &args.q, &args.g, &args.seed, args.counter) {
+        (Some(p), Some(q), Some(g), Some(seed), Some(counter)) => Params {
+            p: parse_number(p)?,
+            q: parse_number(q)?,
+            g: parse_number(g)?,
+            seed: seed.clone(),
+            counter,
+            index: args.index,
+        },
+        _ => {
+            let text = match args.file.as_deref() {
+                Some(path) if path.as_os_str() != "-" => fs::read_to_string(path)?,
+                _ => {
+                    let mut buf = String::new();
+                    io::stdin().read_to_string(&mut buf)?;
+                    buf
+                }
+            };
+            let mut params = parse(&text)?;
+            params.index = args.index.or(params.index);
+            params
+        }
+    };
+
+    let primes = validate_primes(
+        &params.p,
+        &params.q,
+        &params.seed.0,
+        params.counter,
+        &mut OsRng,
+    );
+    let generator = match params.index {
+        Some(index) => {
+            validate_canonical_generator(&params.p, &params.q, &params.g, &params.seed.0, index)
+                .then_some("canonical")
+        }
+        None => is_subgroup_generator(&params.p, &params.q, &params.g).then_some("partial"),
+    };
+    let valid = primes && generator.is_some();
+    println!("bits={}", params.p.bits());
+    println!("q_bits={}", params.q.bits());
+    println!("primes={}", if primes { "valid" } else { "invalid" });
+    println!("generator={}", generator.unwrap_or("invalid"));
+    println!("verdict={}", if valid { "valid" } else { "invalid" });
+    Ok(if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_CERTIFICATE)
+    })
+}
+
+/// Read parameters from `dsa-params` output: `key=value` lines, skipping
+/// any others, or one JSON object.
+fn parse(text: &str) -> Result<Params, CliError> {
+    let mut values = BTreeMap::new();
+    if text.trim_start().starts_with('{') {
+        let Json::Object(members) = Json::parse(text.trim()).map_err(CliError::Params)? else {
+            unreachable!("text starts with `{{`");
+        };
+        for (key, value) in members {
+            if let Some(value) = value.as_number() {
+                values.insert(key, value.to_owned());
+            }
+        }
+    } else {
+        for line in text.lines() {
+            if let Some((key, value)) = line.trim().split_once('=') {
+                values.insert(key.to_owned(), value.to_owned());
+            }
+        }
+    }
+    let get = |key: &str| {
+        values
+            .get(key)
+            .ok_or_else(|| CliError::Params(format!("no {key}")))
+    };
+    let small = |key: &str| -> Result<u64, CliError> {
+        get(key)?
+            .parse()
+            .map_err(|_| CliError::Params(format!("{key} is not a small number")))
+    };
+    Ok(Params {
+        p: parse_number(get("p")?)?,
+        q: parse_number(get("q")?)?,
+        g: parse_number(get("g")?)?,
+        seed: provable::parse_seed(get("domain_parameter_seed")?).map_err(CliError::Params)?,
+        counter: small("counter")?,
+        index: match values.get("index") {
+            Some(_) => Some(
+                u8::try_from(small("index")?)
+                    .map_err(|_| CliError::Params("index is not below 256".to_owned()))?,
+            ),
+            None => None,
+        },
+    })
+}
@@ -0,0 +1,298 @@
+//! `verify-cert` subcommand.
+
+use clap::{Args, ValueEnum};
+use find_big_prime::{EcppCertificate, EcppStep, PrattCertificate, parse_number};
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::certificate::{Certificate, Verdict};
+use super::json::Json;
+use super::primo;
+use super::{CliError, EXIT_CERTIFICATE};
+
+/// Kinds of certificate `check --certificate` prints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CertificateKind {
+    /// Pratt: every p − 1 factored down to 2; primes up to about a hundred bits.
+    Pratt,
+    /// Atkin–Morain elliptic curves: primes of any size, slowly past a thousand bits.
+    Ecpp,
+}
+
+impl CertificateKind {
+    /// The name `--certificate` takes.
+    pub fn name(self) -> &'static str {
+        match self {
+            CertificateKind::Pratt => "pratt",
+            CertificateKind::Ecpp => "ecpp",
+        }
+    }
+}
+
+/// Options for checking a primality certificate.
+#[derive(Args, Debug)]
+pub struct VerifyCertArgs {
+    /// Certificate file: `check --certificate` output, `generate --json` records or a Primo
+    /// certificate. Reads stdin when omitted or `-`.
+    file: Option<PathBuf>,
+
+    /// Print each certificate as a portable JSON document instead of its verdict.
+    ///
+    /// The exit code still reports the verdict. This turns `check --certificate` lines and
+    /// `generate --json` records into the stable format every later version reads.
+    #[arg(long = "json")]
+    json: bool,
+}
+
+/// One `pratt=` line: a prime's witness and the primes dividing it minus one.
+pub type Step = (BigUint, Vec<BigUint>);
+
+/// The distinct odd primes of the tree from `certificate.n` down, in
+/// breadth-first order, each with the certificates of the primes of p − 1.
+pub fn pratt_steps(certificate: &PrattCertificate) -> Vec<&PrattCertificate> {
+    let mut steps = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut queue = VecDeque::from([certificate]);
+    while let Some(node) = queue.pop_front() {
+        if node.factors.is_empty() || !seen.insert(&node.n) {
+            continue;
+        }
+        steps.push(node);
+        queue.extend(&node.factors);
+    }
+    steps
+}
+
+/// `pratt=p:a:q1,q2` lines, one per distinct odd prime p in the tree from
+/// `certificate.n` down, giving its witness a and the primes q of p − 1.
+pub fn pratt_fields(certificate: &PrattCertificate) -> Vec<String> {
+    pratt_steps(certificate)
+        .into_iter()
+        .map(|node| {
+            let factors: Vec<String> = node.factors.iter().map(|q| q.n.to_string()).collect();
+            format!("pratt={}:{}:{}", node.n, node.witness, factors.join(","))
+        })
+        .collect()
+}
+
+/// `ecpp=n:a:b:m:q:x:y` lines, one per step from `certificate.n` down: the
+/// curve y² = x³ + ax + b, the multiple m of the order of P = (x, y) and
+/// the prime q dividing it that the next line proves.
+pub fn ecpp_fields(certificate: &EcppCertificate) -> Vec<String> {
+    certificate
+        .steps
+        .iter()
+        .map(|step| {
+            format!(
+                "ecpp={}:{}:{}:{}:{}:{}:{}",
+                step.n, step.a, step.b, step.m, step.q, step.x, step.y
+            )
+        })
+        .collect()
+}
+
+/// Print `verdict=prime` if the certificate proves its number prime, and
+/// `verdict=invalid` with the certificate exit code if it does not.
+///
+/// Besides the lines `check --certificate` prints, this reads Primo
+/// certificates, certificate documents and the records of
+/// `generate --json`, one per line.
+pub fn run(args: &VerifyCertArgs) -> Result<ExitCode, CliError> {
+    let text = match args.file.as_deref() {
+        Some(path) if path.as_os_str() != "-" => fs::read_to_string(path)?,
+        _ => {
+            let mut buf = String::new();
+            io::stdin().read_to_string(&mut buf)?;
+            buf
+        }
+    };
+    let certificates = if primo::is_primo(&text) {
+        if args.json {
+            clap::Error::raw(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--json cannot rewrite a Primo certificate\n",
+            )
+            .exit();
+        }
+        vec![Certificate::Primo(
+            primo::parse(&text).map_err(CliError::Certificate)?,
+        )]
+    } else if text.trim_start().starts_with('{') {
+        documents(&text)?
+    } else {
+        vec![parse(&text)?]
+    };
+
+    let mut valid = true;
+    for (index, certificate) in certificates.iter().enumerate() {
+        let verdict = certificate.verdict();
+        valid &= verdict != Verdict::Invalid;
+        if let Some(document) = certificate.to_json().filter(|_| args.json) {
+            println!("{document}");
+        } else if certificates.len() == 1 {
+            println!("certificate={}", certificate.name());
+            println!("bits={}", certificate.n().bits());
+            println!("verdict={}", verdict.name());
+        } else {
+            println!(
+                "record={} certificate={} bits={} verdict={}",
+                index + 1,
+                certificate.name(),
+                certificate.n().bits(),
+                verdict.name()
+            );
+        }
+    }
+    Ok(if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_CERTIFICATE)
+    })
+}
+
+/// Read each JSON object in `text`, one per line, skipping blank lines.
+fn documents(text: &str) -> Result<Vec<Certificate>, CliError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            Json::parse(line)
+                .and_then(|value| Certificate::from_json(&value))
+                .map_err(|message| invalid(index + 1, &message))
+        })
+        .collect()
+}
+
+/// Read a certificate from the `pratt=` or the `ecpp=` lines of `text`;
+/// other `key=value` lines, blank lines and `#` comments are skipped, so
+/// saved `check` output reads as is.
+fn parse(text: &str) -> Result<Certificate, CliError> {
+    let has = |key: &str| lines(text, key).next().is_some();
+    match (has("pratt"), has("ecpp")) {
+        (true, true) => Err(CliError::Certificate(
+            "both pratt= and ecpp= lines".to_owned(),
+        )),
+        (false, true) => parse_ecpp(text).map(Certificate::Ecpp),
+        _ => parse_pratt(text).map(Certificate::Pratt),
+    }
+}
+
+/// The values of the `key=` lines of `text`, with their line numbers.
+fn lines<'a>(text: &'a str, key: &'a str) -> impl Iterator<Item = (usize, &'a str)> {
+    text.lines().enumerate().filter_map(move |(index, line)| {
+        let value = line.trim().strip_prefix(key)?.strip_prefix('=')?;
+        Some((index + 1, value))
+    })
+}
+
+/// A malformed-certificate error pointing at line `line`.
+fn invalid(line: usize, message: &str) -> CliError {
+    CliError::Certificate(format!("line {line}: {message}"))
+}
+
+fn number(line: usize, s: &str) -> Result<BigUint, CliError> {
+    parse_number(s).map_err(|err| invalid(line, &err.to_string()))
+}
+
+/// The numbers in `value` between each `separator`.
+fn numbers(line: usize, value: &str, separator: char) -> Result<Vec<BigUint>, CliError> {
+    value.split(separator).map(|s| number(line, s)).collect()
+}
+
+/// The chain of `ecpp=` steps, starting at the first.
+fn parse_ecpp(text: &str) -> Result<EcppCertificate, CliError> {
+    let mut steps = Vec::new();
+    for (line, value) in lines(text, "ecpp") {
+        let Ok([n, a, b, m, q, x, y]) = <[BigUint; 7]>::try_from(numbers(line, value, ':')?) else {
+            return Err(invalid(line, "expected ecpp=n:a:b:m:q:x:y"));
+        };
+        steps.push(EcppStep {
+            n,
+            a,
+            b,
+            m,
+            q,
+            x,
+            y,
+        });
+    }
+    let n = steps
+        .first()
+        .map(|step| step.n.clone())
+        .ok_or_else(|| CliError::Certificate("no ecpp= lines".to_owned()))?;
+    Ok(EcppCertificate { n, steps })
+}
+
+/// Rebuild the certificate tree from the `pratt=` lines of `text`, rooted at
+/// the first.
+fn parse_pratt(text: &str) -> Result<PrattCertificate, CliError> {
+    let mut root = None;
+    let mut steps = BTreeMap::new();
+    for (line, value) in lines(text, "pratt") {
+        let mut parts = value.split(':');
+        let (Some(p), Some(a), Some(qs), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid(line, "expected pratt=p:a:q1,q2,..."));
+        };
+        let (p, a) = (number(line, p)?, number(line, a)?);
+        let qs = numbers(line, qs, ',')?;
+        if steps.insert(p.clone(), (a, qs)).is_some() {
+            return Err(invalid(line, &format!("{p} is certified twice")));
+        }
+        root.get_or_insert(p);
+    }
+    let root = root.ok_or_else(|| CliError::Certificate("no pratt= lines".to_owned()))?;
+    build(&root, &steps).map_err(CliError::Certificate)
+}
+
+/// The certificate for `p` from `steps`, with 2 needing none. Each factor
+/// must be below its prime, so the recursion ends even on a crafted file.
+pub fn build(p: &BigUint, steps: &BTreeMap<BigUint, Step>) -> Result<PrattCertificate, String> {
+    if *p == BigUint::from(2u32) {
+        return Ok(PrattCertificate {
+            n: p.clone(),
+            witness: BigUint::from(1u32),
+            factors: Vec::new(),
+        });
+    }
+    let (witness, qs) = steps.get(p).ok_or_else(|| format!("no step for {p}"))?;
+    let factors = qs
+        .iter()
+        .map(|q| {
+            if q >= p {
+                return Err(format!("factor {q} is not below {p}"));
+            }
+            build(q, steps)
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(PrattCertificate {
+        n: p.clone(),
+        witness: witness.clone(),
+        factors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use find_big_prime::ecpp_certificate;
+
+    #[test]
+    fn ecpp_lines_read_back() {
+        let n = (BigUint::from(1u32) << 127u32) - 1u32;
+        let certificate = ecpp_certificate(&n).unwrap();
+        let text = ecpp_fields(&certificate).join("\n");
+        match parse(&text) {
+            Ok(Certificate::Ecpp(read)) => {
+                assert_eq!(read, certificate);
+                assert!(read.verify());
+            }
+            _ => panic!("not read back as an ECPP certificate"),
+        }
+    }
+}
@@ -0,0 +1,416 @@
+//! `no_std` + `alloc` primality arithmetic.
+//!
+//! Everything here depends only on `num-bigint` and `rand_core` traits, so
+//! it builds without the `std` feature for embedded, SGX and wasm targets.
+//! Callers supply their own RNG.
+
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+/// Object-safe combination of [`RngCore`] and [`CryptoRng`].
+pub trait CryptoRngCore: RngCore + CryptoRng {}
+
+impl<T: RngCore + CryptoRng + ?Sized> CryptoRngCore for T {}
+
+/// Miller–Rabin probabilistic primality test with witnesses drawn from `rng`.
+pub fn is_probable_prime_with_rng<R: RngCore + CryptoRng + ?Sized>(
+    n: &BigUint,
+    rounds: usize,
+    rng: &mut R,
+) -> bool {
+    miller_rabin(n, rounds, rng, &mut |_| {})
+}
+
+/// Why a number failed the primality test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Compositeness {
+    /// Zero or one, which are neither prime nor composite.
+    TooSmall,
+    /// A small prime that divides the number.
+    Divisor(u32),
+    /// A Miller–Rabin witness `a`: with n - 1 = d * 2^s, a^d mod n is not 1
+    /// and none of its first s squarings is n - 1.
+    Witness(BigUint),
+}
+
+/// Trial division followed by Miller–Rabin, returning the evidence for a
+/// composite `n` or `None` when it is a probable prime.
+pub fn compositeness_witness_with_rng<R: RngCore + CryptoRng + ?Sized>(
+    n: &BigUint,
+    rounds: usize,
+    rng: &mut R,
+) -> Option<Compositeness> {
+    if let Some(verdict) = trial_division(n) {
+        return verdict;
+    }
+    mr_witness(n, rounds, rng, &mut |_| {}).map(|a| Compositeness::Witness(a.0.clone()))
+}
+
+/// Like [`compositeness_witness_with_rng`], but running Miller–Rabin to
+/// each of `bases` instead of to random witnesses.
+pub fn compositeness_witness_bases(n: &BigUint, bases: &[BigUint]) -> Option<Compositeness> {
+    if let Some(verdict) = trial_division(n) {
+        return verdict;
+    }
+    fixed_base_witness(n, bases, &mut |_| {}).map(|a| Compositeness::Witness(a.clone()))
+}
+
+/// `Some` result of [`compositeness_witness_with_rng`] if trial division
+/// settles `n`, or `None` if it needs Miller–Rabin.
+pub(crate) fn trial_division(n: &BigUint) -> Option<Option<Compositeness>> {
+    if *n < BigUint::from(2u32) {
+        return Some(Some(Compositeness::TooSmall));
+    }
+    for p in ::core::iter::once(2).chain(SMALL_PRIMES) {
+        let p_big = BigUint::from(p);
+        if *n == p_big {
+            return Some(None);
+        }
+        if (n % &p_big).is_zero() {
+            return Some(Some(Compositeness::Divisor(p)));
+        }
+    }
+    None
+}
+
+/// An intermediate derived from the candidate or a witness.
+///
+/// With the `zeroize` feature it is wiped when dropped, as far as
+/// [`wipe`](crate::secret::wipe) can reach.
+pub(crate) struct Scratch(pub(crate) BigUint);
+
+#[cfg(feature = "zeroize")]
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        crate::secret::wipe(&mut self.0);
+    }
+}
+
+/// Miller–Rabin on `num-bigint`, or on GMP when the `gmp` feature is enabled.
+pub(crate) fn miller_rabin<R: RngCore + CryptoRng + ?Sized>(
+    n: &BigUint,
+    rounds: usize,
+    rng: &mut R,
+    on_round: &mut dyn FnMut(usize),
+) -> bool {
+    #[cfg(feature = "gmp")]
+    return crate::gmp::miller_rabin(n, rounds, rng, on_round);
+    #[cfg(not(feature = "gmp"))]
+    miller_rabin_biguint(n, rounds, rng, on_round)
+}
+
+#[cfg_attr(feature = "gmp", allow(dead_code))]
+fn miller_rabin_biguint<R: RngCore + CryptoRng + ?Sized>(
+    n: &BigUint,
+    rounds: usize,
+    rng: &mut R,
+    on_round: &mut dyn FnMut(usize),
+) -> bool {
+    let two = BigUint::from(2u32);
+
+    if *n < two {
+        return false;
+    }
+    if *n == two {
+        return true;
+    }
+    if n.is_even() {
+        return false;
+    }
+
+    mr_witness(n, rounds, rng, on_round).is_none()
+}
+
+/// Run up to `rounds` Miller–Rabin rounds on an odd `n > 2`, returning the
+/// first witness to its compositeness.
+fn mr_witness<R: RngCore + CryptoRng + ?Sized>(
+    n: &BigUint,
+    rounds: usize,
+    rng: &mut R,
+    on_round: &mut dyn FnMut(usize),
+) -> Option<Scratch> {
+    let n_minus_one = Scratch(n - BigUint::one());
+    let (s, d) = factor_out_twos(&n_minus_one.0);
+    let d = Scratch(d);
+
+    for round in 1..=rounds {
+        on_round(round);
+        let a = Scratch(random_witness(&n_minus_one.0, rng));
+        if is_witness(&a.0, n, &n_minus_one.0, s, &d.0) {
+            return Some(a);
+        }
+    }
+
+    None
+}
+
+/// Sample a Miller–Rabin witness for an odd `n > 2` from [2, n - 2], given
+/// n - 1.
+///
+/// n - 1 passes every round trivially, so drawing it would waste the round.
+/// For n = 3 the range is empty and 2 is returned.
+pub(crate) fn random_witness<R: RngCore + CryptoRng + ?Sized>(
+    n_minus_one: &BigUint,
+    rng: &mut R,
+) -> BigUint {
+    let two = BigUint::from(2u32);
+    let high = n_minus_one - BigUint::one();
+    if high < two {
+        return two;
+    }
+    random_range(&two, &high, rng)
+}
+
+/// Solovay–Strassen with `rounds` random bases: each must satisfy
+/// a^((n - 1) / 2) = (a/n) mod n, with the Jacobi symbol (a/n) nonzero.
+pub(crate) fn solovay_strassen<R: RngCore + CryptoRng + ?Sized>(
+    n: &BigUint,
+    rounds: usize,
+    rng: &mut R,
+    on_round: &mut dyn FnMut(usize),
+) -> bool {
+    let two = BigUint::from(2u32);
+    if *n < two {
+        return false;
+    }
+    if *n == two || *n == BigUint::from(3u32) {
+        return true;
+    }
+    if n.is_even() {
+        return false;
+    }
+
+    let n_minus_one = Scratch(n - BigUint::one());
+    let exponent = Scratch(&n_minus_one.0 >> 1usize);
+    let high = Scratch(&n_minus_one.0 - BigUint::one());
+    for round in 1..=rounds {
+        on_round(round);
+        let a = Scratch(random_range(&two, &high.0, rng));
+        let expected = match jacobi(&a.0, n) {
+            0 => return false,
+            1 => BigUint::one(),
+            _ => n_minus_one.0.clone(),
+        };
+        if a.0.modpow(&exponent.0, n) != expected {
+            return false;
+        }
+    }
+    true
+}
+
+/// Below this bound, Miller–Rabin to the 13 prime bases up to 41 is exact
+/// (Sorenson and Webster, 2015).
+const DETERMINISTIC_MR_BOUND: u128 = 3_317_044_064_679_887_385_961_981;
+
+/// The bases [`is_prime_deterministic`] tests: the primes up to 41.
+pub const DETERMINISTIC_MR_BASES: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/// Deterministic Miller–Rabin: `Some(verdict)` for `n` below about
+/// 3.317 * 10^24, where testing the prime bases 2 to 41 proves the result,
+/// and `None` for larger `n`.
+pub fn is_prime_deterministic(n: &BigUint) -> Option<bool> {
+    if *n >= BigUint::from(DETERMINISTIC_MR_BOUND) {
+        return None;
+    }
+    if *n < BigUint::from(2u32) {
+        return Some(false);
+    }
+    if n.is_even() {
+        return Some(*n == BigUint::from(2u32));
+    }
+    for base in DETERMINISTIC_MR_BASES {
+        let base = BigUint::from(base);
+        if *n == base {
+            return Some(true);
+        }
+        if !is_strong_probable_prime(n, &base) {
+            return Some(false);
+        }
+    }
+    Some(true)
+}
+
+/// Fermat test to base 2: whether 2^(n - 1) = 1 mod n, for odd `n > 2`.
+///
+/// Every prime passes and almost every composite that survives trial
+/// division fails, at the cost of a single modexp.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub(crate) fn is_fermat_probable_prime_base2(n: &BigUint) -> bool {
+    let n_minus_one = Scratch(n - BigUint::one());
+    BigUint::from(2u32).modpow(&n_minus_one.0, n).is_one()
+}
+
+/// Miller–Rabin to each of `bases` on an odd `n > 2`, returning the first
+/// base that proves it composite. Bases that are multiples of `n` are skipped.
+pub(crate) fn fixed_base_witness<'a>(
+    n: &BigUint,
+    bases: &'a [BigUint],
+    on_round: &mut dyn FnMut(usize),
+) -> Option<&'a BigUint> {
+    let n_minus_one = Scratch(n - BigUint::one());
+    let (s, d) = factor_out_twos(&n_minus_one.0);
+    let d = Scratch(d);
+    for (round, base) in bases.iter().enumerate() {
+        on_round(round + 1);
+        let a = Scratch(base % n);
+        if !a.0.is_zero() && is_witness(&a.0, n, &n_minus_one.0, s, &d.0) {
+            return Some(base);
+        }
+    }
+    None
+}
+
+/// Strong probable-prime test of an odd `n > 2` to a single `base`.
+pub(crate) fn is_strong_probable_prime(n: &BigUint, base: &BigUint) -> bool {
+    let n_minus_one = Scratch(n - BigUint::one());
+    let (s, d) = factor_out_twos(&n_minus_one.0);
+    let d = Scratch(d);
+    !is_witness(base, n, &n_minus_one.0, s, &d.0)
+}
+
+/// Whether `a` proves `n` composite, where n - 1 = d * 2^s.
+pub(crate) fn is_witness(
+    a: &BigUint,
+    n: &BigUint,
+    n_minus_one: &BigUint,
+    s: u32,
+    d: &BigUint,
+) -> bool {
+    let one = BigUint::one();
+    let two = BigUint::from(2u32);
+    let mut x = Scratch(a.modpow(d, n));
+
+    if x.0 == one || x.0 == *n_minus_one {
+        return false;
+    }
+
+    for _ in 1..s {
+        x = Scratch(x.0.modpow(&two, n));
+        if x.0 == *n_minus_one {
+            return false;
+        }
+        if x.0 == one {
+            return true;
+        }
+    }
+
+    true
+}
+
+/// Jacobi symbol (a/n) for odd `n`, as -1, 0 or 1.
+///
+/// For a prime `n` this is the Legendre symbol: 1 if `a` is a nonzero
+/// square modulo `n`, -1 if it is not, 0 if `n` divides `a`.
+///
+/// # Panics
+///
+/// If `n` is even.
+pub fn jacobi(a: &BigUint, n: &BigUint) -> i8 {
+    assert!(n.is_odd(), "the Jacobi symbol needs an odd modulus");
+    let mut a = a % n;
+    let mut n = n.clone();
+    let mut result = 1;
+    while !a.is_zero() {
+        let twos = a.trailing_zeros().unwrap_or(0);
+        a >>= twos;
+        let n_mod_8 = n.iter_u32_digits().next().unwrap_or(0) & 7;
+        if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            result = -result;
+        }
+        ::core::mem::swap(&mut a, &mut n);
+        if a.bit(1) && n.bit(1) {
+            result = -result;
+        }
+        a %= &n;
+    }
+    if n.is_one() { result } else { 0 }
+}
+
+/// Express n as d * 2^s with d odd, returning (s, d).
+pub fn factor_out_twos(n: &BigUint) -> (u32, BigUint) {
+    let mut s = 0u32;
+    let mut d = n.clone();
+    while d.is_even() {
+        d >>= 1;
+        s += 1;
+    }
+    (s, d)
+}
+
+/// Sample a random value in the inclusive range [low, high].
+pub fn random_range<R: RngCore + CryptoRng + ?Sized>(
+    low: &BigUint,
+    high: &BigUint,
+    rng: &mut R,
+) -> BigUint {
+    if low == high {
+        return low.clone();
+    }
+    let high_exclusive = high + BigUint::one();
+    rng.gen_biguint_range(low, &high_exclusive)
+}
+
+/// Odd primes used for trial division.
+const SMALL_PRIMES: [u32; 16] = [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59];
+
+/// Filter out obvious composites using a small set of primes.
+///
+/// Returns `false` when `n` is definitely composite (or one) and `true`
+/// when it survives trial division and should go on to Miller–Rabin.
+pub fn small_prime_precheck(n: &BigUint) -> bool {
+    if n == &BigUint::one() {
+        return false;
+    }
+
+    for &p in SMALL_PRIMES.iter() {
+        let p_big = BigUint::from(p);
+        if n == &p_big {
+            return true;
+        }
+        if (n % &p_big).is_zero() {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn random_witness_stays_in_two_to_n_minus_two() {
+        let mut rng = StdRng::seed_from_u64(61);
+        for n in [5u32, 7, 9] {
+            let n_minus_one = BigUint::from(n - 1);
+            let mut seen = [false; 10];
+            for _ in 0..1000 {
+                let a = random_witness(&n_minus_one, &mut rng);
+                assert!(
+                    a >= BigUint::from(2u32) && a <= BigUint::from(n - 2),
+                    "{a} for {n}"
+                );
+                assert_ne!(a, n_minus_one);
+                seen[a.to_u32_digits()[0] as usize] = true;
+            }
+            // Every base in the range comes up.
+            assert!((2..=n as usize - 2).all(|a| seen[a]), "{n}");
+        }
+    }
+
+    #[test]
+    fn random_witness_of_three_is_two() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..100 {
+            assert_eq!(
+                random_witness(&BigUint::from(2u32), &mut rng),
+                BigUint::from(2u32)
+            );
+        }
+    }
+}
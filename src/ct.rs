@@ -0,0 +1,104 @@
+//! Miller–Rabin over `crypto-bigint`, enabled with the `crypto-bigint` feature.
+//!
+//! Modular exponentiation and squaring run in constant time with respect to
+//! the witness and the candidate's value (only its bit length is public).
+//! The test still returns early once a round decides, so the number of
+//! rounds executed reveals whether the candidate was composite.
+
+use crypto_bigint::modular::{BoxedMontyForm, BoxedMontyParams};
+use crypto_bigint::subtle::ConstantTimeEq;
+use crypto_bigint::{BoxedUint, Odd};
+use num_bigint::BigUint;
+use num_integer::Integer;
+
+use crate::core::{factor_out_twos, random_witness};
+use crate::{CryptoRngCore, DEFAULT_MR_ROUNDS, PrimalityTest};
+
+/// Miller–Rabin whose inner loop uses constant-time Montgomery arithmetic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstantTimeMillerRabin {
+    rounds: usize,
+}
+
+impl ConstantTimeMillerRabin {
+    /// Create a test running `rounds` random witnesses.
+    pub fn new(rounds: usize) -> Self {
+        Self { rounds }
+    }
+
+    /// Number of witnesses tried per candidate.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+}
+
+impl Default for ConstantTimeMillerRabin {
+    fn default() -> Self {
+        Self::new(DEFAULT_MR_ROUNDS)
+    }
+}
+
+impl PrimalityTest for ConstantTimeMillerRabin {
+    fn is_probable_prime(&self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool {
+        self.is_probable_prime_observed(n, rng, &mut |_| {})
+    }
+
+    fn is_probable_prime_observed(
+        &self,
+        n: &BigUint,
+        rng: &mut dyn CryptoRngCore,
+        on_round: &mut dyn FnMut(usize),
+    ) -> bool {
+        let two = BigUint::from(2u32);
+        if *n < two {
+            return false;
+        }
+        if *n == two {
+            return true;
+        }
+        if n.is_even() {
+            return false;
+        }
+
+        let precision = u32::try_from(n.bits()).expect("candidate too large for crypto-bigint");
+        let to_boxed = |v: &BigUint| {
+            BoxedUint::from_be_slice(&v.to_bytes_be(), precision)
+                .expect("value fits the candidate's precision")
+        };
+
+        let n_minus_one = n - 1u32;
+        let (s, d) = factor_out_twos(&n_minus_one);
+        let d = to_boxed(&d);
+        let modulus = Odd::new(to_boxed(n)).expect("candidate is odd");
+        let params = BoxedMontyParams::new(modulus);
+        let one = BoxedMontyForm::one(params.clone());
+        let minus_one = BoxedMontyForm::new(to_boxed(&n_minus_one), params.clone());
+        let eq = |a: &BoxedMontyForm, b: &BoxedMontyForm| -> bool {
+            a.as_montgomery().ct_eq(b.as_montgomery()).into()
+        };
+
+        'witness: for round in 1..=self.rounds {
+            on_round(round);
+            let a = random_witness(&n_minus_one, rng);
+            let mut x = BoxedMontyForm::new(to_boxed(&a), params.clone()).pow(&d);
+
+            if eq(&x, &one) || eq(&x, &minus_one) {
+                continue 'witness;
+            }
+
+            for _ in 1..s {
+                x = x.square();
+                if eq(&x, &minus_one) {
+                    continue 'witness;
+                }
+                if eq(&x, &one) {
+                    return false;
+                }
+            }
+
+            return false;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,257 @@
+//! DSA domain parameters by FIPS 186-4, enabled with the `provable`
+//! feature.
+//!
+//! p and q come from a `domain_parameter_seed` by appendix A.1.1.2 and g
+//! from the same seed and an index by A.2.3, all with SHA-256, so anyone
+//! holding the seed, the counter and the index can redo the derivation and
+//! see that the parameters were not chosen to hide a weakness. Like
+//! [`crate::core`] this needs only `alloc`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use crate::core::{CryptoRngCore, compositeness_witness_with_rng};
+use crate::provable::{hash, offset};
+
+/// Bits of output of the hash the derivation runs on, SHA-256.
+const OUTLEN: u64 = 256;
+
+/// The (L, N) pairs FIPS 186-4 approves, with the Miller–Rabin rounds
+/// table C.1 asks for on p and on q.
+const SIZES: [(u64, u64, usize, usize); 4] = [
+    (1024, 160, 40, 40),
+    (2048, 224, 56, 64),
+    (2048, 256, 56, 64),
+    (3072, 256, 64, 64),
+];
+
+/// The tag A.2.3 hashes between the seed and the index.
+const GGEN: &[u8] = b"ggen";
+
+/// DSA domain parameters with the values that re-derive them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DomainParameters {
+    /// The L-bit prime modulus.
+    pub p: BigUint,
+    /// The N-bit prime dividing p − 1.
+    pub q: BigUint,
+    /// A generator of the subgroup of order q.
+    pub g: BigUint,
+    /// The seed p and q were derived from.
+    pub domain_parameter_seed: Vec<u8>,
+    /// The iteration at which p turned up, below 4L.
+    pub counter: u64,
+    /// The index g was derived with.
+    pub index: u8,
+}
+
+/// Whether FIPS 186-4 approves an `l`-bit p with an `n`-bit q: (1024, 160),
+/// (2048, 224), (2048, 256) or (3072, 256).
+pub fn is_approved_size(l: u64, n: u64) -> bool {
+    rounds(l, n).is_some()
+}
+
+/// Domain parameters with an `l`-bit p and an `n`-bit q, from fresh
+/// `seedlen`-bit seeds drawn from `rng`, and g derived with `index`.
+///
+/// `None` if (`l`, `n`) is not approved or `seedlen` is below `n` or not a
+/// whole number of bytes.
+pub fn generate_domain_parameters<R: CryptoRngCore + ?Sized>(
+    l: u64,
+    n: u64,
+    seedlen: u64,
+    index: u8,
+    rng: &mut R,
+) -> Option<DomainParameters> {
+    if !is_approved_size(l, n) || seedlen < n || !seedlen.is_multiple_of(8) {
+        return None;
+    }
+    loop {
+        let mut seed = vec![0u8; (seedlen / 8) as usize];
+        rng.fill_bytes(&mut seed);
+        let Some((p, q, counter)) = primes_from_seed(l, n, &seed, rng) else {
+            continue;
+        };
+        let g = canonical_generator(&p, &q, &seed, index)?;
+        return Some(DomainParameters {
+            p,
+            q,
+            g,
+            domain_parameter_seed: seed,
+            counter,
+            index,
+        });
+    }
+}
+
+/// Steps 6 to 11 of A.1.1.2: q and then p from `seed`, with the counter p
+/// was found at. The primality tests draw their bases from `rng`.
+///
+/// `None` if (`l`, `n`) is not approved, if the q of this seed is composite,
+/// or if no p turns up within 4L iterations; the standard then draws a new
+/// seed.
+pub fn primes_from_seed<R: CryptoRngCore + ?Sized>(
+    l: u64,
+    n: u64,
+    seed: &[u8],
+    rng: &mut R,
+) -> Option<(BigUint, BigUint, u64)> {
+    let (p_rounds, q_rounds) = rounds(l, n)?;
+    if seed.is_empty() {
+        return None;
+    }
+    let is_prime = |candidate: &BigUint, rounds, rng: &mut R| {
+        compositeness_witness_with_rng(candidate, rounds, rng).is_none()
+    };
+
+    let u = BigUint::from_bytes_be(&hash(seed)) % (BigUint::one() << (n - 1));
+    let q = (BigUint::one() << (n - 1)) + &u + 1u32 - (&u & BigUint::one());
+    if !is_prime(&q, q_rounds, rng) {
+        return None;
+    }
+
+    let blocks = l.div_ceil(OUTLEN) - 1;
+    let b = l - 1 - blocks * OUTLEN;
+    let top = BigUint::one() << (l - 1);
+    let two_q = &q << 1u32;
+    let mut next = 1;
+    for counter in 0..4 * l {
+        let mut w = BigUint::zero();
+        for j in 0..=blocks {
+            let mut v = BigUint::from_bytes_be(&hash(&offset(seed, next + j)));
+            if j == blocks {
+                v %= BigUint::one() << b;
+            }
+            w += v << (j * OUTLEN);
+        }
+        let x = w + &top;
+        let p = &x + 1u32 - x % &two_q;
+        if p >= top && is_prime(&p, p_rounds, rng) {
+            return Some((p, q, counter));
+        }
+        next += blocks + 1;
+    }
+    None
+}
+
+/// The generator A.2.3 derives from `seed` and `index`: the first
+/// Hash(seed ‖ "ggen" ‖ index ‖ count)^((p − 1)/q) mod p above 1.
+///
+/// `None` if q does not divide p − 1, or if the 16-bit count runs out,
+/// which for genuine parameters never happens.
+pub fn canonical_generator(p: &BigUint, q: &BigUint, seed: &[u8], index: u8) -> Option<BigUint> {
+    if q.is_zero() {
+        return None;
+    }
+    let (e, remainder) = (p - 1u32).div_rem(q);
+    if !remainder.is_zero() {
+        return None;
+    }
+    let two = BigUint::from(2u32);
+    (1..=u16::MAX).find_map(|count| {
+        let mut u = seed.to_vec();
+        u.extend_from_slice(GGEN);
+        u.push(index);
+        u.extend_from_slice(&count.to_be_bytes());
+        let g = BigUint::from_bytes_be(&hash(&u)).modpow(&e, p);
+        (g >= two).then_some(g)
+    })
+}
+
+/// A.1.1.3: whether p and q re-derive from `seed`, p at iteration
+/// `counter`, by A.1.1.2. The primality tests draw their bases from `rng`.
+pub fn validate_primes<R: CryptoRngCore + ?Sized>(
+    p: &BigUint,
+    q: &BigUint,
+    seed: &[u8],
+    counter: u64,
+    rng: &mut R,
+) -> bool {
+    let (l, n) = (p.bits(), q.bits());
+    if !is_approved_size(l, n) || counter >= 4 * l || (seed.len() as u64) * 8 < n {
+        return false;
+    }
+    primes_from_seed(l, n, seed, rng).is_some_and(|(computed_p, computed_q, computed_counter)| {
+        computed_p == *p && computed_q == *q && computed_counter == counter
+    })
+}
+
+/// A.2.2, the partial validation any g allows: 2 ≤ g ≤ p − 1 and
+/// g^q = 1 modulo p, so g generates the subgroup of prime order q.
+pub fn is_subgroup_generator(p: &BigUint, q: &BigUint, g: &BigUint) -> bool {
+    *g >= BigUint::from(2u32) && *g < *p && g.modpow(q, p).is_one()
+}
+
+/// A.2.4: whether `g` passes [`is_subgroup_generator`] and is the one
+/// [`canonical_generator`] derives from `seed` and `index`.
+pub fn validate_canonical_generator(
+    p: &BigUint,
+    q: &BigUint,
+    g: &BigUint,
+    seed: &[u8],
+    index: u8,
+) -> bool {
+    is_subgroup_generator(p, q, g)
+        && canonical_generator(p, q, seed, index).is_some_and(|computed| computed == *g)
+}
+
+/// The Miller–Rabin rounds for p and for q at an approved size.
+fn rounds(l: u64, n: u64) -> Option<(usize, usize)> {
+    SIZES
+        .iter()
+        .find(|&&(size_l, size_n, _, _)| (size_l, size_n) == (l, n))
+        .map(|&(_, _, p_rounds, q_rounds)| (p_rounds, q_rounds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn hex(digits: &str) -> BigUint {
+        BigUint::parse_bytes(digits.as_bytes(), 16).unwrap()
+    }
+
+    /// (2048, 256) parameters checked against an independent implementation
+    /// of A.1.1.2 and A.2.3 from the text of FIPS 186-4.
+    #[test]
+    fn domain_parameters_regression() {
+        let seed =
+            hex("80dea7fbb744acdc5d7b227fb7aa62a3c5e081417874bdb6bfe79e17243aa0b7").to_bytes_be();
+        let p = hex(concat!(
+            "c752622ce5f99b3884d5afd0bcb6711266b68d83681529758c52993dacd35919",
+            "6be9d232ebb195601d9058ae417052d203fe99768a644fdab84ec6152ac95896",
+            "7b803c35663588c0d2f0e9fd74ff33e5558223573b15ccb142b6e71a6e6f8d85",
+            "29c77229d74a59d36a6e5d92aab537ea6f1eb3daa43595e2fc1e6014033a4d50",
+            "f6af13882f0e8a438b9926cf43a0e284d250aea100e3a0758614166b923795cd",
+            "8e84da0ef46de15c798d2c4642c1b42aeb0bb3364fc57c976fbd3f59d77359a0",
+            "24c99c888e4aacdd5699ea0404a0af43eba9a374a3b7dd1ad56b6dec777c0bdc",
+            "5f648d41b5d9dad93d4a292c24eb2332f156434f59a8a01587bc1b78522cd1b3",
+        ));
+        let q = hex("f115f52df276fd21f795f5e45e725a03c1e55a41c2f329724833dcd280b03321");
+        let g = hex(concat!(
+            "aefc45ea6f4cb4eaaa53d406d26ec94e966c3f24804a1aa1ab08418cdc94b204",
+            "8231b72f3048b474a7420ed5d93a50d63e9311dcda83784944855ad013edb8b1",
+            "70ba106d4b0d8dade0e62cd828e2704afbd0566a13d5709900c10bd3f114f396",
+            "8f66b4c1cb8dd30029b884b01fc71688bd264c8c1860ea0e5fdd4bf86e1d0713",
+            "b799dd441f82d8779b15ca453c7db535d627304f140426e3ea79a6bfbf5b1f70",
+            "669a44176f47710073303cb5f2885e1833b7bdef2f2c1c92be9b527ff932d673",
+            "2a32083d0cc5a004ac0b75f1f9bd0a277042cec915088ce213b1dd6ac3ce18d9",
+            "6a6f47f1da0de0cc987e68e44b8c2d6b4bea362f6374829dc81f2c39ef8ebb27",
+        ));
+        let mut rng = StdRng::seed_from_u64(79);
+
+        assert_eq!(
+            primes_from_seed(2048, 256, &seed, &mut rng),
+            Some((p.clone(), q.clone(), 331))
+        );
+        assert!(validate_primes(&p, &q, &seed, 331, &mut rng));
+        assert!(!validate_primes(&p, &q, &seed, 330, &mut rng));
+        assert_eq!(canonical_generator(&p, &q, &seed, 1), Some(g.clone()));
+        assert!(validate_canonical_generator(&p, &q, &g, &seed, 1));
+    }
+}
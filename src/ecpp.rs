@@ -0,0 +1,570 @@
+//! Elliptic-curve primality proving (Atkin–Morain), and the certificates
+//! it produces.
+//!
+//! Each step of a certificate gives a curve E modulo n, an integer m, a
+//! prime q dividing m and a point P with m·P = O but (m/q)·P ≠ O. When
+//! q > (n^(1/4) + 1)², n must be prime, provided q is; q is the n of the
+//! next step, until one is small enough for [`is_prime_deterministic`].
+//! The curves come from complex multiplication, so their orders are known
+//! without counting points. Like [`crate::core`] this needs only `alloc`.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use crate::core::{is_prime_deterministic, jacobi};
+use crate::factor::primes_up_to;
+use crate::hilbert::{hilbert_class_polynomial, is_fundamental, reduced_forms, residue};
+use crate::lucas::baillie_psw;
+
+/// Discriminants tried go down to −this.
+const MAX_DISCRIMINANT: i64 = 4000;
+
+/// Class polynomials of degree above this are not worth their root finding.
+const MAX_CLASS_NUMBER: usize = 16;
+
+/// Curve orders are stripped of prime factors up to this before the
+/// cofactor is tested as the next q.
+const SMOOTH_BOUND: u64 = 1 << 16;
+
+/// Twists and points tried on one discriminant before moving on.
+const MAX_ATTEMPTS: u64 = 256;
+
+/// One step of an [`EcppCertificate`]: n is prime if q is, by the point
+/// (x, y) on y² = x³ + ax + b modulo n.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcppStep {
+    /// The number this step proves prime.
+    pub n: BigUint,
+    /// The curve coefficient a.
+    pub a: BigUint,
+    /// The curve coefficient b.
+    pub b: BigUint,
+    /// The order of the curve, or any multiple of the order of P.
+    pub m: BigUint,
+    /// The prime factor of m above (n^(1/4) + 1)² that the next step proves.
+    pub q: BigUint,
+    /// The x coordinate of P.
+    pub x: BigUint,
+    /// The y coordinate of P.
+    pub y: BigUint,
+}
+
+impl EcppStep {
+    /// Whether the step proves `n` prime, given that `q` is.
+    pub fn verify(&self) -> bool {
+        let n = &self.n;
+        if !n.gcd(&BigUint::from(6u32)).is_one()
+            || *n <= BigUint::one()
+            || self.q.is_zero()
+            || !self.m.is_multiple_of(&self.q)
+            || !exceeds_quartic_bound(&self.q, n)
+        {
+            return false;
+        }
+        let curve = Curve::new(n, &self.a);
+        let discriminant = (&self.a * &self.a * &self.a * 4u32 + &self.b * &self.b * 27u32) % n;
+        if !discriminant.gcd(n).is_one() || !curve.contains(&self.x, &self.y, &self.b) {
+            return false;
+        }
+        let p = Point::Affine(self.x.clone() % n, self.y.clone() % n);
+        match curve.multiply(&p, &(&self.m / &self.q)) {
+            Some(q_point @ Point::Affine(..)) => {
+                curve.multiply(&q_point, &self.q) == Some(Point::Infinity)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// An elliptic-curve primality certificate for `n`: the chain of steps
+/// from `n` down to a prime small enough to test directly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcppCertificate {
+    /// The number proven prime.
+    pub n: BigUint,
+    /// One step per level, starting with `n`; empty when `n` itself is
+    /// small enough for [`is_prime_deterministic`].
+    pub steps: Vec<EcppStep>,
+}
+
+impl EcppCertificate {
+    /// Whether the certificate proves `n` prime: every step holds, each q
+    /// is the n of the next step, and the last q is a small prime.
+    pub fn verify(&self) -> bool {
+        let mut n = &self.n;
+        for step in &self.steps {
+            if step.n != *n || !step.verify() {
+                return false;
+            }
+            n = &step.q;
+        }
+        is_prime_deterministic(n) == Some(true)
+    }
+}
+
+/// An ECPP certificate for `n`, or `None` if `n` is composite or no curve
+/// from the discriminants down to −4000 fits some level.
+///
+/// Each level sheds about 20 bits and takes a fraction of a second at a
+/// thousand bits but several seconds at two thousand, so a 2048-bit proof
+/// takes minutes.
+pub fn ecpp_certificate(n: &BigUint) -> Option<EcppCertificate> {
+    if let Some(prime) = is_prime_deterministic(n) {
+        return prime.then(|| EcppCertificate {
+            n: n.clone(),
+            steps: Vec::new(),
+        });
+    }
+    if !baillie_psw(n) {
+        return None;
+    }
+    let mut discriminants: Vec<(usize, i64)> = (3..=MAX_DISCRIMINANT)
+        .map(|d| -d)
+        .filter(|&d| is_fundamental(d))
+        .map(|d| (reduced_forms(d).len(), d))
+        .filter(|&(h, _)| h <= MAX_CLASS_NUMBER)
+        .collect();
+    discriminants.sort_unstable();
+    let mut prover = Prover {
+        discriminants: discriminants.into_iter().map(|(_, d)| d).collect(),
+        primorial: primes_up_to(SMOOTH_BOUND)
+            .into_iter()
+            .map(BigUint::from)
+            .product(),
+        polynomials: BTreeMap::new(),
+    };
+    let steps = prover.prove(n)?;
+    Some(EcppCertificate {
+        n: n.clone(),
+        steps,
+    })
+}
+
+/// State shared by the levels of one proof.
+struct Prover {
+    /// Fundamental discriminants by increasing class number.
+    discriminants: Vec<i64>,
+    /// The product of the primes up to [`SMOOTH_BOUND`].
+    primorial: BigUint,
+    /// Class polynomials computed so far.
+    polynomials: BTreeMap<i64, Vec<BigInt>>,
+}
+
+impl Prover {
+    /// Steps proving the probable prime `n`, backtracking to the next curve
+    /// order when a level below finds none.
+    fn prove(&mut self, n: &BigUint) -> Option<Vec<EcppStep>> {
+        for index in 0..self.discriminants.len() {
+            let d = self.discriminants[index];
+            for (m, q) in self.orders(n, d) {
+                let Some(step) = self.step(n, d, m, q) else {
+                    continue;
+                };
+                if is_prime_deterministic(&step.q) == Some(true) {
+                    return Some(vec![step]);
+                }
+                if let Some(rest) = self.prove(&step.q) {
+                    return Some([vec![step], rest].concat());
+                }
+            }
+        }
+        None
+    }
+
+    /// Curve orders m for discriminant `d` modulo `n` whose part above
+    /// [`SMOOTH_BOUND`] is a probable prime q large enough to prove `n`.
+    fn orders(&self, n: &BigUint, d: i64) -> Vec<(BigUint, BigUint)> {
+        let Some((u, v)) = cornacchia(n, d) else {
+            return Vec::new();
+        };
+        let (u, v) = (BigInt::from(u), BigInt::from(v));
+        let traces = match d {
+            -3 => vec![u.clone(), (&u + &v * 3) / 2, (&u - &v * 3) / 2],
+            -4 => vec![u.clone(), v * 2],
+            _ => vec![u],
+        };
+        let n_plus_one = BigInt::from(n + 1u32);
+        traces
+            .iter()
+            .flat_map(|t| [&n_plus_one - t, &n_plus_one + t])
+            .filter_map(|m| m.to_biguint())
+            .filter_map(|m| {
+                let q = self.strip_smooth(&m);
+                (q < *n && exceeds_quartic_bound(&q, n) && baillie_psw(&q)).then_some((m, q))
+            })
+            .collect()
+    }
+
+    /// `m` without its prime factors up to [`SMOOTH_BOUND`].
+    fn strip_smooth(&self, m: &BigUint) -> BigUint {
+        let mut rest = m.clone();
+        let mut g = (&self.primorial % m).gcd(m);
+        while !g.is_one() && !rest.is_zero() {
+            rest /= &g;
+            g = rest.gcd(&g);
+        }
+        rest
+    }
+
+    /// A step for `n` on a curve of order `m` with CM by `d`, if one of its
+    /// twists and points turns up.
+    fn step(&mut self, n: &BigUint, d: i64, m: BigUint, q: BigUint) -> Option<EcppStep> {
+        let curves: Vec<(BigUint, BigUint)> = match d {
+            // j = 0 and 1728 have six and four twists, so try coefficients in turn.
+            -3 => (1..MAX_ATTEMPTS)
+                .map(|b| (BigUint::zero(), BigUint::from(b)))
+                .collect(),
+            -4 => (1..MAX_ATTEMPTS)
+                .map(|a| (BigUint::from(a), BigUint::zero()))
+                .collect(),
+            _ => {
+                let j = self.j_invariant(n, d)?;
+                let denominator = (BigUint::from(1728u32) + n - &j) % n;
+                let k = &j * denominator.modinv(n)? % n;
+                let c = (2u32..).map(BigUint::from).find(|c| jacobi(c, n) == -1)?;
+                let (a, b) = (&k * 3u32 % n, &k * 2u32 % n);
+                let c2 = &c * &c % n;
+                vec![(a.clone(), b.clone()), (a * &c2 % n, b * c2 * c % n)]
+            }
+        };
+        let cofactor = &m / &q;
+        for (a, b) in curves {
+            let curve = Curve::new(n, &a);
+            for x in 0..MAX_ATTEMPTS {
+                let x = BigUint::from(x);
+                let rhs = (&x * &x * &x + &a * &x + &b) % n;
+                if jacobi(&rhs, n) != 1 {
+                    continue;
+                }
+                let y = sqrt_mod(&rhs, n)?;
+                let p = Point::Affine(x.clone(), y.clone());
+                if curve.multiply(&p, &m) != Some(Point::Infinity) {
+                    // Not the twist of order m.
+                    break;
+                }
+                if let Some(Point::Affine(..)) = curve.multiply(&p, &cofactor) {
+                    let step = EcppStep {
+                        n: n.clone(),
+                        a,
+                        b,
+                        m,
+                        q,
+                        x,
+                        y,
+                    };
+                    return step.verify().then_some(step);
+                }
+            }
+        }
+        None
+    }
+
+    /// A root of H_`d` modulo the prime `n`, other than 0 and 1728.
+    fn j_invariant(&mut self, n: &BigUint, d: i64) -> Option<BigUint> {
+        let polynomial = self
+            .polynomials
+            .entry(d)
+            .or_insert_with(|| hilbert_class_polynomial(d));
+        let f: Vec<BigUint> = polynomial.iter().map(|c| residue(c, n)).collect();
+        let j = find_root(&f, n)?;
+        (!j.is_zero() && j != BigUint::from(1728u32) % n).then_some(j)
+    }
+}
+
+/// Whether q > (n^(1/4) + 1)², checked as (⌊√q⌋ − 1)^4 > n, which implies it.
+fn exceeds_quartic_bound(q: &BigUint, n: &BigUint) -> bool {
+    let root = q.sqrt();
+    root > BigUint::one() && (root - 1u32).pow(4) > *n
+}
+
+/// A point of an elliptic curve modulo n.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Point {
+    Infinity,
+    Affine(BigUint, BigUint),
+}
+
+/// y² = x³ + ax + b modulo `n`, in affine coordinates. Arithmetic returns
+/// `None` when a denominator shares a factor with `n`: for prime `n` that
+/// never happens, and for composite `n` it keeps every computed point equal
+/// to the true one modulo each prime factor.
+struct Curve<'a> {
+    n: &'a BigUint,
+    a: &'a BigUint,
+}
+
+impl<'a> Curve<'a> {
+    fn new(n: &'a BigUint, a: &'a BigUint) -> Self {
+        Curve { n, a }
+    }
+
+    fn contains(&self, x: &BigUint, y: &BigUint, b: &BigUint) -> bool {
+        let n = self.n;
+        (y * y) % n == (x * x * x + self.a * x + b) % n
+    }
+
+    fn add(&self, p: &Point, q: &Point) -> Option<Point> {
+        let n = self.n;
+        let (Point::Affine(x1, y1), Point::Affine(x2, y2)) = (p, q) else {
+            return Some(if *p == Point::Infinity { q } else { p }.clone());
+        };
+        let lambda = if x1 == x2 {
+            if ((y1 + y2) % n).is_zero() {
+                return Some(Point::Infinity);
+            }
+            if y1 != y2 {
+                return None;
+            }
+            let numerator = (x1 * x1 * 3u32 + self.a) % n;
+            numerator * (y1 * 2u32 % n).modinv(n)? % n
+        } else {
+            let numerator = (y2 + n - y1) % n;
+            numerator * ((x2 + n - x1) % n).modinv(n)? % n
+        };
+        let x3 = (&lambda * &lambda + n * 2u32 - x1 - x2) % n;
+        let y3 = (lambda * ((x1 + n - &x3) % n) + n - y1) % n;
+        Some(Point::Affine(x3, y3))
+    }
+
+    fn multiply(&self, p: &Point, k: &BigUint) -> Option<Point> {
+        let mut result = Point::Infinity;
+        for i in (0..k.bits()).rev() {
+            result = self.add(&result, &result)?;
+            if k.bit(i) {
+                result = self.add(&result, p)?;
+            }
+        }
+        Some(result)
+    }
+}
+
+/// u and v with 4n = u² + |d|v², by Cornacchia's algorithm as modified for
+/// 4n (Cohen, algorithm 1.5.3), for a prime `n` and `d` ≡ 0 or 1 mod 4.
+fn cornacchia(n: &BigUint, d: i64) -> Option<(BigUint, BigUint)> {
+    let abs_d = BigUint::from(d.unsigned_abs());
+    let d_mod_n = residue(&BigInt::from(d), n);
+    if jacobi(&d_mod_n, n) != 1 {
+        return None;
+    }
+    let mut b = sqrt_mod(&d_mod_n, n)?;
+    if b.is_odd() != d.is_odd() {
+        b = n - b;
+    }
+    let mut a = n << 1u32;
+    let limit = (n << 2u32).sqrt();
+    while b > limit {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+    let (four_n, b2) = (n << 2u32, &b * &b);
+    if b2 > four_n {
+        return None;
+    }
+    let rest = four_n - b2;
+    if !rest.is_multiple_of(&abs_d) {
+        return None;
+    }
+    let c = rest / abs_d;
+    let v = c.sqrt();
+    (&v * &v == c).then_some((b, v))
+}
+
+/// A square root of `a` modulo the odd prime `p` by Tonelli–Shanks, or
+/// `None` if `a` is not a square (or `p` turns out not to be prime).
+fn sqrt_mod(a: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let a = a % p;
+    if a.is_zero() {
+        return Some(a);
+    }
+    let p_minus_one = p - 1u32;
+    let s = p_minus_one.trailing_zeros()?;
+    let q = &p_minus_one >> s;
+    let z = (2u32..).map(BigUint::from).find(|z| jacobi(z, p) == -1)?;
+    let mut m = s;
+    let mut c = z.modpow(&q, p);
+    let mut t = a.modpow(&q, p);
+    let mut r = a.modpow(&((&q + 1u32) >> 1u32), p);
+    while !t.is_one() {
+        let mut i = 0;
+        let mut t2 = t.clone();
+        while !t2.is_one() {
+            t2 = &t2 * &t2 % p;
+            i += 1;
+            if i == m {
+                return None;
+            }
+        }
+        let b = c.modpow(&(BigUint::one() << (m - i - 1)), p);
+        m = i;
+        c = &b * &b % p;
+        t = t * &c % p;
+        r = r * b % p;
+    }
+    Some(r)
+}
+
+/// A root modulo the prime `p` of the monic polynomial `f` (coefficients
+/// constant term first), which splits into linear factors, by equal-degree
+/// splitting with (x + δ)^((p − 1)/2) − 1 for δ = 0, 1, 2, …
+fn find_root(f: &[BigUint], p: &BigUint) -> Option<BigUint> {
+    let mut f = f.to_vec();
+    let exponent = (p - 1u32) >> 1u32;
+    let mut delta = 0u64;
+    while f.len() > 2 {
+        if delta >= MAX_ATTEMPTS {
+            return None;
+        }
+        let base = vec![BigUint::from(delta) % p, BigUint::one()];
+        let mut g = poly_pow_mod(&base, &exponent, &f, p);
+        g[0] = (&g[0] + p - 1u32) % p;
+        let factor = poly_gcd(&f, &normalize(g), p);
+        if factor.len() > 1 && factor.len() < f.len() {
+            f = if 2 * factor.len() <= f.len() + 1 {
+                factor
+            } else {
+                poly_div(&f, &factor, p)
+            };
+        }
+        delta += 1;
+    }
+    // f = x + c, monic.
+    let c = f.first()?;
+    Some((p - c) % p)
+}
+
+/// `f` without leading zero coefficients.
+fn normalize(mut f: Vec<BigUint>) -> Vec<BigUint> {
+    while f.last().is_some_and(Zero::is_zero) {
+        f.pop();
+    }
+    f
+}
+
+/// `f` made monic modulo `p`.
+fn monic(f: Vec<BigUint>, p: &BigUint) -> Option<Vec<BigUint>> {
+    let inverse = f.last()?.modinv(p)?;
+    Some(f.into_iter().map(|c| c * &inverse % p).collect())
+}
+
+/// The remainder of `f` divided by the monic `g` modulo `p`.
+fn poly_rem(f: &[BigUint], g: &[BigUint], p: &BigUint) -> Vec<BigUint> {
+    let mut r = f.to_vec();
+    let degree = g.len() - 1;
+    while r.len() > degree {
+        let lead = r.pop().expect("longer than g");
+        let shift = r.len() - degree;
+        for (k, c) in g[..degree].iter().enumerate() {
+            r[shift + k] = (&r[shift + k] + p - (&lead * c) % p) % p;
+        }
+    }
+    normalize(r)
+}
+
+/// The quotient of `f` by the monic `g` modulo `p`, where `g` divides `f`.
+fn poly_div(f: &[BigUint], g: &[BigUint], p: &BigUint) -> Vec<BigUint> {
+    let mut r = f.to_vec();
+    let degree = g.len() - 1;
+    let mut quotient = vec![BigUint::zero(); f.len() - degree];
+    while r.len() > degree {
+        let lead = r.pop().expect("longer than g");
+        let shift = r.len() - degree;
+        for (k, c) in g[..degree].iter().enumerate() {
+            r[shift + k] = (&r[shift + k] + p - (&lead * c) % p) % p;
+        }
+        quotient[shift] = lead;
+    }
+    quotient
+}
+
+/// The monic gcd of `f` and `g` modulo `p`; `[1]` when they are coprime.
+fn poly_gcd(f: &[BigUint], g: &[BigUint], p: &BigUint) -> Vec<BigUint> {
+    let (mut a, mut b) = (f.to_vec(), g.to_vec());
+    while !b.is_empty() {
+        let Some(b_monic) = monic(b, p) else {
+            return vec![BigUint::one()];
+        };
+        let r = poly_rem(&a, &b_monic, p);
+        a = b_monic;
+        b = r;
+    }
+    monic(a, p).unwrap_or_else(|| vec![BigUint::one()])
+}
+
+/// `base`^`exponent` modulo the monic `f` and `p`.
+fn poly_pow_mod(base: &[BigUint], exponent: &BigUint, f: &[BigUint], p: &BigUint) -> Vec<BigUint> {
+    let mut result = vec![BigUint::one()];
+    for i in (0..exponent.bits()).rev() {
+        result = poly_rem(&poly_mul(&result, &result, p), f, p);
+        if exponent.bit(i) {
+            result = poly_rem(&poly_mul(&result, base, p), f, p);
+        }
+    }
+    while result.len() < f.len() - 1 {
+        result.push(BigUint::zero());
+    }
+    result
+}
+
+fn poly_mul(a: &[BigUint], b: &[BigUint], p: &BigUint) -> Vec<BigUint> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![BigUint::zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += x * y;
+        }
+    }
+    product.into_iter().map(|c| c % p).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mersenne(p: u32) -> BigUint {
+        (BigUint::one() << p) - 1u32
+    }
+
+    #[test]
+    fn certificates_verify() {
+        for n in [mersenne(89), mersenne(127)] {
+            let certificate = ecpp_certificate(&n).expect("a prime gets a certificate");
+            assert!(!certificate.steps.is_empty());
+            assert!(certificate.verify(), "{n}");
+        }
+        let small = ecpp_certificate(&BigUint::from(1_000_003u32)).unwrap();
+        assert!(small.steps.is_empty() && small.verify());
+        assert_eq!(ecpp_certificate(&(mersenne(89) * mersenne(61))), None);
+    }
+
+    #[test]
+    fn tampered_certificates_fail() {
+        let certificate = ecpp_certificate(&mersenne(127)).unwrap();
+        let tamper = |change: &dyn Fn(&mut EcppStep)| {
+            (0..certificate.steps.len()).all(|index| {
+                let mut tampered = certificate.clone();
+                change(&mut tampered.steps[index]);
+                !tampered.verify()
+            })
+        };
+        assert!(tamper(&|step| step.q += 2u32));
+        assert!(tamper(&|step| step.m += 1u32));
+        assert!(tamper(&|step| step.x += 1u32));
+        assert!(tamper(&|step| step.y += 1u32));
+        assert!(tamper(&|step| step.a += 1u32));
+        assert!(tamper(&|step| step.b += 1u32));
+
+        let mut other = certificate.clone();
+        other.n += 2u32;
+        assert!(!other.verify());
+        let mut short = certificate;
+        short.steps.pop();
+        assert!(!short.verify());
+    }
+}
@@ -0,0 +1,55 @@
+//! Error type returned by the generation routines.
+
+use thiserror::Error;
+
+use crate::SearchStats;
+
+/// Errors produced while configuring or running a prime search.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PrimeGenError {
+    /// The requested bit length is below what the operation supports.
+    #[error("bit size {bits} is too small; at least {min} bits are required")]
+    BitsTooSmall { bits: usize, min: usize },
+
+    /// The requested bit length does not fit the internal representation.
+    #[error("bit size {0} is too large")]
+    BitsTooLarge(usize),
+
+    /// No candidate of the requested size can have the requested
+    /// [Hamming weight](crate::PrimeGeneratorBuilder::hamming_weight).
+    #[error("no {bits}-bit candidate has {weight} set bits; {min} to {max} are possible")]
+    WeightOutOfRange {
+        bits: usize,
+        weight: usize,
+        min: usize,
+        max: usize,
+    },
+
+    /// The search was stopped through a [`CancellationToken`](crate::CancellationToken).
+    #[error("search cancelled after {} candidates", .0.candidates)]
+    Cancelled(SearchStats),
+
+    /// The search ran past its [timeout](crate::PrimeGeneratorBuilder::timeout).
+    #[error("no prime found within the timeout ({} candidates)", .0.candidates)]
+    TimedOut(SearchStats),
+
+    /// The search drew its [maximum number of candidates](crate::PrimeGeneratorBuilder::max_attempts).
+    #[error("no prime found within {} candidates", .0.candidates)]
+    AttemptsExhausted(SearchStats),
+}
+
+impl PrimeGenError {
+    /// Replace the statistics carried by a search-stopped error.
+    #[cfg_attr(not(feature = "os-rng"), allow(dead_code))]
+    pub(crate) fn with_stats(self, stats: SearchStats) -> Self {
+        match self {
+            PrimeGenError::Cancelled(_) => PrimeGenError::Cancelled(stats),
+            PrimeGenError::TimedOut(_) => PrimeGenError::TimedOut(stats),
+            PrimeGenError::AttemptsExhausted(_) => PrimeGenError::AttemptsExhausted(stats),
+            other => other,
+        }
+    }
+}
+
+/// Convenience alias for results carrying a [`PrimeGenError`].
+pub type Result<T> = std::result::Result<T, PrimeGenError>;
@@ -0,0 +1,469 @@
+//! Factoring composites, to show why they failed a primality test.
+//!
+//! Like [`crate::core`] this needs only `alloc`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use crate::lucas::baillie_psw;
+
+/// Gaps between consecutive numbers coprime to 30, starting from 7.
+const WHEEL: [u64; 8] = [4, 2, 4, 2, 4, 6, 2, 6];
+
+/// [`factorize`] trial-divides by everything below this before Pollard's rho.
+const TRIAL_BOUND: u64 = 1 << 16;
+
+/// Pollard's rho multiplies this many differences together between gcds.
+const RHO_BATCH: u64 = 128;
+
+/// Pollard's p − 1 raises to this many prime powers between gcds.
+const P1_BATCH: usize = 64;
+
+/// Numbers [`primes`] sieves at a time.
+const SEGMENT: u64 = 1 << 16;
+
+/// 2, 3, 5 and then every number coprime to 30: all primes, and few
+/// composites, in increasing order.
+fn wheel() -> impl Iterator<Item = u64> {
+    let mut p = 7u64;
+    let spokes = WHEEL.iter().cycle().map(move |gap| {
+        let current = p;
+        p += gap;
+        current
+    });
+    [2, 3, 5].into_iter().chain(spokes)
+}
+
+/// Prime factorization of `n` by trial division with a mod-30 wheel, as
+/// (prime, exponent) pairs in increasing order of prime.
+///
+/// Empty for 0 and 1. Takes up to a few seconds when `n` is near 2^64 and
+/// has two large factors.
+pub fn trial_factor(mut n: u64) -> Vec<(u64, u32)> {
+    let mut factors = Vec::new();
+    if n == 0 {
+        return factors;
+    }
+    for p in wheel() {
+        if p > n / p {
+            break;
+        }
+        let mut exponent = 0;
+        while n.is_multiple_of(p) {
+            n /= p;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((p, exponent));
+        }
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+/// The prime factors [`factorize`] found, and what it could not split.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Factorization {
+    /// Prime factors with their exponents, in increasing order of prime.
+    ///
+    /// Factors above 2^64 are Baillie–PSW probable primes rather than proven.
+    pub primes: Vec<(BigUint, u32)>,
+    /// The product of the composite parts the splitting method gave up on, or
+    /// `None` if `n` was factored completely.
+    pub cofactor: Option<BigUint>,
+}
+
+/// Factor `n` by trial division below 2^16 and then Pollard's rho, giving
+/// up on a composite part after `max_iterations` steps of the sequence.
+///
+/// Rho needs about the square root of a factor in steps, so a budget of
+/// 2^30 finds factors of up to about 60 bits. Empty for 0 and 1.
+pub fn factorize(n: &BigUint, max_iterations: u64) -> Factorization {
+    factorize_with(n, |part| pollard_rho(part, max_iterations))
+}
+
+/// Factor `n` by trial division below 2^16 and then `split`, which is given
+/// each composite part in turn and returns a nontrivial divisor or `None`
+/// to give up on it. Empty for 0 and 1.
+pub fn factorize_with<F>(n: &BigUint, mut split: F) -> Factorization
+where
+    F: FnMut(&BigUint) -> Option<BigUint>,
+{
+    let mut factorization = Factorization::default();
+    if *n <= BigUint::one() {
+        return factorization;
+    }
+
+    let mut remaining = n.clone();
+    let mut primes = Vec::new();
+    for p in wheel().take_while(|&p| p < TRIAL_BOUND) {
+        let p = BigUint::from(p);
+        if &p * &p > remaining {
+            break;
+        }
+        while (&remaining % &p).is_zero() {
+            remaining /= &p;
+            primes.push(p.clone());
+        }
+    }
+
+    let mut cofactor = BigUint::one();
+    let mut parts = vec![remaining];
+    while let Some(part) = parts.pop() {
+        if part.is_one() {
+            continue;
+        }
+        if baillie_psw(&part) {
+            primes.push(part);
+            continue;
+        }
+        match split(&part) {
+            Some(divisor) => {
+                parts.push(&part / &divisor);
+                parts.push(divisor);
+            }
+            None => cofactor *= part,
+        }
+    }
+
+    primes.sort();
+    for p in primes {
+        match factorization.primes.last_mut() {
+            Some((last, exponent)) if *last == p => *exponent += 1,
+            _ => factorization.primes.push((p, 1)),
+        }
+    }
+    factorization.cofactor = (!cofactor.is_one()).then_some(cofactor);
+    factorization
+}
+
+/// A nontrivial divisor of the composite `n` by Brent's variant of
+/// Pollard's rho, or `None` if none turns up within `max_iterations` steps.
+///
+/// The sequences x -> x^2 + c are tried for c = 1, 2, ... in turn, so the
+/// result is deterministic.
+pub fn pollard_rho(n: &BigUint, max_iterations: u64) -> Option<BigUint> {
+    if n.is_even() {
+        return (*n > BigUint::from(2u32)).then(|| BigUint::from(2u32));
+    }
+    let mut budget = max_iterations;
+    let mut c = BigUint::one();
+    while budget > 0 {
+        let step = |x: &BigUint| (x * x + &c) % n;
+        let mut y = BigUint::from(2u32);
+        let mut x = y.clone();
+        let mut saved = y.clone();
+        let mut product = BigUint::one();
+        let mut divisor = BigUint::one();
+        let mut length = 1u64;
+        while divisor.is_one() && budget > 0 {
+            x = y.clone();
+            for _ in 0..length {
+                y = step(&y);
+            }
+            let mut done = 0;
+            while done < length && divisor.is_one() && budget > 0 {
+                saved = y.clone();
+                let batch = RHO_BATCH.min(length - done).min(budget);
+                for _ in 0..batch {
+                    y = step(&y);
+                    product = product * distance(&x, &y) % n;
+                }
+                budget -= batch;
+                done += batch;
+                divisor = product.gcd(n);
+            }
+            length *= 2;
+        }
+        if divisor == *n {
+            // The batch overshot: redo it one step at a time.
+            loop {
+                saved = step(&saved);
+                divisor = distance(&x, &saved).gcd(n);
+                if !divisor.is_one() {
+                    break;
+                }
+            }
+        }
+        if !divisor.is_one() && divisor != *n {
+            return Some(divisor);
+        }
+        c += 1u32;
+    }
+    None
+}
+
+/// A nontrivial divisor of `n` by stage 1 of Pollard's p − 1 method, or
+/// `None` if there is none to find with smoothness bound `bound`.
+///
+/// Finds a prime factor p exactly when every prime power dividing p − 1 is
+/// at most `bound`, however large p itself is.
+pub fn pollard_p_minus_1(n: &BigUint, bound: u64) -> Option<BigUint> {
+    if n.is_even() {
+        return (*n > BigUint::from(2u32)).then(|| BigUint::from(2u32));
+    }
+    let mut primes = primes(bound);
+    // Not 2, whose order modulo a Mersenne factor is tiny.
+    let mut a = BigUint::from(3u32);
+    loop {
+        let batch: Vec<u64> = primes.by_ref().take(P1_BATCH).collect();
+        if batch.is_empty() {
+            return None;
+        }
+        let saved = a.clone();
+        for &q in &batch {
+            a = a.modpow(&BigUint::from(prime_power(q, bound)), n);
+        }
+        let divisor = (&a - 1u32).gcd(n);
+        if divisor.is_one() {
+            continue;
+        }
+        if divisor != *n {
+            return Some(divisor);
+        }
+        // Every factor of n turned up in the same batch: redo it one prime at
+        // a time, in the hope that they appear at different primes.
+        a = saved;
+        for &q in &batch {
+            let mut power = q;
+            loop {
+                a = a.modpow(&BigUint::from(q), n);
+                let divisor = (&a - 1u32).gcd(n);
+                if divisor == *n {
+                    return None;
+                }
+                if !divisor.is_one() {
+                    return Some(divisor);
+                }
+                match power.checked_mul(q) {
+                    Some(next) if next <= bound => power = next,
+                    _ => break,
+                }
+            }
+        }
+        return None;
+    }
+}
+
+/// A nontrivial divisor of `n` by stage 1 of Lenstra's elliptic-curve
+/// method, trying up to `curves` curves with smoothness bound `bound`, or
+/// `None` if none of them finds one.
+///
+/// Each curve is a fresh chance to find a prime factor p: it succeeds when
+/// the curve's group order modulo p, a number near p rather than p − 1, is
+/// `bound`-smooth. Bounds of 11 000, 50 000 and 250 000 suit factors of
+/// about 20, 25 and 30 digits, with a few hundred to a few thousand curves.
+///
+/// The curves are Montgomery curves in Suyama's parametrization with
+/// σ = 6, 7, 8, ..., so the result is deterministic.
+pub fn ecm(n: &BigUint, bound: u64, curves: u64) -> Option<BigUint> {
+    if n.is_even() {
+        return (*n > BigUint::from(2u32)).then(|| BigUint::from(2u32));
+    }
+    for sigma in (6u64..).take(usize::try_from(curves).unwrap_or(usize::MAX)) {
+        let curve = Curve::suyama(n, sigma);
+        let mut point = curve.start.clone();
+        for q in primes(bound) {
+            point = curve.multiply(&point, prime_power(q, bound));
+        }
+        let divisor = point.1.gcd(n);
+        if !divisor.is_one() && divisor != *n {
+            return Some(divisor);
+        }
+    }
+    None
+}
+
+/// A point in Montgomery's projective (X : Z) coordinates.
+type XzPoint = (BigUint, BigUint);
+
+/// A Montgomery curve By^2 = x^3 + Ax^2 + x modulo `n`, with a point on it.
+struct Curve<'a> {
+    n: &'a BigUint,
+    /// (A + 2) / 4 as the fraction `a24.0 / a24.1`, which spares an inversion.
+    a24: (BigUint, BigUint),
+    start: XzPoint,
+}
+
+impl<'a> Curve<'a> {
+    /// The curve and point Suyama's parametrization gives for `sigma`; its
+    /// group order is always a multiple of 12.
+    fn suyama(n: &'a BigUint, sigma: u64) -> Self {
+        let sigma = BigUint::from(sigma) % n;
+        let u = sub_mod(&(&sigma * &sigma % n), &BigUint::from(5u32), n);
+        let v = (&sigma << 2u32) % n;
+        let u3 = u.modpow(&BigUint::from(3u32), n);
+        let v_minus_u = sub_mod(&v, &u, n);
+        let numerator = v_minus_u.modpow(&BigUint::from(3u32), n) * (&u * 3u32 + &v) % n;
+        let denominator = ((&u3 * &v) << 4u32) % n;
+        Curve {
+            n,
+            a24: (numerator, denominator),
+            start: (u3, v.modpow(&BigUint::from(3u32), n)),
+        }
+    }
+
+    /// 2P.
+    fn double(&self, (x, z): &XzPoint) -> XzPoint {
+        let n = self.n;
+        let sum = (x + z) % n;
+        let sum2 = &sum * &sum % n;
+        let diff = sub_mod(x, z, n);
+        let diff2 = &diff * &diff % n;
+        let t = sub_mod(&sum2, &diff2, n);
+        let (a, d) = &self.a24;
+        (
+            d * &sum2 % n * &diff2 % n,
+            (d * &diff2 + a * &t) % n * &t % n,
+        )
+    }
+
+    /// P + Q, given P − Q.
+    fn add(&self, (xp, zp): &XzPoint, (xq, zq): &XzPoint, (xd, zd): &XzPoint) -> XzPoint {
+        let n = self.n;
+        let u = sub_mod(xp, zp, n) * ((xq + zq) % n) % n;
+        let v = (xp + zp) % n * sub_mod(xq, zq, n) % n;
+        let sum = (&u + &v) % n;
+        let diff = sub_mod(&u, &v, n);
+        (zd * (&sum * &sum % n) % n, xd * (&diff * &diff % n) % n)
+    }
+
+    /// kP by the Montgomery ladder, for k ≥ 1.
+    fn multiply(&self, point: &XzPoint, k: u64) -> XzPoint {
+        let mut low = point.clone();
+        let mut high = self.double(point);
+        for bit in (0..k.ilog2()).rev() {
+            if k >> bit & 1 == 1 {
+                low = self.add(&high, &low, point);
+                high = self.double(&high);
+            } else {
+                high = self.add(&high, &low, point);
+                low = self.double(&low);
+            }
+        }
+        low
+    }
+}
+
+/// a − b modulo `n`, for a and b already reduced.
+fn sub_mod(a: &BigUint, b: &BigUint, n: &BigUint) -> BigUint {
+    if a >= b { a - b } else { n - b + a }
+}
+
+/// The largest power of the prime `q` that is at most `bound`.
+fn prime_power(q: u64, bound: u64) -> u64 {
+    let mut power = q;
+    while let Some(next) = power.checked_mul(q).filter(|&next| next <= bound) {
+        power = next;
+    }
+    power
+}
+
+/// The primes up to `bound`, by the sieve of Eratosthenes.
+pub(crate) fn primes_up_to(bound: u64) -> Vec<u64> {
+    primes(bound).collect()
+}
+
+/// The primes up to `bound` in increasing order, sieved [`SEGMENT`]
+/// numbers at a time.
+///
+/// Only the primes up to the square root of how far it has got are kept,
+/// so a bound far beyond what fits in memory costs time, not space.
+pub(crate) fn primes(bound: u64) -> impl Iterator<Item = u64> {
+    // Each sieving prime with its next multiple yet to be struck.
+    let mut sieving: Vec<(u64, u64)> = Vec::new();
+    let mut low = Some(2u64);
+    let mut segment = Vec::new().into_iter();
+    core::iter::from_fn(move || {
+        loop {
+            if let Some(p) = segment.next() {
+                return Some(p);
+            }
+            let start = low.filter(|&start| start <= bound)?;
+            let high = start.saturating_add(SEGMENT - 1).min(bound);
+            let mut composite = vec![false; (high - start + 1) as usize];
+            sieving.retain_mut(
+                |(p, next)| match strike(&mut composite, start, high, *p, *next) {
+                    Some(after) => {
+                        *next = after;
+                        true
+                    }
+                    None => false,
+                },
+            );
+            let mut found = Vec::new();
+            for i in 0..composite.len() {
+                if composite[i] {
+                    continue;
+                }
+                let p = start + i as u64;
+                found.push(p);
+                if let Some(square) = p.checked_mul(p).filter(|&square| square <= bound)
+                    && let Some(next) = strike(&mut composite, start, high, p, square)
+                {
+                    sieving.push((p, next));
+                }
+            }
+            segment = found.into_iter();
+            low = high.checked_add(1);
+        }
+    })
+}
+
+/// Mark the multiples of `p` from `next` through `high` in the segment of
+/// `composite` that starts at `low`; the first one past `high`, or `None`
+/// if that overflows.
+fn strike(composite: &mut [bool], low: u64, high: u64, p: u64, mut next: u64) -> Option<u64> {
+    while next <= high {
+        composite[(next - low) as usize] = true;
+        next = next.checked_add(p)?;
+    }
+    Some(next)
+}
+
+/// |a - b|.
+fn distance(a: &BigUint, b: &BigUint) -> BigUint {
+    if a > b { a - b } else { b - a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_join_up() {
+        let sieved: Vec<u64> = primes(3 * SEGMENT + 7).collect();
+        let expected: Vec<u64> = (2..=3 * SEGMENT + 7)
+            .filter(|&n| {
+                (2..)
+                    .take_while(|d| d * d <= n)
+                    .all(|d| !n.is_multiple_of(d))
+            })
+            .collect();
+        assert_eq!(sieved, expected);
+        assert_eq!(primes(1).count(), 0);
+        assert_eq!(primes(2).collect::<Vec<_>>(), [2]);
+    }
+
+    #[test]
+    fn p_minus_1_takes_bounds_past_memory() {
+        // 6810804001 − 1 = 2^5·3^5·5^3·7^2·11·13, while the other factor's
+        // p − 1 has a 15-digit prime in it.
+        let p = BigUint::from(6810804001u64);
+        let n = &p * BigUint::from(100000000000000000039u128);
+        assert_eq!(pollard_p_minus_1(&n, 1 << 60), Some(p));
+    }
+
+    #[test]
+    fn ecm_finds_a_twenty_digit_factor() {
+        // Primes after random 20- and 30-digit numbers, from a fixed seed.
+        let p: BigUint = "60678864083171197597".parse().unwrap();
+        let q: BigUint = "598020845102392624347073452367".parse().unwrap();
+        assert_eq!(ecm(&(&p * &q), 11_000, 20), Some(p));
+    }
+}
@@ -0,0 +1,173 @@
+//! C ABI, enabled with the `ffi` feature.
+//!
+//! Build the shared library with
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib`.
+//!
+//! Integers cross the boundary as big-endian byte arrays. Every function
+//! returns an [`FbpStatus`]; the matching C declarations live in
+//! `include/find_big_prime.h`.
+
+use num_bigint::BigUint;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+use crate::{PrimeGenError, generate_probable_prime, generate_safe_prime, is_probable_prime};
+
+/// Status codes returned by every `fbp_*` function.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FbpStatus {
+    Ok = 0,
+    NullPointer = 1,
+    BitsTooSmall = 2,
+    BitsTooLarge = 3,
+    BufferTooSmall = 4,
+    Cancelled = 5,
+    LimitReached = 6,
+    WeightOutOfRange = 7,
+    Panic = 255,
+}
+
+impl From<&PrimeGenError> for FbpStatus {
+    fn from(err: &PrimeGenError) -> Self {
+        match err {
+            PrimeGenError::BitsTooSmall { .. } => FbpStatus::BitsTooSmall,
+            PrimeGenError::BitsTooLarge(_) => FbpStatus::BitsTooLarge,
+            PrimeGenError::WeightOutOfRange { .. } => FbpStatus::WeightOutOfRange,
+            PrimeGenError::Cancelled(_) => FbpStatus::Cancelled,
+            PrimeGenError::TimedOut(_) | PrimeGenError::AttemptsExhausted(_) => {
+                FbpStatus::LimitReached
+            }
+        }
+    }
+}
+
+/// Generate a probable prime of `bits` bits into `out`.
+///
+/// `out` must hold at least `(bits + 7) / 8` bytes, which is checked before
+/// any work is done; the number of bytes written is stored in `*written`.
+///
+/// # Safety
+///
+/// `out` must be valid for writes of `out_len` bytes and `written` must be
+/// a valid pointer to a `size_t`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fbp_generate_prime(
+    bits: usize,
+    rounds: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> FbpStatus {
+    // SAFETY: forwarded from the caller's contract.
+    unsafe {
+        generate_into(bits, out, out_len, written, || {
+            generate_probable_prime(bits, rounds)
+        })
+    }
+}
+
+/// Generate a safe prime of `bits` bits into `out`.
+///
+/// Buffer handling is the same as [`fbp_generate_prime`].
+///
+/// # Safety
+///
+/// Same requirements as [`fbp_generate_prime`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fbp_generate_safe_prime(
+    bits: usize,
+    rounds: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> FbpStatus {
+    // SAFETY: forwarded from the caller's contract.
+    unsafe {
+        generate_into(bits, out, out_len, written, || {
+            generate_safe_prime(bits, rounds)
+        })
+    }
+}
+
+/// Run Miller–Rabin on the big-endian integer in `n[0..n_len]`.
+///
+/// Stores 1 in `*is_prime` for a probable prime, 0 otherwise.
+///
+/// # Safety
+///
+/// `n` must be valid for reads of `n_len` bytes and `is_prime` must be a
+/// valid pointer to an `int`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn fbp_is_probable_prime(
+    n: *const u8,
+    n_len: usize,
+    rounds: usize,
+    is_prime: *mut i32,
+) -> FbpStatus {
+    if n.is_null() || is_prime.is_null() {
+        return FbpStatus::NullPointer;
+    }
+    // SAFETY: `n` is non-null and the caller guarantees `n_len` readable bytes.
+    let bytes = unsafe { slice::from_raw_parts(n, n_len) };
+    let value = BigUint::from_bytes_be(bytes);
+    match panic::catch_unwind(|| is_probable_prime(&value, rounds)) {
+        Ok(prime) => {
+            // SAFETY: `is_prime` is non-null and points to an `int`.
+            unsafe { *is_prime = i32::from(prime) };
+            FbpStatus::Ok
+        }
+        Err(_) => FbpStatus::Panic,
+    }
+}
+
+/// Run `f`, turning panics into [`FbpStatus::Panic`] so they never unwind into C.
+fn guard<F>(f: F) -> Result<BigUint, FbpStatus>
+where
+    F: FnOnce() -> crate::Result<BigUint>,
+{
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(n)) => Ok(n),
+        Ok(Err(err)) => Err(FbpStatus::from(&err)),
+        Err(_) => Err(FbpStatus::Panic),
+    }
+}
+
+/// Check the caller's buffer can take a `bits`-bit prime, then generate
+/// one with `f` and copy it in.
+///
+/// # Safety
+///
+/// See [`fbp_generate_prime`].
+unsafe fn generate_into<F>(
+    bits: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+    f: F,
+) -> FbpStatus
+where
+    F: FnOnce() -> crate::Result<BigUint>,
+{
+    if out.is_null() || written.is_null() {
+        return FbpStatus::NullPointer;
+    }
+    if out_len < bits.div_ceil(8) {
+        return FbpStatus::BufferTooSmall;
+    }
+    let n = match guard(f) {
+        Ok(n) => n,
+        Err(status) => return status,
+    };
+    let bytes = n.to_bytes_be();
+    if bytes.len() > out_len {
+        return FbpStatus::BufferTooSmall;
+    }
+    // SAFETY: both pointers are non-null, `out` holds `out_len >= bytes.len()`
+    // bytes and cannot overlap a freshly allocated `Vec`.
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len());
+        *written = bytes.len();
+    }
+    FbpStatus::Ok
+}
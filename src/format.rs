@@ -0,0 +1,131 @@
+//! Textual encodings for big integers, with input autodetection.
+
+use ::base64::alphabet;
+use ::base64::engine::{DecodePaddingMode, Engine, GeneralPurpose, GeneralPurposeConfig};
+use num_bigint::BigUint;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Standard-alphabet base64 that emits padding and accepts it optionally.
+pub(crate) const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// A textual representation of an integer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// Base 10.
+    #[default]
+    Decimal,
+    /// Lowercase base 16; rendered with a `0x` prefix under `{:#}`.
+    Hex,
+    /// Standard base64 of the big-endian bytes.
+    Base64,
+}
+
+impl Format {
+    /// Guess the format of `s`: `0x` prefix is hex, all digits is decimal,
+    /// anything else is treated as base64.
+    pub fn detect(s: &str) -> Format {
+        let s = s.trim();
+        if s.starts_with("0x") || s.starts_with("0X") {
+            Format::Hex
+        } else if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+            Format::Decimal
+        } else {
+            Format::Base64
+        }
+    }
+
+    /// Parse `s` in this format, ignoring surrounding whitespace.
+    pub fn parse(self, s: &str) -> Result<BigUint, ParseNumberError> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseNumberError::Empty);
+        }
+        let parsed = match self {
+            Format::Decimal => BigUint::parse_bytes(s.as_bytes(), 10),
+            Format::Hex => {
+                let digits = s
+                    .strip_prefix("0x")
+                    .or_else(|| s.strip_prefix("0X"))
+                    .unwrap_or(s);
+                BigUint::parse_bytes(digits.as_bytes(), 16)
+            }
+            Format::Base64 => BASE64
+                .decode(s)
+                .ok()
+                .filter(|bytes| !bytes.is_empty())
+                .map(|bytes| BigUint::from_bytes_be(&bytes)),
+        };
+        parsed.ok_or_else(|| ParseNumberError::Invalid {
+            format: self,
+            input: s.to_owned(),
+        })
+    }
+
+    /// Render `n` in this format.
+    pub fn display(self, n: &BigUint) -> Formatted<'_> {
+        Formatted { n, format: self }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::Decimal => "decimal",
+            Format::Hex => "hex",
+            Format::Base64 => "base64",
+        })
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "decimal" | "dec" => Ok(Format::Decimal),
+            "hex" => Ok(Format::Hex),
+            "base64" | "b64" => Ok(Format::Base64),
+            other => Err(format!("unknown format `{other}`")),
+        }
+    }
+}
+
+/// Parse an integer, autodetecting its format with [`Format::detect`].
+pub fn parse_number(s: &str) -> Result<BigUint, ParseNumberError> {
+    Format::detect(s).parse(s)
+}
+
+/// Errors from [`parse_number`] and [`Format::parse`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseNumberError {
+    /// The input was empty or only whitespace.
+    #[error("empty input")]
+    Empty,
+
+    /// The input is not valid in the detected or requested format.
+    #[error("`{input}` is not a valid {format} number")]
+    Invalid { format: Format, input: String },
+}
+
+/// Display adapter returned by [`Format::display`].
+#[derive(Clone, Copy, Debug)]
+pub struct Formatted<'a> {
+    n: &'a BigUint,
+    format: Format,
+}
+
+impl fmt::Display for Formatted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.format {
+            Format::Decimal => write!(f, "{}", self.n),
+            Format::Hex if f.alternate() => write!(f, "{:#x}", self.n),
+            Format::Hex => write!(f, "{:x}", self.n),
+            Format::Base64 => f.write_str(&BASE64.encode(self.n.to_bytes_be())),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Deterministic tests for numbers of special form.
+//!
+//! These settle primality exactly, and far faster than Miller–Rabin, but
+//! only for inputs with the right shape. Like [`crate::core`] they need
+//! only `alloc`.
+
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::core::jacobi;
+
+/// The exponent p if `n` is a Mersenne number 2^p - 1 with p >= 2.
+pub fn mersenne_exponent(n: &BigUint) -> Option<u64> {
+    let next = n + BigUint::one();
+    let p = next.bits() - 1;
+    (p >= 2 && next.trailing_zeros() == Some(p)).then_some(p)
+}
+
+/// Lucas–Lehmer test: whether the Mersenne number 2^p - 1 is prime.
+///
+/// With s_0 = 4 and s_(i+1) = s_i^2 - 2, M_p is prime exactly when
+/// s_(p-2) = 0 mod M_p. Composite exponents give composite M_p and are
+/// rejected without running the sequence.
+pub fn lucas_lehmer(p: u64) -> bool {
+    if p == 2 {
+        return true;
+    }
+    if !is_small_prime(p) {
+        return false;
+    }
+    let m = (BigUint::one() << p) - BigUint::one();
+    let two = BigUint::from(2u32);
+    let mut s = BigUint::from(4u32);
+    for _ in 0..p - 2 {
+        s = reduce_mersenne(&s * &s + &m - &two, p, &m);
+    }
+    s.is_zero()
+}
+
+/// The index m if `n` is a Fermat number 2^(2^m) + 1.
+pub fn fermat_index(n: &BigUint) -> Option<u32> {
+    if *n < BigUint::from(3u32) {
+        return None;
+    }
+    let below = n - BigUint::one();
+    let k = below.bits() - 1;
+    (below.trailing_zeros() == Some(k) && k.is_power_of_two()).then(|| k.trailing_zeros())
+}
+
+/// Pépin's test: whether the Fermat number F_m = 2^(2^m) + 1 is prime.
+///
+/// For m >= 1, F_m is prime exactly when 3^((F_m - 1) / 2) = -1 mod F_m;
+/// the exponent is a power of two, so this is 2^m - 1 squarings.
+pub fn pepin(m: u32) -> bool {
+    if m == 0 {
+        return true;
+    }
+    let f = (BigUint::one() << (1u64 << m)) + BigUint::one();
+    let mut x = BigUint::from(3u32);
+    for _ in 0..(1u64 << m) - 1 {
+        x = &x * &x % &f;
+    }
+    x + BigUint::one() == f
+}
+
+/// `(k, e)` if `n` is a Riesel number k * 2^e - 1 with k odd, k < 2^e and
+/// e >= 2.
+pub fn riesel_form(n: &BigUint) -> Option<(BigUint, u64)> {
+    let above = n + BigUint::one();
+    let e = above.trailing_zeros()?;
+    let k = above >> e;
+    (e >= 2 && k.bits() <= e).then_some((k, e))
+}
+
+/// Lucas–Lehmer–Riesel test: whether N = k * 2^e - 1 is prime, for odd
+/// k < 2^e and e >= 2.
+///
+/// Rödseth's choice of P (the first with Jacobi symbols ((P - 2)/N) = 1 and
+/// ((P + 2)/N) = -1) gives the seed u_0 = V_k(P, 1) mod N; with
+/// u_(i+1) = u_i^2 - 2, N is prime exactly when u_(e-2) = 0 mod N.
+pub fn lucas_lehmer_riesel(k: &BigUint, e: u64) -> bool {
+    if k.is_one() {
+        return lucas_lehmer(e);
+    }
+    let n = (k << e) - BigUint::one();
+    let mut p = 3u32;
+    let p = loop {
+        let below = jacobi(&BigUint::from(p - 2), &n);
+        let above = jacobi(&BigUint::from(p + 2), &n);
+        if below == 0 || above == 0 {
+            // N shares a factor with P - 2 or P + 2, so it is only prime if it is that factor.
+            return n <= BigUint::from(p + 2) && n.to_u64().is_some_and(is_small_prime);
+        }
+        if below == 1 && above == -1 {
+            break BigUint::from(p);
+        }
+        p += 1;
+    };
+
+    let two = BigUint::from(2u32);
+    let mut u = lucas_v(k, &p, &n);
+    for _ in 0..e - 2 {
+        u = (&u * &u + &n - &two) % &n;
+    }
+    u.is_zero()
+}
+
+/// `(k, e)` if `n` is a Proth number k * 2^e + 1 with k odd, k < 2^e and
+/// e >= 1.
+pub fn proth_form(n: &BigUint) -> Option<(BigUint, u64)> {
+    if *n < BigUint::from(3u32) {
+        return None;
+    }
+    let below = n - BigUint::one();
+    let e = below.trailing_zeros()?;
+    let k = below >> e;
+    (k.bits() <= e).then_some((k, e))
+}
+
+/// Proth's theorem: whether N = k * 2^e + 1 is prime, for odd k < 2^e.
+///
+/// N is prime exactly when a^((N - 1) / 2) = -1 mod N for some a; any
+/// quadratic non-residue a decides it with one modexp.
+pub fn proth(k: &BigUint, e: u64) -> bool {
+    let n = (k << e) + BigUint::one();
+    let below = &n - BigUint::one();
+    let mut a = BigUint::from(2u32);
+    loop {
+        match jacobi(&a, &n) {
+            -1 => break,
+            0 => return a == n,
+            _ => a += 1u32,
+        }
+    }
+    a.modpow(&(&below >> 1usize), &n) == below
+}
+
+/// V_k(P, 1) mod `n` by the Lucas ladder V_2m = V_m^2 - 2,
+/// V_(2m+1) = V_m V_(m+1) - P.
+fn lucas_v(k: &BigUint, p: &BigUint, n: &BigUint) -> BigUint {
+    let two = BigUint::from(2u32);
+    let mut a = p % n;
+    let mut b = (&a * &a + n - &two) % n;
+    for bit in (0..k.bits() - 1).rev() {
+        let ab = (&a * &b + n - p % n) % n;
+        if k.bit(bit) {
+            a = ab;
+            b = (&b * &b + n - &two) % n;
+        } else {
+            b = ab;
+            a = (&a * &a + n - &two) % n;
+        }
+    }
+    a
+}
+
+/// `x mod (2^p - 1)`, folding the high bits onto the low ones instead of dividing.
+fn reduce_mersenne(mut x: BigUint, p: u64, m: &BigUint) -> BigUint {
+    while x.bits() > p {
+        x = (&x & m) + (x >> p);
+    }
+    if x == *m { BigUint::zero() } else { x }
+}
+
+/// Trial division for the exponents and multipliers of special forms.
+fn is_small_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    if n.is_multiple_of(2) {
+        return n == 2;
+    }
+    let mut d = 3;
+    while d <= n / d {
+        if n.is_multiple_of(d) {
+            return false;
+        }
+        d += 2;
+    }
+    true
+}
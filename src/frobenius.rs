@@ -0,0 +1,219 @@
+//! Grantham's random quadratic Frobenius test.
+//!
+//! Like [`crate::core`] this needs only `alloc`.
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
+
+use crate::core::{Scratch, factor_out_twos, jacobi, random_range};
+use crate::factor::primes_up_to;
+
+/// Parameter draws per round before giving up on finding a suitable (b, c).
+///
+/// For a prime, each draw succeeds with probability about 1/4.
+const MAX_PARAMETER_DRAWS: usize = 1000;
+
+/// Grantham's per-round bound holds for `n` with no prime factor up to
+/// this, so [`frobenius`] trial-divides that far first.
+const TRIAL_BOUND: u64 = 50_000;
+
+/// u + v x in [`Ring`].
+struct Quadratic {
+    u: Scratch,
+    v: Scratch,
+}
+
+impl Quadratic {
+    fn is_constant(&self, value: &BigUint) -> bool {
+        self.v.0.is_zero() && self.u.0 == *value
+    }
+}
+
+/// (Z/nZ)[x] modulo x^2 - b x - c.
+struct Ring<'a> {
+    n: &'a BigUint,
+    b: BigUint,
+    c: BigUint,
+}
+
+impl Ring<'_> {
+    fn mul(&self, p: &Quadratic, q: &Quadratic) -> Quadratic {
+        let n = self.n;
+        let vv = Scratch(&p.v.0 * &q.v.0 % n);
+        // x^2 = b x + c
+        Quadratic {
+            u: Scratch((&p.u.0 * &q.u.0 + &self.c * &vv.0) % n),
+            v: Scratch((&p.u.0 * &q.v.0 + &q.u.0 * &p.v.0 + &self.b * &vv.0) % n),
+        }
+    }
+
+    /// x^exponent.
+    fn x_pow(&self, exponent: &BigUint) -> Quadratic {
+        let x = Quadratic {
+            u: Scratch(BigUint::zero()),
+            v: Scratch(BigUint::one()),
+        };
+        let mut result = Quadratic {
+            u: Scratch(BigUint::one()),
+            v: Scratch(BigUint::zero()),
+        };
+        for bit in (0..exponent.bits()).rev() {
+            result = self.mul(&result, &result);
+            if exponent.bit(bit) {
+                result = self.mul(&result, &x);
+            }
+        }
+        result
+    }
+}
+
+/// Run up to `rounds` rounds of the random quadratic Frobenius test on `n`.
+///
+/// Each round draws b, c with Jacobi symbols ((b^2 + 4c)/n) = -1 and
+/// (-c/n) = 1 and checks, modulo n and x^2 - b x - c, that x^((n+1)/2) is
+/// an integer, that x^(n+1) = -c, and that with n^2 - 1 = 2^r s, either
+/// x^s = 1 or x^(2^j s) = -1 for some j < r - 1. Having trial-divided `n`
+/// by the primes up to 50000 first, as Grantham assumes, the chance that a
+/// composite passes one round is below 1/7710.
+pub(crate) fn frobenius<R: RngCore + CryptoRng + ?Sized>(
+    n: &BigUint,
+    rounds: usize,
+    rng: &mut R,
+    on_round: &mut dyn FnMut(usize),
+) -> bool {
+    let two = BigUint::from(2u32);
+    if *n < two {
+        return false;
+    }
+    if *n == two {
+        return true;
+    }
+    if n.is_even() {
+        return false;
+    }
+    for p in primes_up_to(TRIAL_BOUND).into_iter().skip(1) {
+        if (n % p).is_zero() {
+            return *n == BigUint::from(p);
+        }
+    }
+    if *n < BigUint::from(TRIAL_BOUND * TRIAL_BOUND) {
+        return true;
+    }
+    let root = n.sqrt();
+    if &root * &root == *n {
+        return false;
+    }
+
+    let n_minus_one = Scratch(n - BigUint::one());
+    let n_plus_one = Scratch(n + BigUint::one());
+    let half = Scratch(&n_plus_one.0 >> 1usize);
+    let (r, s) = factor_out_twos(&(&n_minus_one.0 * &n_plus_one.0));
+    let s = Scratch(s);
+    for round in 1..=rounds {
+        on_round(round);
+        let Some((b, c)) = parameters(n, rng) else {
+            return false;
+        };
+        let minus_c = Scratch(n - &c);
+        let ring = Ring { n, b, c };
+
+        let half_power = ring.x_pow(&half.0);
+        if !half_power.v.0.is_zero() {
+            return false;
+        }
+        if !ring.mul(&half_power, &half_power).is_constant(&minus_c.0) {
+            return false;
+        }
+        let mut y = ring.x_pow(&s.0);
+        if y.is_constant(&BigUint::one()) {
+            continue;
+        }
+        let mut passed = false;
+        for _ in 0..r - 1 {
+            if y.is_constant(&n_minus_one.0) {
+                passed = true;
+                break;
+            }
+            y = ring.mul(&y, &y);
+        }
+        if !passed {
+            return false;
+        }
+    }
+    true
+}
+
+/// Draw (b, c) for [`frobenius`], or `None` once a Jacobi symbol of zero
+/// reveals a factor of `n` (or no suitable pair turns up).
+fn parameters<R: RngCore + CryptoRng + ?Sized>(
+    n: &BigUint,
+    rng: &mut R,
+) -> Option<(BigUint, BigUint)> {
+    let one = BigUint::one();
+    let high = n - &one;
+    for _ in 0..MAX_PARAMETER_DRAWS {
+        let b = random_range(&one, &high, rng);
+        let c = random_range(&one, &high, rng);
+        let delta = (&b * &b + (&c << 2usize)) % n;
+        if delta.is_zero() {
+            continue;
+        }
+        match (jacobi(&delta, n), jacobi(&(n - &c), n)) {
+            (0, _) | (_, 0) => return None,
+            (-1, 1) => return Some((b, c)),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn passes(n: u128) -> bool {
+        let mut rng = StdRng::seed_from_u64(53);
+        frobenius(&BigUint::from(n), 10, &mut rng, &mut |_| {})
+    }
+
+    #[test]
+    fn rejects_lucas_and_fibonacci_pseudoprimes() {
+        // Lucas pseudoprimes under Selfridge's parameters, then Fibonacci
+        // pseudoprimes.
+        let lucas = [323, 377, 1159, 1829, 3827, 5459, 5777, 9071, 9179, 10877];
+        let fibonacci = [323, 377, 1891, 3827, 4181, 5777, 6601, 6721, 8149, 10877];
+        for n in lucas.into_iter().chain(fibonacci) {
+            assert!(!passes(n), "{n}");
+        }
+    }
+
+    #[test]
+    fn rejects_pseudoprimes_past_trial_division() {
+        // A Carmichael number and a strong pseudoprime to the prime bases
+        // up to 23, every factor of both above the trial bound.
+        assert!(!passes(50647 * 101293 * 151939));
+        assert!(!passes(3825123056546413051));
+    }
+
+    #[test]
+    fn accepts_primes() {
+        for n in [
+            2,
+            3,
+            59,
+            61,
+            3727,
+            49999,
+            50021,
+            (1 << 61) - 1,
+            (1 << 89) - 1,
+            (1 << 127) - 1,
+        ] {
+            assert!(passes(n), "{n}");
+        }
+    }
+}
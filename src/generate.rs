@@ -0,0 +1,57 @@
+//! One-shot generation functions.
+
+use num_bigint::BigUint;
+#[cfg(feature = "os-rng")]
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+
+use crate::search::Search;
+use crate::{MillerRabin, PrimalityTest, Result};
+
+/// Generate a random probable prime with the requested bit length.
+#[cfg(feature = "os-rng")]
+pub fn generate_probable_prime(bits: usize, rounds: usize) -> Result<BigUint> {
+    generate_probable_prime_with_rng(bits, rounds, &mut OsRng)
+}
+
+/// Like [`generate_probable_prime`], drawing randomness from `rng`.
+pub fn generate_probable_prime_with_rng<R: RngCore + CryptoRng>(
+    bits: usize,
+    rounds: usize,
+    rng: &mut R,
+) -> Result<BigUint> {
+    generate_probable_prime_with_test(bits, &MillerRabin::new(rounds), rng)
+}
+
+/// Generate a probable prime, accepting candidates that pass `test`.
+pub fn generate_probable_prime_with_test<R: RngCore + CryptoRng>(
+    bits: usize,
+    test: &dyn PrimalityTest,
+    rng: &mut R,
+) -> Result<BigUint> {
+    Search::new(test).find_probable_prime(bits, rng)
+}
+
+/// Generate a safe prime p = 2q + 1 where both p and q are probable primes.
+#[cfg(feature = "os-rng")]
+pub fn generate_safe_prime(bits: usize, rounds: usize) -> Result<BigUint> {
+    generate_safe_prime_with_rng(bits, rounds, &mut OsRng)
+}
+
+/// Like [`generate_safe_prime`], drawing randomness from `rng`.
+pub fn generate_safe_prime_with_rng<R: RngCore + CryptoRng>(
+    bits: usize,
+    rounds: usize,
+    rng: &mut R,
+) -> Result<BigUint> {
+    generate_safe_prime_with_test(bits, &MillerRabin::new(rounds), rng)
+}
+
+/// Generate a safe prime, requiring both q and p to pass `test`.
+pub fn generate_safe_prime_with_test<R: RngCore + CryptoRng>(
+    bits: usize,
+    test: &dyn PrimalityTest,
+    rng: &mut R,
+) -> Result<BigUint> {
+    Search::new(test).find_safe_prime(bits, rng)
+}
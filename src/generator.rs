@@ -0,0 +1,640 @@
+//! Fluent configuration for prime generation.
+
+use num_bigint::BigUint;
+#[cfg(feature = "os-rng")]
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "os-rng")]
+use std::panic::resume_unwind;
+use std::sync::Arc;
+#[cfg(feature = "os-rng")]
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "os-rng")]
+use crate::SearchStats;
+use crate::chain::ChainKind;
+use crate::residue::ResidueClass;
+use crate::search::Search;
+#[cfg(feature = "zeroize")]
+use crate::secret::SecretPrime;
+use crate::{
+    CancellationToken, CandidateEvent, CryptoRngCore, GenerationReport, MillerRabin, PrimalityTest,
+    ProgressHook, Result, rounds_for_bits,
+};
+
+/// Default bit length used when the builder is not told otherwise.
+pub const DEFAULT_BITS: usize = 2048;
+
+/// Bits of the auxiliary primes of a `bits`-bit strong prime: just over
+/// the minimums of ANSI X9.31 and FIPS 186-4 table B.1 for an RSA modulus
+/// of twice its size (100, 140 and 170 bits).
+pub fn strong_factor_bits(bits: usize) -> usize {
+    match bits {
+        0..=512 => 101,
+        513..=1024 => 141,
+        _ => 171,
+    }
+}
+
+/// A configured prime generator, built via [`PrimeGenerator::builder`].
+#[derive(Clone, Debug)]
+pub struct PrimeGenerator {
+    bits: usize,
+    rounds: usize,
+    safe: bool,
+    twin: bool,
+    chain: Option<(ChainKind, usize)>,
+    bi_twin: Option<usize>,
+    strong: Option<usize>,
+    blum: bool,
+    class: Option<ResidueClass>,
+    top_bits: usize,
+    prefix: Option<BigUint>,
+    leading: BigUint,
+    weight: Option<usize>,
+    threads: usize,
+    test: Arc<dyn PrimalityTest>,
+    cancel: Option<CancellationToken>,
+    hook: Option<ProgressHook>,
+    timeout: Option<Duration>,
+    max_attempts: Option<u64>,
+    fermat: bool,
+    screen: Option<usize>,
+    #[cfg(feature = "zeroize")]
+    secret: bool,
+}
+
+impl PrimeGenerator {
+    /// Start configuring a generator with the default settings.
+    pub fn builder() -> PrimeGeneratorBuilder {
+        PrimeGeneratorBuilder::default()
+    }
+
+    /// Bit length of the primes this generator produces.
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Miller–Rabin rounds run on each candidate.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// Whether this generator produces safe primes.
+    pub fn safe(&self) -> bool {
+        self.safe
+    }
+
+    /// Whether this generator produces the smaller primes of twin pairs.
+    pub fn twin(&self) -> bool {
+        self.twin
+    }
+
+    /// The kind and length of the Cunningham chains this generator starts,
+    /// if it does.
+    pub fn chain(&self) -> Option<(ChainKind, usize)> {
+        self.chain
+    }
+
+    /// The links in the bi-twin chains this generator finds, if it does.
+    pub fn bi_twin(&self) -> Option<usize> {
+        self.bi_twin
+    }
+
+    /// Bits of the auxiliary primes of the strong primes this generator
+    /// produces, if it does.
+    pub fn strong(&self) -> Option<usize> {
+        self.strong
+    }
+
+    /// Whether this generator produces Blum primes, p ≡ 3 (mod 4).
+    pub fn blum(&self) -> bool {
+        self.blum
+    }
+
+    /// The residue class plain probable primes are drawn from, if any: the
+    /// one given to [`PrimeGeneratorBuilder::congruent`], or the Blum
+    /// primes'.
+    pub fn congruence(&self) -> Option<&ResidueClass> {
+        self.class.as_ref()
+    }
+
+    /// How many of the top bits are set on every candidate.
+    pub fn top_bits(&self) -> usize {
+        self.top_bits
+    }
+
+    /// The leading bits every candidate starts with, if given.
+    pub fn prefix(&self) -> Option<&BigUint> {
+        self.prefix.as_ref()
+    }
+
+    /// How many bits of each prime are set, if fixed.
+    pub fn hamming_weight(&self) -> Option<usize> {
+        self.weight
+    }
+
+    /// Worker threads used by [`Self::generate`] and [`Self::generate_report`].
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// The primality test applied to candidates.
+    pub fn test(&self) -> &dyn PrimalityTest {
+        self.test.as_ref()
+    }
+
+    /// Generate one prime with the configured parameters.
+    #[cfg(feature = "os-rng")]
+    pub fn generate(&self) -> Result<BigUint> {
+        self.generate_report().map(|report| report.prime)
+    }
+
+    /// Generate one prime, drawing randomness from `rng`.
+    pub fn generate_with_rng<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<BigUint> {
+        self.generate_report_with_rng(rng)
+            .map(|report| report.prime)
+    }
+
+    /// Generate one prime and report how much work the search took.
+    #[cfg(feature = "os-rng")]
+    pub fn generate_report(&self) -> Result<GenerationReport> {
+        if self.threads > 1 {
+            return self.generate_report_parallel();
+        }
+        self.generate_report_with_rng(&mut OsRng)
+    }
+
+    /// Race [`Self::threads`] searches, each drawing from its own OS RNG
+    /// handle, and keep the first prime found.
+    #[cfg(feature = "os-rng")]
+    fn generate_report_parallel(&self) -> Result<GenerationReport> {
+        let start = Instant::now();
+        let found = CancellationToken::new();
+        let max_attempts = self
+            .max_attempts
+            .map(|max| max.div_ceil(self.threads as u64));
+        let results: Vec<(Result<BigUint>, SearchStats)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut search = self
+                            .search(start)
+                            .max_attempts(max_attempts)
+                            .stop(Some(&found));
+                        let result = self.find(&mut search, &mut OsRng);
+                        if result.is_ok() {
+                            found.cancel();
+                        }
+                        (result, search.stats())
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap_or_else(|panic| resume_unwind(panic)))
+                .collect()
+        });
+
+        let mut stats = SearchStats::default();
+        let mut prime = None;
+        let mut error = None;
+        for (result, worker_stats) in results {
+            stats += worker_stats;
+            match result {
+                Ok(p) if prime.is_none() => prime = Some(p),
+                Ok(p) => self.discard(p),
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        match (prime, error) {
+            (Some(prime), _) => Ok(GenerationReport {
+                prime,
+                stats,
+                elapsed: start.elapsed(),
+                rng: std::any::type_name::<OsRng>(),
+            }),
+            (None, Some(err)) => Err(err.with_stats(stats)),
+            (None, None) => unreachable!("every worker returns a prime or an error"),
+        }
+    }
+
+    /// Drop a surplus prime, wiping it first for secret generators.
+    #[cfg(feature = "os-rng")]
+    #[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+    fn discard(&self, mut prime: BigUint) {
+        #[cfg(feature = "zeroize")]
+        if self.secret {
+            crate::secret::wipe(&mut prime);
+        }
+        drop(prime);
+    }
+
+    /// Like [`Self::generate_report`], drawing randomness from `rng`.
+    pub fn generate_report_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> Result<GenerationReport> {
+        let start = Instant::now();
+        let mut search = self.search(start).max_attempts(self.max_attempts);
+        let prime = self.find(&mut search, rng)?;
+        Ok(GenerationReport {
+            prime,
+            stats: search.stats(),
+            elapsed: start.elapsed(),
+            rng: std::any::type_name::<R>(),
+        })
+    }
+
+    /// A search configured from this generator, with its timeout counted from `start`.
+    fn search(&self, start: Instant) -> Search<'_> {
+        let search = Search::new(self.test.as_ref())
+            .cancel(self.cancel.as_ref())
+            .hook(self.hook.as_ref())
+            .deadline(self.timeout.map(|timeout| start + timeout))
+            .fermat(self.fermat)
+            .screen(self.screen)
+            .class(self.class.as_ref())
+            .prefix(Some(&self.leading))
+            .weight(self.weight);
+        #[cfg(feature = "zeroize")]
+        let search = search.secret(self.secret);
+        search
+    }
+
+    fn find(&self, search: &mut Search<'_>, rng: &mut dyn CryptoRngCore) -> Result<BigUint> {
+        if self.safe {
+            search.find_safe_prime(self.bits, rng)
+        } else if let Some(factor_bits) = self.strong {
+            search.find_strong_prime(self.bits, factor_bits, rng)
+        } else if let Some((kind, length)) = self.chain {
+            search.find_chain(self.bits, kind, length, rng)
+        } else if let Some(links) = self.bi_twin {
+            search.find_bi_twin(self.bits, links, rng)
+        } else if self.twin {
+            search.find_twin_prime(self.bits, rng)
+        } else {
+            search.find_probable_prime(self.bits, rng)
+        }
+    }
+
+    /// Iterate over freshly generated primes.
+    ///
+    /// The iterator is infinite for a valid configuration; it ends early
+    /// only if generation fails.
+    #[cfg(feature = "os-rng")]
+    pub fn iter(&self) -> Primes<'_, OsRng> {
+        self.iter_with_rng(OsRng)
+    }
+
+    /// Generate one prime wrapped so it is wiped on drop.
+    ///
+    /// Combine with [`PrimeGeneratorBuilder::secret`] to also wipe rejected
+    /// candidates during the search.
+    #[cfg(all(feature = "zeroize", feature = "os-rng"))]
+    pub fn generate_secret(&self) -> Result<SecretPrime> {
+        self.generate_secret_with_rng(&mut OsRng)
+    }
+
+    /// Like [`Self::generate_secret`], drawing randomness from `rng`.
+    #[cfg(feature = "zeroize")]
+    pub fn generate_secret_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> Result<SecretPrime> {
+        self.generate_with_rng(rng).map(SecretPrime::new)
+    }
+
+    /// Like [`Self::iter`], drawing randomness from `rng`.
+    pub fn iter_with_rng<R: RngCore + CryptoRng>(&self, rng: R) -> Primes<'_, R> {
+        Primes {
+            generator: self,
+            rng,
+            done: false,
+        }
+    }
+}
+
+/// Iterator returned by [`PrimeGenerator::iter`].
+#[derive(Debug)]
+pub struct Primes<'a, R> {
+    generator: &'a PrimeGenerator,
+    rng: R,
+    done: bool,
+}
+
+impl<R: RngCore + CryptoRng> Iterator for Primes<'_, R> {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        if self.done {
+            return None;
+        }
+        let prime = self.generator.generate_with_rng(&mut self.rng).ok();
+        self.done = prime.is_none();
+        prime
+    }
+}
+
+impl<R: RngCore + CryptoRng> std::iter::FusedIterator for Primes<'_, R> {}
+
+impl Default for PrimeGenerator {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// Builder for [`PrimeGenerator`].
+#[derive(Clone, Debug)]
+pub struct PrimeGeneratorBuilder {
+    bits: usize,
+    rounds: Option<usize>,
+    safe: bool,
+    twin: bool,
+    chain: Option<(ChainKind, usize)>,
+    bi_twin: Option<usize>,
+    strong: Option<usize>,
+    blum: bool,
+    congruence: Option<ResidueClass>,
+    top_bits: usize,
+    prefix: Option<BigUint>,
+    weight: Option<usize>,
+    threads: usize,
+    test: Option<Arc<dyn PrimalityTest>>,
+    cancel: Option<CancellationToken>,
+    hook: Option<ProgressHook>,
+    timeout: Option<Duration>,
+    max_attempts: Option<u64>,
+    fermat: bool,
+    screen: Option<usize>,
+    #[cfg(feature = "zeroize")]
+    secret: bool,
+}
+
+impl Default for PrimeGeneratorBuilder {
+    fn default() -> Self {
+        Self {
+            bits: DEFAULT_BITS,
+            rounds: None,
+            safe: false,
+            twin: false,
+            chain: None,
+            bi_twin: None,
+            strong: None,
+            blum: false,
+            congruence: None,
+            top_bits: 1,
+            prefix: None,
+            weight: None,
+            threads: 1,
+            test: None,
+            cancel: None,
+            hook: None,
+            timeout: None,
+            max_attempts: None,
+            fermat: true,
+            screen: None,
+            #[cfg(feature = "zeroize")]
+            secret: false,
+        }
+    }
+}
+
+impl PrimeGeneratorBuilder {
+    /// Set the bit length of the generated prime.
+    pub fn bits(mut self, bits: usize) -> Self {
+        self.bits = bits;
+        self
+    }
+
+    /// Set the number of Miller–Rabin rounds.
+    ///
+    /// Defaults to [`rounds_for_bits`] for the configured size, which keeps
+    /// the error below 2^-128 for random candidates. Ignored when a custom
+    /// test is supplied via [`Self::test`].
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = Some(rounds);
+        self
+    }
+
+    /// Generate a safe prime p = 2q + 1 instead of a plain probable prime.
+    pub fn safe(mut self, safe: bool) -> Self {
+        self.safe = safe;
+        self
+    }
+
+    /// Generate the smaller prime p of a twin pair, with p + 2 also a
+    /// probable prime. Ignored for safe primes.
+    pub fn twin(mut self, twin: bool) -> Self {
+        self.twin = twin;
+        self
+    }
+
+    /// Generate the first prime p of a Cunningham chain of `kind` with
+    /// `length` members, each twice the one before plus or minus one; a
+    /// length of one is any prime. Ignored for safe primes, and takes
+    /// precedence over [`Self::twin`].
+    pub fn chain(mut self, kind: ChainKind, length: usize) -> Self {
+        self.chain = Some((kind, length));
+        self
+    }
+
+    /// Generate the bottom prime n − 1 of a bi-twin chain on a base n with
+    /// `links` links: n ± 1, 2n ± 1, … up to 2^(`links`−1)·n ± 1, all
+    /// probable primes. One link is a twin pair. Ignored for safe primes
+    /// and chains, and takes precedence over [`Self::twin`].
+    pub fn bi_twin(mut self, links: usize) -> Self {
+        self.bi_twin = Some(links);
+        self
+    }
+
+    /// Generate a strong prime p in the sense of ANSI X9.31 by Gordon's
+    /// algorithm: p − 1, p + 1 and r − 1, for the large factor r of p − 1,
+    /// each have a prime factor of at least `factor_bits` bits.
+    ///
+    /// [`strong_factor_bits`] gives the sizes X9.31 asks for. Ignored for
+    /// safe primes, and takes precedence over chains and twins.
+    pub fn strong(mut self, factor_bits: usize) -> Self {
+        self.strong = Some(factor_bits);
+        self
+    }
+
+    /// Generate a Blum prime, p ≡ 3 (mod 4), as Blum–Blum–Shub and Rabin
+    /// need. Bit 1 is set on every candidate as it is drawn, so the
+    /// constraint costs no extra tests. Safe primes above 7 are Blum
+    /// primes already; twins, chains and strong primes ignore it.
+    pub fn blum(mut self, blum: bool) -> Self {
+        self.blum = blum;
+        self
+    }
+
+    /// Generate a prime in `class`, p ≡ r (mod m). Like [`Self::blum`],
+    /// which it takes precedence over, each candidate is placed in the
+    /// class as it is drawn; the primes must have at least
+    /// [`ResidueClass::min_bits`] bits. Safe primes, twins, chains and
+    /// strong primes ignore it.
+    pub fn congruent(mut self, class: ResidueClass) -> Self {
+        self.congruence = Some(class);
+        self
+    }
+
+    /// Set the top `top_bits` bits of every candidate rather than only the
+    /// highest (the default, 1). With 2, as OpenSSL's RSA key generation
+    /// does, the product of two such primes has exactly twice their bits,
+    /// and each is above the √2·2^(bits − 1) floor of FIPS 186-4. Applies
+    /// to plain and safe primes; the rest ignore it.
+    pub fn top_bits(mut self, top_bits: usize) -> Self {
+        self.top_bits = top_bits;
+        self
+    }
+
+    /// Pin the most significant bits of every candidate to `prefix`, from
+    /// its leading one down, and draw only the bits below it; a prefix of
+    /// 0xdeadbeef gives primes whose hex starts `deadbeef`. Takes
+    /// precedence over [`Self::top_bits`], which is the prefix of that
+    /// many ones, and applies to the same primes. The primes need at least
+    /// one more bit than the prefix, two for safe primes.
+    pub fn prefix(mut self, prefix: BigUint) -> Self {
+        self.prefix = Some(prefix);
+        self
+    }
+
+    /// Generate a prime with exactly `weight` bits set: the prefix (the
+    /// top bit by default) and the lowest bit, and the rest at random
+    /// positions between.
+    ///
+    /// The search fails with
+    /// [`WeightOutOfRange`](crate::PrimeGenError::WeightOutOfRange) when
+    /// no candidate can have the weight. At extreme weights there may be
+    /// no prime among those that can (of weight 2 only the Fermat primes):
+    /// when there are a few thousand candidates or fewer, each is tried
+    /// once and the search fails with
+    /// [`AttemptsExhausted`](crate::PrimeGenError::AttemptsExhausted);
+    /// beyond that, set a timeout or attempt limit. Takes precedence over
+    /// [`Self::congruent`] and [`Self::blum`]; safe primes, twins, chains
+    /// and strong primes ignore it.
+    pub fn hamming_weight(mut self, weight: usize) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Use `test` instead of the default Miller–Rabin test.
+    pub fn test<T: PrimalityTest + 'static>(mut self, test: T) -> Self {
+        self.test = Some(Arc::new(test));
+        self
+    }
+
+    /// Abort the search once `token` is cancelled.
+    pub fn cancel_token(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Fail with [`PrimeGenError::TimedOut`](crate::PrimeGenError::TimedOut)
+    /// if a single prime takes longer than `timeout` to find.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fail with [`PrimeGenError::AttemptsExhausted`](crate::PrimeGenError::AttemptsExhausted)
+    /// after drawing `max` candidates for a single prime.
+    pub fn max_attempts(mut self, max: u64) -> Self {
+        self.max_attempts = Some(max);
+        self
+    }
+
+    /// Check 2^(n - 1) = 1 mod n before running the primality test. On by default.
+    ///
+    /// The prefilter uses variable-time `num-bigint` arithmetic, so turn it
+    /// off when pairing the generator with
+    /// [`ConstantTimeMillerRabin`](crate::ct::ConstantTimeMillerRabin).
+    pub fn fermat_prefilter(mut self, enabled: bool) -> Self {
+        self.fermat = enabled;
+        self
+    }
+
+    /// Screen candidates with `rounds` Miller–Rabin rounds during the search
+    /// and run the full test only on the survivor.
+    ///
+    /// Almost every composite fails its first round, so this mostly saves
+    /// work on safe primes, where a prime q is otherwise fully tested before
+    /// 2q + 1 is even looked at. The result passes the same full test either way.
+    pub fn screen_rounds(mut self, rounds: usize) -> Self {
+        self.screen = Some(rounds.max(1));
+        self
+    }
+
+    /// Search on `threads` worker threads in [`PrimeGenerator::generate`] and
+    /// [`PrimeGenerator::generate_report`]; the `_with_rng` variants always
+    /// use one thread because they borrow a single RNG. Defaults to 1.
+    ///
+    /// A [`Self::max_attempts`] limit is split evenly between the workers.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Call `f` for every candidate drawn, sieve rejection, and test round.
+    pub fn on_candidate<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&CandidateEvent<'_>) + Send + Sync + 'static,
+    {
+        self.hook = Some(ProgressHook::new(f));
+        self
+    }
+
+    /// Treat candidates as secret key material and wipe rejected ones.
+    #[cfg(feature = "zeroize")]
+    pub fn secret(mut self, secret: bool) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    /// Finish configuration.
+    pub fn build(self) -> PrimeGenerator {
+        let rounds = self.rounds.unwrap_or_else(|| rounds_for_bits(self.bits));
+        PrimeGenerator {
+            bits: self.bits,
+            rounds,
+            safe: self.safe,
+            twin: self.twin,
+            chain: self.chain,
+            bi_twin: self.bi_twin,
+            strong: self.strong,
+            blum: self.blum,
+            class: self
+                .congruence
+                .or_else(|| self.blum.then(ResidueClass::blum)),
+            top_bits: self.top_bits,
+            leading: self
+                .prefix
+                .clone()
+                .filter(|prefix| prefix.bits() > 0)
+                .unwrap_or_else(|| (BigUint::from(1u32) << self.top_bits.max(1)) - 1u32),
+            prefix: self.prefix,
+            weight: self.weight,
+            threads: self.threads,
+            test: self
+                .test
+                .unwrap_or_else(|| Arc::new(MillerRabin::new(rounds))),
+            cancel: self.cancel,
+            hook: self.hook,
+            timeout: self.timeout,
+            max_attempts: self.max_attempts,
+            fermat: self.fermat,
+            screen: self.screen,
+            #[cfg(feature = "zeroize")]
+            secret: self.secret,
+        }
+    }
+
+    /// Shorthand for `build().generate()`.
+    #[cfg(feature = "os-rng")]
+    pub fn generate(self) -> Result<BigUint> {
+        self.build().generate()
+    }
+}
@@ -0,0 +1,68 @@
+//! GMP-backed Miller–Rabin, enabled with the `gmp` feature.
+//!
+//! The public API still speaks `BigUint`; candidates and witnesses are
+//! converted to `rug::Integer` so the exponentiations run on GMP, which is
+//! markedly faster than `num-bigint` at 4096 bits and above.
+
+use num_bigint::BigUint;
+use num_integer::Integer as _;
+use rand::{CryptoRng, RngCore};
+use rug::Integer;
+use rug::integer::Order;
+
+use crate::core::{factor_out_twos, random_witness};
+
+fn to_rug(n: &BigUint) -> Integer {
+    Integer::from_digits(&n.to_u64_digits(), Order::Lsf)
+}
+
+pub(crate) fn miller_rabin<R: RngCore + CryptoRng + ?Sized>(
+    n: &BigUint,
+    rounds: usize,
+    rng: &mut R,
+    on_round: &mut dyn FnMut(usize),
+) -> bool {
+    let two = BigUint::from(2u32);
+
+    if *n < two {
+        return false;
+    }
+    if *n == two {
+        return true;
+    }
+    if n.is_even() {
+        return false;
+    }
+
+    let n_minus_one = n - 1u32;
+    let (s, d) = factor_out_twos(&n_minus_one);
+    let modulus = to_rug(n);
+    let minus_one = to_rug(&n_minus_one);
+    let d = to_rug(&d);
+
+    'witness: for round in 1..=rounds {
+        on_round(round);
+        let mut x = to_rug(&random_witness(&n_minus_one, rng));
+        x.pow_mod_mut(&d, &modulus)
+            .expect("exponent is non-negative");
+
+        if x == 1 || x == minus_one {
+            continue 'witness;
+        }
+
+        for _ in 1..s {
+            x.square_mut();
+            x %= &modulus;
+            if x == minus_one {
+                continue 'witness;
+            }
+            if x == 1 {
+                return false;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
@@ -0,0 +1,298 @@
+//! Hilbert class polynomials, the source of the curves [`crate::ecpp`]
+//! builds with complex multiplication.
+//!
+//! H_D is the monic polynomial whose roots are j(τ) for the reduced forms
+//! of discriminant D. Its coefficients are integers, so they are found by
+//! evaluating j in fixed-point complex arithmetic with enough bits and
+//! rounding. Like [`crate::core`] this needs only `alloc`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// Fractional bits beyond the estimated coefficient size, absorbing the
+/// rounding of several hundred fixed-point operations.
+const GUARD_BITS: u64 = 128;
+
+/// A reduced, primitive binary quadratic form ax² + bxy + cy².
+type Form = (i64, i64, i64);
+
+/// The reduced primitive forms of discriminant `d` < 0: |b| ≤ a ≤ c, with
+/// b ≥ 0 when |b| = a or a = c. There are h(d) of them.
+pub(crate) fn reduced_forms(d: i64) -> Vec<Form> {
+    let mut forms = Vec::new();
+    let mut a = 1;
+    while 3 * a * a <= -d {
+        for b in 1 - a..=a {
+            let numerator = b * b - d;
+            if numerator % (4 * a) != 0 {
+                continue;
+            }
+            let c = numerator / (4 * a);
+            if c < a || (b < 0 && a == c) || a.gcd(&b).gcd(&c) != 1 {
+                continue;
+            }
+            forms.push((a, b, c));
+        }
+        a += 1;
+    }
+    forms
+}
+
+/// Whether `d` < 0 is a fundamental discriminant: squarefree and 1 modulo
+/// 4, or 4 times a squarefree number that is 2 or 3 modulo 4.
+pub(crate) fn is_fundamental(d: i64) -> bool {
+    let m = -d;
+    match d.rem_euclid(4) {
+        1 => is_squarefree(m),
+        0 => matches!((d / 4).rem_euclid(4), 2 | 3) && is_squarefree(m / 4),
+        _ => false,
+    }
+}
+
+fn is_squarefree(m: i64) -> bool {
+    (2..).take_while(|p| p * p <= m).all(|p| m % (p * p) != 0)
+}
+
+/// The coefficients of H_`d`, constant term first, for a discriminant
+/// `d` < −4 (−3 and −4 have the roots 0 and 1728).
+pub(crate) fn hilbert_class_polynomial(d: i64) -> Vec<BigInt> {
+    let forms = reduced_forms(d);
+    // |j(τ)| is about e^(π√|d|/a), so the coefficients, products of at most
+    // h of the roots, have at most about this many bits.
+    let root_d = ((-d).isqrt() + 1) as f64;
+    let size: f64 = forms
+        .iter()
+        .map(|&(a, _, _)| {
+            core::f64::consts::PI * root_d / (a as f64 * core::f64::consts::LN_2) + 12.0
+        })
+        .sum();
+    let fixed = Fixed::new(size as u64 + GUARD_BITS);
+
+    let mut poly = vec![Complex::real(fixed.one())];
+    for &(a, b, _) in &forms {
+        let j = fixed.j_invariant(d, a, b);
+        let mut next = vec![Complex::default(); poly.len() + 1];
+        for (k, coefficient) in poly.iter().enumerate() {
+            next[k + 1] = next[k + 1].add(coefficient);
+            next[k] = next[k].sub(&fixed.mul(&j, coefficient));
+        }
+        poly = next;
+    }
+    poly.iter().map(|c| fixed.round(&c.re)).collect()
+}
+
+/// A complex number with fixed-point parts.
+#[derive(Clone, Debug, Default)]
+struct Complex {
+    re: BigInt,
+    im: BigInt,
+}
+
+impl Complex {
+    fn real(re: BigInt) -> Self {
+        Complex {
+            re,
+            im: BigInt::zero(),
+        }
+    }
+
+    fn add(&self, other: &Complex) -> Complex {
+        Complex {
+            re: &self.re + &other.re,
+            im: &self.im + &other.im,
+        }
+    }
+
+    fn sub(&self, other: &Complex) -> Complex {
+        Complex {
+            re: &self.re - &other.re,
+            im: &self.im - &other.im,
+        }
+    }
+
+    fn scale(&self, k: i64) -> Complex {
+        Complex {
+            re: &self.re * k,
+            im: &self.im * k,
+        }
+    }
+
+    fn conj(&self) -> Complex {
+        Complex {
+            re: self.re.clone(),
+            im: -&self.im,
+        }
+    }
+
+    /// Whether both parts are within a unit of the last place of zero, where
+    /// truncation leaves negative values.
+    fn is_negligible(&self) -> bool {
+        self.re.bits() <= 1 && self.im.bits() <= 1
+    }
+}
+
+/// Fixed-point arithmetic with `bits` fractional bits.
+struct Fixed {
+    bits: u64,
+    pi: BigInt,
+}
+
+impl Fixed {
+    fn new(bits: u64) -> Self {
+        let mut fixed = Fixed {
+            bits,
+            pi: BigInt::zero(),
+        };
+        // Machin: π = 16 atan(1/5) − 4 atan(1/239).
+        fixed.pi = fixed.atan_inverse(5) * 16 - fixed.atan_inverse(239) * 4;
+        fixed
+    }
+
+    fn one(&self) -> BigInt {
+        BigInt::one() << self.bits
+    }
+
+    /// The nearest integer to `x`.
+    fn round(&self, x: &BigInt) -> BigInt {
+        (x + (BigInt::one() << (self.bits - 1))) >> self.bits
+    }
+
+    fn mul_real(&self, x: &BigInt, y: &BigInt) -> BigInt {
+        (x * y) >> self.bits
+    }
+
+    fn mul(&self, x: &Complex, y: &Complex) -> Complex {
+        Complex {
+            re: (&x.re * &y.re - &x.im * &y.im) >> self.bits,
+            im: (&x.re * &y.im + &x.im * &y.re) >> self.bits,
+        }
+    }
+
+    fn div(&self, x: &Complex, y: &Complex) -> Complex {
+        let numerator = self.mul(x, &y.conj());
+        let norm = self.mul_real(&y.re, &y.re) + self.mul_real(&y.im, &y.im);
+        Complex {
+            re: (numerator.re << self.bits) / &norm,
+            im: (numerator.im << self.bits) / &norm,
+        }
+    }
+
+    /// atan(1/`x`) by its alternating series.
+    fn atan_inverse(&self, x: i64) -> BigInt {
+        let mut term = self.one() / x;
+        let mut sum = BigInt::zero();
+        let mut k = 0i64;
+        while !term.is_zero() {
+            let part = &term / (2 * k + 1);
+            if k.is_even() {
+                sum += part;
+            } else {
+                sum -= part;
+            }
+            term /= x * x;
+            k += 1;
+        }
+        sum
+    }
+
+    /// e^`x`, by the Taylor series of x / 2^k squared k times.
+    fn exp(&self, x: &BigInt) -> BigInt {
+        let halvings = (x.bits() + 8).saturating_sub(self.bits);
+        let y = x >> halvings;
+        let mut sum = self.one();
+        let mut term = self.one();
+        for i in 1i64.. {
+            term = self.mul_real(&term, &y) / i;
+            if term.is_zero() {
+                break;
+            }
+            sum += &term;
+        }
+        for _ in 0..halvings {
+            sum = self.mul_real(&sum, &sum);
+        }
+        sum
+    }
+
+    /// cos θ + i sin θ, by the Taylor series, for |θ| ≤ π.
+    fn cis(&self, theta: &BigInt) -> Complex {
+        let mut result = Complex::real(self.one());
+        let mut term = self.one();
+        for i in 1i64.. {
+            term = self.mul_real(&term, theta) / i;
+            if term.is_zero() {
+                break;
+            }
+            match i % 4 {
+                0 => result.re += &term,
+                1 => result.im += &term,
+                2 => result.re -= &term,
+                _ => result.im -= &term,
+            }
+        }
+        result
+    }
+
+    /// j(τ) for τ = (−b + √d) / 2a, from q = e^(2πiτ) as E4³ / Δ with
+    /// Δ = q ∏ (1 − qⁿ)^24, the product summed by Euler's pentagonal series.
+    fn j_invariant(&self, d: i64, a: i64, b: i64) -> Complex {
+        let root_d = (BigInt::from(-d) << (2 * self.bits)).sqrt();
+        let t = self.mul_real(&self.pi, &root_d) / a;
+        let theta = -(&self.pi * b) / a;
+        let turn = self.cis(&theta);
+        let scale = |r: BigInt| Complex {
+            re: self.mul_real(&r, &turn.re),
+            im: self.mul_real(&r, &turn.im),
+        };
+        let q = scale(self.exp(&-&t));
+        let q_inverse = scale(self.exp(&t)).conj();
+
+        let mut powers = vec![Complex::real(self.one())];
+        loop {
+            let next = self.mul(powers.last().expect("starts with q^0"), &q);
+            if next.is_negligible() {
+                break;
+            }
+            powers.push(next);
+        }
+        let power = |n: usize| powers.get(n).cloned().unwrap_or_default();
+
+        let mut e4 = Complex::real(self.one());
+        for (n, q_n) in powers.iter().enumerate().skip(1) {
+            e4 = e4.add(&q_n.scale(240 * sigma3(n as i64)));
+        }
+        let mut eta = Complex::real(self.one());
+        for k in 1.. {
+            let low = k * (3 * k - 1) / 2;
+            if low >= powers.len() {
+                break;
+            }
+            let pair = power(low).add(&power(low + k));
+            eta = if k.is_odd() {
+                eta.sub(&pair)
+            } else {
+                eta.add(&pair)
+            };
+        }
+        let eta8 = (0..3).fold(eta, |x, _| self.mul(&x, &x));
+        let eta24 = self.mul(&self.mul(&eta8, &eta8), &eta8);
+        let e4_cubed = self.mul(&self.mul(&e4, &e4), &e4);
+        self.div(&self.mul(&e4_cubed, &q_inverse), &eta24)
+    }
+}
+
+/// The sum of the cubes of the divisors of `n`.
+fn sigma3(n: i64) -> i64 {
+    (1..=n).filter(|k| n % k == 0).map(|k| k * k * k).sum()
+}
+
+/// `x` modulo `n`, in 0..`n`, for reducing H_D modulo a prime.
+pub(crate) fn residue(x: &BigInt, n: &BigUint) -> BigUint {
+    let n = BigInt::from_biguint(Sign::Plus, n.clone());
+    x.mod_floor(&n)
+        .to_biguint()
+        .expect("mod_floor by a positive modulus is non-negative")
+}
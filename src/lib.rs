@@ -0,0 +1,157 @@
+//! Generate large probable primes and safe primes.
+//!
+//! The routines here back the `find-big-prime` binary and can be used
+//! directly by other crates that need DH or RSA style parameters.
+//!
+//! Cargo features:
+//!
+//! - `std` (default): generators, builder, errors and reports.
+//! - `os-rng` (default): convenience entry points that draw from the OS RNG.
+//! - `cli` (default): the `find-big-prime` binary and its `clap` dependency.
+//! - `tokio`: async wrappers on tokio's blocking pool.
+//! - `serde`: `Serialize`/`Deserialize` for [`Prime`].
+//! - `zeroize`: best-effort wiping of candidates, witnesses and results
+//!   marked secret; see [`secret`] for what it cannot reach.
+//! - `ffi`: `fbp_*` C ABI for a cdylib build.
+//! - `wasm`: wasm-bindgen exports using the browser's crypto RNG.
+//! - `python`: the `find_big_prime` PyO3 extension module.
+//! - `crypto-bigint`: [`ConstantTimeMillerRabin`](ct::ConstantTimeMillerRabin),
+//!   a constant-time modexp backend.
+//! - `gmp`: run Miller–Rabin exponentiations on GMP through `rug`.
+//! - `provable`: [`shawe_taylor`](provable::shawe_taylor), provable primes
+//!   from a seed per FIPS 186-4, Maurer's algorithm with `std`,
+//!   verifiable DSA domain parameters in [`dsa`], and nothing-up-my-sleeve
+//!   primes from a phrase in [`nums`] (the CLI enables it).
+//! - `tracing`: `tracing` events for candidates, sieve rejections and test
+//!   timings.
+//! - `minimal`: `std` without the OS RNG or the CLI stack; enable it with
+//!   `default-features = false` when you bring your own RNG.
+//!
+//! With no features at all only [`cert`], [`chain`], [`core`], [`ecpp`],
+//! [`factor`], [`forms`], [`modp`], [`primality`], [`residue`] and
+//! [`special`] are built; they need nothing beyond `alloc`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod aks;
+#[cfg(feature = "tokio")]
+pub mod asynchronous;
+#[cfg(feature = "std")]
+pub mod cancel;
+pub mod cert;
+pub mod chain;
+pub mod core;
+#[cfg(feature = "crypto-bigint")]
+pub mod ct;
+#[cfg(feature = "provable")]
+pub mod dsa;
+pub mod ecpp;
+#[cfg(feature = "std")]
+pub mod error;
+pub mod factor;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod format;
+pub mod forms;
+mod frobenius;
+#[cfg(feature = "std")]
+pub mod generate;
+#[cfg(feature = "std")]
+pub mod generator;
+#[cfg(feature = "gmp")]
+mod gmp;
+mod hilbert;
+mod lucas;
+pub mod modp;
+#[cfg(feature = "provable")]
+pub mod nums;
+pub mod primality;
+#[cfg(feature = "std")]
+pub mod prime;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "provable")]
+pub mod provable;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "std")]
+pub mod report;
+pub mod residue;
+#[cfg(feature = "std")]
+pub mod search;
+#[cfg(feature = "zeroize")]
+pub mod secret;
+pub mod special;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "tokio")]
+pub use asynchronous::{generate_probable_prime_async, generate_safe_prime_async};
+#[cfg(feature = "std")]
+pub use cancel::CancellationToken;
+pub use cert::{
+    PocklingtonCertificate, PrattCertificate, pocklington_certificate, pratt_certificate,
+};
+pub use chain::{ChainKind, bi_twin_members};
+#[cfg(feature = "crypto-bigint")]
+pub use ct::ConstantTimeMillerRabin;
+#[cfg(feature = "provable")]
+pub use dsa::{
+    DomainParameters, canonical_generator, generate_domain_parameters, is_approved_size,
+    is_subgroup_generator, primes_from_seed, validate_canonical_generator, validate_primes,
+};
+pub use ecpp::{EcppCertificate, EcppStep, ecpp_certificate};
+#[cfg(feature = "std")]
+pub use error::{PrimeGenError, Result};
+pub use factor::{
+    Factorization, ecm, factorize, factorize_with, pollard_p_minus_1, pollard_rho, trial_factor,
+};
+#[cfg(feature = "std")]
+pub use format::{Format, ParseNumberError, parse_number};
+pub use forms::{
+    fermat_index, lucas_lehmer, lucas_lehmer_riesel, mersenne_exponent, pepin, proth, proth_form,
+    riesel_form,
+};
+#[cfg(feature = "os-rng")]
+pub use generate::{generate_probable_prime, generate_safe_prime};
+#[cfg(feature = "std")]
+pub use generate::{
+    generate_probable_prime_with_rng, generate_probable_prime_with_test,
+    generate_safe_prime_with_rng, generate_safe_prime_with_test,
+};
+#[cfg(feature = "std")]
+pub use generator::{PrimeGenerator, PrimeGeneratorBuilder, Primes};
+pub use modp::{ModpPrime, modp_candidate, modp_prime, pi_scaled};
+#[cfg(feature = "provable")]
+pub use nums::{NumsPrime, nums_candidate, nums_prime};
+pub use primality::{
+    Aks, BailliePsw, Compositeness, CryptoRngCore, DEFAULT_FROBENIUS_ROUNDS, DEFAULT_MR_ROUNDS,
+    DETERMINISTIC_MR_BASES, FixedBases, Frobenius, MillerRabin, Primality, PrimalityTest,
+    SolovayStrassen, Stacked, StrongLucas, check_primality_with_rng, compositeness_witness_bases,
+    compositeness_witness_with_rng, factor_out_twos, is_prime_aks, is_prime_deterministic,
+    is_probable_prime_with_rng, is_strong_lucas_probable_prime, jacobi, random_range,
+    rounds_for_bits, small_prime_precheck,
+};
+#[cfg(feature = "std")]
+pub use primality::{ParallelMillerRabin, mr_error_log2, mr_rounds_for_error};
+#[cfg(feature = "os-rng")]
+pub use primality::{check_primality, compositeness_witness, is_probable_prime};
+#[cfg(feature = "std")]
+pub use prime::Prime;
+#[cfg(feature = "std")]
+pub use progress::{CandidateEvent, ProgressHook};
+#[cfg(all(feature = "provable", feature = "std"))]
+pub use provable::{MaurerPrime, PocklingtonStep, maurer};
+#[cfg(feature = "provable")]
+pub use provable::{ShaweTaylorPrime, shawe_taylor};
+#[cfg(feature = "std")]
+pub use report::GenerationReport;
+pub use residue::ResidueClass;
+#[cfg(feature = "std")]
+pub use search::SearchStats;
+#[cfg(feature = "zeroize")]
+pub use secret::SecretPrime;
+pub use special::{SignedPowers, proth_primes, proth_primes_between, pseudo_mersenne, solinas};
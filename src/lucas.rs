@@ -0,0 +1,153 @@
+//! Strong Lucas probable-prime test with Selfridge parameters, and the
+//! Baillie–PSW test built on it.
+//!
+//! Like [`crate::core`] this needs only `alloc`.
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use crate::core::{
+    Scratch, factor_out_twos, is_strong_probable_prime, jacobi, small_prime_precheck,
+};
+
+/// `value` reduced into [0, n).
+fn signed_mod(value: i64, n: &BigUint) -> BigUint {
+    let magnitude = BigUint::from(value.unsigned_abs()) % n;
+    if value >= 0 || magnitude.is_zero() {
+        magnitude
+    } else {
+        n - magnitude
+    }
+}
+
+/// `x / 2` modulo the odd `n`.
+fn half_mod(x: BigUint, n: &BigUint) -> BigUint {
+    if x.bit(0) { (x + n) >> 1 } else { x >> 1 }
+}
+
+/// Selfridge's method A: the first D in 5, -7, 9, -11, ... with Jacobi
+/// symbol (D/n) = -1, or `None` if some |D| shares a factor with `n`.
+///
+/// `n` must be odd and not a perfect square, otherwise no such D exists.
+fn selfridge(n: &BigUint) -> Option<i64> {
+    let mut d: i64 = 5;
+    loop {
+        match jacobi(&signed_mod(d, n), n) {
+            -1 => return Some(d),
+            0 if BigUint::from(d.unsigned_abs()) != *n => return None,
+            _ => {}
+        }
+        d = if d > 0 { -(d + 2) } else { -d + 2 };
+    }
+}
+
+/// Strong Lucas probable-prime test of an odd `n > 2` with P = 1 and
+/// Q = (1 - D) / 4, D chosen by Selfridge's method A.
+///
+/// With n + 1 = d * 2^s, `n` passes when U_d = 0 or V_(d*2^r) = 0 for some
+/// 0 <= r < s, all modulo n. Perfect squares and even `n` are rejected.
+pub fn is_strong_lucas_probable_prime(n: &BigUint) -> bool {
+    if n.is_even() || *n < BigUint::from(3u32) {
+        return *n == BigUint::from(2u32);
+    }
+    let root = n.sqrt();
+    if &root * &root == *n {
+        return false;
+    }
+    let Some(d) = selfridge(n) else {
+        return false;
+    };
+    let big_d = signed_mod(d, n);
+    let q = signed_mod((1 - d) / 4, n);
+    let (s, k) = factor_out_twos(&(n + BigUint::one()));
+
+    // Walk the bits of k from the top with U_1 = 1, V_1 = P = 1, Q^1 = Q.
+    let mut u = Scratch(BigUint::one());
+    let mut v = Scratch(BigUint::one());
+    let mut qk = Scratch(q.clone());
+    for bit in (0..k.bits() - 1).rev() {
+        // U_2m = U_m V_m, V_2m = V_m^2 - 2 Q^m.
+        u = Scratch(&u.0 * &v.0 % n);
+        v = Scratch((&v.0 * &v.0 + n * 2u32 - (&qk.0 << 1usize)) % n);
+        qk = Scratch(&qk.0 * &qk.0 % n);
+        if k.bit(bit) {
+            // U_(m+1) = (P U_m + V_m) / 2, V_(m+1) = (D U_m + P V_m) / 2.
+            let next_u = half_mod(&u.0 + &v.0, n);
+            let next_v = half_mod((&big_d * &u.0 + &v.0) % n, n);
+            u = Scratch(next_u % n);
+            v = Scratch(next_v % n);
+            qk = Scratch(&qk.0 * &q % n);
+        }
+    }
+
+    if u.0.is_zero() || v.0.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = Scratch((&v.0 * &v.0 + n * 2u32 - (&qk.0 << 1usize)) % n);
+        if v.0.is_zero() {
+            return true;
+        }
+        qk = Scratch(&qk.0 * &qk.0 % n);
+    }
+    false
+}
+
+/// Baillie–PSW: trial division, a strong probable-prime test to base 2, then
+/// a strong Lucas test. No composite is known to pass.
+pub(crate) fn baillie_psw(n: &BigUint) -> bool {
+    let two = BigUint::from(2u32);
+    if *n < two {
+        return false;
+    }
+    if *n == two {
+        return true;
+    }
+    if n.is_even() || !small_prime_precheck(n) {
+        return false;
+    }
+    if *n < BigUint::from(61u32 * 61) {
+        return true;
+    }
+    is_strong_probable_prime(n, &two) && is_strong_lucas_probable_prime(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strong_lucas_pseudoprimes_fail_baillie_psw() {
+        for n in [5459u32, 5777, 10877, 16109, 18971] {
+            let n = BigUint::from(n);
+            assert!(is_strong_lucas_probable_prime(&n), "{n}");
+            assert!(!baillie_psw(&n), "{n}");
+        }
+    }
+
+    #[test]
+    fn carmichael_numbers_and_base_2_pseudoprimes_are_composite() {
+        let carmichael = [
+            561u32, 1105, 1729, 2465, 2821, 6601, 8911, 41041, 62745, 75361,
+        ];
+        let strong_base_2 = [2047, 3277, 4033, 4681, 8321];
+        for n in carmichael.into_iter().chain(strong_base_2) {
+            assert!(!baillie_psw(&BigUint::from(n)), "{n}");
+        }
+    }
+
+    #[test]
+    fn agrees_with_trial_division() {
+        for n in 0..20_000u32 {
+            let prime = n >= 2
+                && (2..)
+                    .take_while(|d| d * d <= n)
+                    .all(|d| !n.is_multiple_of(d));
+            assert_eq!(baillie_psw(&BigUint::from(n)), prime, "{n}");
+            if prime && n > 2 {
+                assert!(is_strong_lucas_probable_prime(&BigUint::from(n)), "{n}");
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! MODP primes in the style of RFC 2412 and RFC 3526, whose middle bits
+//! come from the binary expansion of π.
+//!
+//! With n bits and k fixed bits at either end,
+//!
+//! p = 2^n − 2^(n−k) − 1 + 2^k · (⌊2^(n−2k−2) · π⌋ + X),
+//!
+//! where X is the smallest offset making p a safe prime. The top and bottom
+//! k bits are all ones, which speeds up reduction, and π leaves no room to
+//! hide a weakness in the rest. RFC 3526 uses k = 64; its 2048-bit group
+//! has X = 124476. Like [`crate::core`] this needs only `alloc`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::factor::primes_up_to;
+use crate::lucas::baillie_psw;
+
+/// Bits fixed to one at each end of the RFC 2409 and RFC 3526 primes.
+pub const RFC_FIXED_BITS: u64 = 64;
+
+/// Offsets are sieved by the primes up to this before the full tests.
+const SIEVE_BOUND: u64 = 1 << 16;
+
+/// Offsets sieved at a time.
+const WINDOW: u64 = 1 << 16;
+
+/// Extra bits carried while summing the series for π.
+const GUARD_BITS: u64 = 64;
+
+/// A prime from [`modp_prime`] with the offset that gives it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModpPrime {
+    /// The safe prime p.
+    pub prime: BigUint,
+    /// X in the formula.
+    pub offset: u64,
+}
+
+/// ⌊2^`shift` · π⌋, from Machin's formula π = 16 arctan(1/5) − 4 arctan(1/239).
+pub fn pi_scaled(shift: u64) -> BigUint {
+    let scale = BigUint::one() << (shift + GUARD_BITS);
+    let pi = arctan_inverse(5, &scale) * 16u32 - arctan_inverse(239, &scale) * 4u32;
+    pi >> GUARD_BITS
+}
+
+/// arctan(1/`x`) · `scale`, truncating each term of its series.
+fn arctan_inverse(x: u32, scale: &BigUint) -> BigUint {
+    let square = x * x;
+    let mut power = scale / x;
+    let mut sum = power.clone();
+    for k in 1u32.. {
+        power /= square;
+        if power.is_zero() {
+            break;
+        }
+        let term = &power / (2 * k + 1);
+        // The terms shrink, so the alternating partial sums stay positive.
+        if k % 2 == 1 {
+            sum -= term;
+        } else {
+            sum += term;
+        }
+    }
+    sum
+}
+
+/// The candidate for offset `offset`: p from the formula with `bits` bits
+/// and `fixed_bits` ones at each end.
+///
+/// `None` unless 1 ≤ `fixed_bits` and 2·`fixed_bits` + 2 < `bits`.
+pub fn modp_candidate(bits: u64, fixed_bits: u64, offset: u64) -> Option<BigUint> {
+    Some(modp_base(bits, fixed_bits)? + (BigUint::from(offset) << fixed_bits))
+}
+
+/// p for offset 0.
+fn modp_base(bits: u64, fixed_bits: u64) -> Option<BigUint> {
+    if fixed_bits == 0 || 2 * fixed_bits + 2 >= bits {
+        return None;
+    }
+    let pi = pi_scaled(bits - 2 * fixed_bits - 2);
+    Some(
+        (BigUint::one() << bits) - (BigUint::one() << (bits - fixed_bits)) - 1u32
+            + (pi << fixed_bits),
+    )
+}
+
+/// The first safe prime p of the formula at an offset of `from` or later,
+/// settled by Baillie–PSW on (p − 1)/2 and on p.
+///
+/// Start `from` 0 for the group's own prime. `None` for sizes
+/// [`modp_candidate`] refuses, or if the offsets run out, which at any
+/// sensible size they do not.
+pub fn modp_prime(bits: u64, fixed_bits: u64, from: u64) -> Option<ModpPrime> {
+    let first = modp_candidate(bits, fixed_bits, from)?;
+    let step = BigUint::one() << fixed_bits;
+    // For each sieving prime r, the index i past `from` at which r divides
+    // p = first + i · 2^k, and the one at which it divides q = (p − 1)/2.
+    // Tiny sizes, where q could be one of the primes, skip the sieve.
+    let sieve = if bits > 40 {
+        primes_up_to(SIEVE_BOUND)
+    } else {
+        Vec::new()
+    };
+    let roots: Vec<(u64, u64, u64)> = sieve
+        .into_iter()
+        .skip(1)
+        .map(|r| {
+            let residue = |n: &BigUint| (n % r).to_u64().expect("below r");
+            let inverse = pow_mod(residue(&step), r - 2, r);
+            let p_root = (r - residue(&first)) * inverse % r;
+            let q_root = (r - residue(&(&first >> 1u32))) * inverse % r * 2 % r;
+            (r, p_root, q_root)
+        })
+        .collect();
+    let mut start = 0u64;
+    loop {
+        let mut alive = vec![true; WINDOW as usize];
+        for &(r, p_root, q_root) in &roots {
+            for root in [p_root, q_root] {
+                let mut i = (root + r - start % r) % r;
+                while i < WINDOW {
+                    alive[i as usize] = false;
+                    i += r;
+                }
+            }
+        }
+        for i in (0..WINDOW).filter(|&i| alive[i as usize]) {
+            let offset = from.checked_add(start + i)?;
+            let p = &first + BigUint::from(start + i) * &step;
+            if baillie_psw(&(&p >> 1u32)) && baillie_psw(&p) {
+                return Some(ModpPrime { prime: p, offset });
+            }
+        }
+        start = start.checked_add(WINDOW)?;
+    }
+}
+
+/// `base`^`exponent` modulo a `modulus` below 2^32.
+fn pow_mod(base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let (mut base, mut result) = (base % modulus, 1 % modulus);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    result
+}
@@ -0,0 +1,79 @@
+//! Nothing-up-my-sleeve primes from a public phrase, enabled with the
+//! `provable` feature.
+//!
+//! Candidate i for a phrase and size is SHA-256(phrase ‖ i ‖ 0) ‖
+//! SHA-256(phrase ‖ i ‖ 1) ‖ …, with i an 8-byte and the block index a
+//! 4-byte big-endian integer, cut to its leading bits, with the top bit and
+//! the low bit set (and bit 1 for a safe prime). The prime is the first
+//! candidate that passes Baillie–PSW, which is deterministic, so anyone
+//! holding the phrase can redo the derivation and see that nobody picked
+//! the prime. Like [`crate::core`] this needs only `alloc`.
+
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+use crate::core::small_prime_precheck;
+use crate::lucas::baillie_psw;
+
+/// Bits of output of the hash the candidates are built from, SHA-256.
+const OUTLEN: u64 = 256;
+
+/// A prime from [`nums_prime`] with the counter it turned up at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NumsPrime {
+    /// The prime.
+    pub prime: BigUint,
+    /// The index of the candidate that was prime.
+    pub counter: u64,
+}
+
+/// Candidate `counter` for `phrase`: `bits` bits of SHA-256 output with the
+/// top and low bits set, and bit 1 as well when `safe`, so that
+/// (candidate − 1)/2 is odd.
+pub fn nums_candidate(phrase: &[u8], bits: u64, counter: u64, safe: bool) -> BigUint {
+    let blocks = bits.div_ceil(OUTLEN);
+    let mut bytes = Vec::with_capacity((blocks * OUTLEN / 8) as usize);
+    for block in 0..blocks {
+        let mut hasher = Sha256::new();
+        hasher.update(phrase);
+        hasher.update(counter.to_be_bytes());
+        hasher.update((block as u32).to_be_bytes());
+        bytes.extend_from_slice(&hasher.finalize());
+    }
+    let mut candidate = BigUint::from_bytes_be(&bytes) >> (blocks * OUTLEN - bits);
+    candidate.set_bit(bits - 1, true);
+    candidate.set_bit(0, true);
+    if safe {
+        candidate.set_bit(1, true);
+    }
+    candidate
+}
+
+/// The first `bits`-bit prime derived from `phrase` at a counter of `from`
+/// or later; with `safe`, the first whose (p − 1)/2 is prime too.
+///
+/// Start `from` 0 for the phrase's own prime, and one past a previous
+/// counter for the next. `None` below 2 bits, or 3 for a safe prime.
+pub fn nums_prime(phrase: &[u8], bits: u64, safe: bool, from: u64) -> Option<NumsPrime> {
+    if bits < if safe { 3 } else { 2 } {
+        return None;
+    }
+    (from..=u64::MAX).find_map(|counter| {
+        let candidate = nums_candidate(phrase, bits, counter, safe);
+        let prime = if safe {
+            let q = &candidate >> 1u32;
+            // Sieve both halves before running either full test.
+            small_prime_precheck(&candidate)
+                && small_prime_precheck(&q)
+                && baillie_psw(&q)
+                && baillie_psw(&candidate)
+        } else {
+            baillie_psw(&candidate)
+        };
+        prime.then_some(NumsPrime {
+            prime: candidate,
+            counter,
+        })
+    })
+}
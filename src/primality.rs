@@ -0,0 +1,627 @@
+//! Primality tests used by the generation loop.
+
+use ::core::fmt::Debug;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+#[cfg(feature = "std")]
+use num_traits::One;
+#[cfg(feature = "os-rng")]
+use rand::rngs::OsRng;
+
+pub use crate::aks::is_prime_aks;
+pub use crate::core::{
+    Compositeness, CryptoRngCore, DETERMINISTIC_MR_BASES, compositeness_witness_bases,
+    compositeness_witness_with_rng, factor_out_twos, is_prime_deterministic,
+    is_probable_prime_with_rng, jacobi, random_range, small_prime_precheck,
+};
+#[cfg(feature = "std")]
+use crate::core::{Scratch, is_witness, random_witness, trial_division};
+use crate::core::{fixed_base_witness, miller_rabin, solovay_strassen};
+use crate::forms::{
+    fermat_index, lucas_lehmer, lucas_lehmer_riesel, mersenne_exponent, pepin, proth, proth_form,
+    riesel_form,
+};
+use crate::frobenius::frobenius;
+use crate::lucas::baillie_psw;
+pub use crate::lucas::is_strong_lucas_probable_prime;
+
+/// Default Miller–Rabin rounds. Increase for extra certainty.
+pub const DEFAULT_MR_ROUNDS: usize = 64;
+
+/// Miller–Rabin rounds that bound the error below 2^-128 for a random odd
+/// `bits`-bit candidate.
+///
+/// Each row is the most [`mr_rounds_for_error`] asks for at 2^-128 across
+/// its range of sizes, from the Damgård–Landrock–Pomerance average-case
+/// bounds; they are far below [`DEFAULT_MR_ROUNDS`] at common key sizes.
+/// They assume the candidate was drawn at random, so test numbers from
+/// elsewhere with the full default instead.
+pub fn rounds_for_bits(bits: usize) -> usize {
+    match bits {
+        2840.. => 2,
+        1889.. => 3,
+        1420.. => 4,
+        1142.. => 5,
+        958.. => 6,
+        827.. => 7,
+        730.. => 8,
+        655.. => 9,
+        595.. => 10,
+        546.. => 11,
+        505.. => 12,
+        471.. => 13,
+        442.. => 14,
+        417.. => 15,
+        395.. => 16,
+        376.. => 17,
+        359.. => 18,
+        344.. => 19,
+        330.. => 20,
+        318.. => 21,
+        307.. => 22,
+        297.. => 23,
+        287.. => 24,
+        279.. => 25,
+        271.. => 26,
+        264.. => 27,
+        // The bounds change form around 256 bits and need a few more here.
+        241.. => 29,
+        180.. => 32,
+        127.. => 44,
+        83.. => 54,
+        _ => 64,
+    }
+}
+
+/// Fewest Miller–Rabin rounds that bound the chance a random odd `bits`-bit
+/// candidate passing them is composite below 2^-`error_bits`.
+///
+/// Uses the Damgård–Landrock–Pomerance estimates (HAC fact 4.48), evaluated
+/// in floating point; below 21 bits, where they do not apply, it falls back
+/// to the worst-case 4^-t bound.
+#[cfg(feature = "std")]
+pub fn mr_rounds_for_error(bits: usize, error_bits: u32) -> usize {
+    let worst_case = error_bits.div_ceil(2).max(1) as usize;
+    (1..worst_case)
+        .find(|&t| dlp_log2_bound(bits as f64, t as f64) <= -f64::from(error_bits))
+        .unwrap_or(worst_case)
+}
+
+/// log2 of the bound on the chance that a random odd `bits`-bit candidate
+/// passing `rounds` Miller–Rabin rounds is composite.
+///
+/// The best of the Damgård–Landrock–Pomerance estimates (HAC fact 4.48)
+/// and the worst-case 4^-t; for numbers not drawn at random only the
+/// latter, `-2 * rounds`, holds.
+#[cfg(feature = "std")]
+pub fn mr_error_log2(bits: usize, rounds: usize) -> f64 {
+    let worst_case = -2.0 * rounds as f64;
+    worst_case.min(dlp_log2_bound(bits as f64, rounds as f64))
+}
+
+/// log2 of the best Damgård–Landrock–Pomerance bound on p_(k,t) that applies.
+#[cfg(feature = "std")]
+fn dlp_log2_bound(k: f64, t: f64) -> f64 {
+    let mut best = f64::INFINITY;
+    if t == 1.0 && k >= 2.0 {
+        best = best.min(2.0 * k.log2() + 2.0 * (2.0 - k.sqrt()));
+    }
+    if k < 21.0 {
+        return best;
+    }
+    if (t == 2.0 && k >= 88.0) || (3.0 <= t && t <= k / 9.0) {
+        let bound = 1.5 * k.log2() + t - 0.5 * t.log2() + 2.0 * (2.0 - (t * k).sqrt());
+        best = best.min(bound);
+    }
+    if k / 9.0 <= t && t <= k / 4.0 {
+        let bound = 0.35 * k * (-5.0 * t).exp2()
+            + k.powf(3.75) / 7.0 * (-k / 2.0 - 2.0 * t).exp2()
+            + 12.0 * k * (-k / 4.0 - 3.0 * t).exp2();
+        best = best.min(bound.log2());
+    }
+    if t >= k / 4.0 {
+        best = best.min((1.0f64 / 7.0).log2() + 3.75 * k.log2() - k / 2.0 - 2.0 * t);
+    }
+    best
+}
+
+/// Default Frobenius rounds: ten rounds bound the error below 2^-129.
+pub const DEFAULT_FROBENIUS_ROUNDS: usize = 10;
+
+/// A (probabilistic or deterministic) primality test.
+///
+/// Implement this to plug a different test into the generation loop; the
+/// loop only calls it on odd candidates that survived trial division.
+pub trait PrimalityTest: Debug + Send + Sync {
+    /// Return `true` if `n` is (probably) prime.
+    fn is_probable_prime(&self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool;
+
+    /// Like [`Self::is_probable_prime`], calling `on_round` with the 1-based
+    /// index of each round before it runs.
+    ///
+    /// The default implementation reports nothing; multi-round tests should
+    /// override it so progress hooks can see individual rounds.
+    fn is_probable_prime_observed(
+        &self,
+        n: &BigUint,
+        rng: &mut dyn CryptoRngCore,
+        on_round: &mut dyn FnMut(usize),
+    ) -> bool {
+        let _ = on_round;
+        self.is_probable_prime(n, rng)
+    }
+}
+
+/// Miller–Rabin with a fixed number of random witnesses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MillerRabin {
+    rounds: usize,
+}
+
+impl MillerRabin {
+    /// Create a Miller–Rabin test running `rounds` witnesses.
+    pub fn new(rounds: usize) -> Self {
+        Self { rounds }
+    }
+
+    /// Number of witnesses tried per candidate.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+}
+
+impl Default for MillerRabin {
+    fn default() -> Self {
+        Self::new(DEFAULT_MR_ROUNDS)
+    }
+}
+
+impl PrimalityTest for MillerRabin {
+    fn is_probable_prime(&self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool {
+        is_probable_prime_with_rng(n, self.rounds, rng)
+    }
+
+    fn is_probable_prime_observed(
+        &self,
+        n: &BigUint,
+        rng: &mut dyn CryptoRngCore,
+        on_round: &mut dyn FnMut(usize),
+    ) -> bool {
+        miller_rabin(n, self.rounds, rng, on_round)
+    }
+}
+
+/// Miller–Rabin with its rounds split across worker threads.
+///
+/// The witnesses are all drawn up front on the calling thread, and the
+/// remaining rounds are abandoned as soon as one of them proves the number
+/// composite. Spawning the workers costs more than a round on small numbers,
+/// so this only pays off from several thousand bits.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParallelMillerRabin {
+    rounds: usize,
+    threads: usize,
+}
+
+#[cfg(feature = "std")]
+impl ParallelMillerRabin {
+    /// Create a Miller–Rabin test running `rounds` witnesses on up to `threads` threads.
+    pub fn new(rounds: usize, threads: usize) -> Self {
+        Self {
+            rounds,
+            threads: threads.max(1),
+        }
+    }
+
+    /// Number of witnesses tried per candidate.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// Maximum number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Evidence that `n` is composite from trial division or a witness, or
+    /// `None` for a probable prime.
+    pub fn compositeness_witness(
+        &self,
+        n: &BigUint,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Option<Compositeness> {
+        if let Some(verdict) = trial_division(n) {
+            return verdict;
+        }
+        self.witness(n, rng, &mut |_| {})
+            .map(Compositeness::Witness)
+    }
+
+    /// Run the rounds on an odd `n > 2`, returning a witness if one turns up.
+    fn witness(
+        &self,
+        n: &BigUint,
+        rng: &mut dyn CryptoRngCore,
+        on_round: &mut dyn FnMut(usize),
+    ) -> Option<BigUint> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{OnceLock, mpsc};
+
+        let n_minus_one = Scratch(n - BigUint::one());
+        let (s, d) = factor_out_twos(&n_minus_one.0);
+        let d = Scratch(d);
+        let witnesses: Vec<Scratch> = (0..self.rounds)
+            .map(|_| Scratch(random_witness(&n_minus_one.0, rng)))
+            .collect();
+        let next = AtomicUsize::new(0);
+        let found = OnceLock::new();
+        let (started, rounds) = mpsc::channel();
+        std::thread::scope(|scope| {
+            for _ in 0..self.threads.min(self.rounds) {
+                let (started, next, found) = (started.clone(), &next, &found);
+                let (witnesses, n_minus_one, d) = (&witnesses, &n_minus_one, &d);
+                scope.spawn(move || {
+                    while found.get().is_none() {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(a) = witnesses.get(index) else {
+                            break;
+                        };
+                        let _ = started.send(index + 1);
+                        if is_witness(&a.0, n, &n_minus_one.0, s, &d.0) {
+                            let _ = found.set(index);
+                        }
+                    }
+                });
+            }
+            drop(started);
+            rounds.into_iter().for_each(&mut *on_round);
+        });
+        found.into_inner().map(|index| witnesses[index].0.clone())
+    }
+}
+
+#[cfg(feature = "std")]
+impl PrimalityTest for ParallelMillerRabin {
+    fn is_probable_prime(&self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool {
+        self.is_probable_prime_observed(n, rng, &mut |_| {})
+    }
+
+    fn is_probable_prime_observed(
+        &self,
+        n: &BigUint,
+        rng: &mut dyn CryptoRngCore,
+        on_round: &mut dyn FnMut(usize),
+    ) -> bool {
+        let two = BigUint::from(2u32);
+        if *n <= two {
+            return *n == two;
+        }
+        n.bit(0) && self.witness(n, rng, on_round).is_none()
+    }
+}
+
+/// Miller–Rabin to a fixed list of bases instead of random witnesses.
+///
+/// Every run tests the same bases, so an audit can reproduce exactly what
+/// was checked. A fixed list is only a proof below known bounds (see
+/// [`is_prime_deterministic`]); above them an adversary can construct
+/// composites that pass it, so prefer random witnesses for untrusted input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedBases {
+    bases: Vec<BigUint>,
+}
+
+impl FixedBases {
+    /// Test to `bases`, in order.
+    pub fn new(bases: Vec<BigUint>) -> Self {
+        Self { bases }
+    }
+
+    /// Test to the first `count` primes 2, 3, 5, ...
+    pub fn first_primes(count: usize) -> Self {
+        let mut primes: Vec<u64> = Vec::with_capacity(count);
+        let mut candidate = 2u64;
+        while primes.len() < count {
+            if primes
+                .iter()
+                .take_while(|&&p| p * p <= candidate)
+                .all(|&p| !candidate.is_multiple_of(p))
+            {
+                primes.push(candidate);
+            }
+            candidate += 1;
+        }
+        Self::new(primes.into_iter().map(BigUint::from).collect())
+    }
+
+    /// The bases tested, in order.
+    pub fn bases(&self) -> &[BigUint] {
+        &self.bases
+    }
+
+    /// Evidence that `n` is composite from trial division or one of the
+    /// bases, or `None` if it passes them all.
+    pub fn compositeness_witness(&self, n: &BigUint) -> Option<Compositeness> {
+        compositeness_witness_bases(n, &self.bases)
+    }
+}
+
+impl PrimalityTest for FixedBases {
+    fn is_probable_prime(&self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool {
+        self.is_probable_prime_observed(n, rng, &mut |_| {})
+    }
+
+    fn is_probable_prime_observed(
+        &self,
+        n: &BigUint,
+        _rng: &mut dyn CryptoRngCore,
+        on_round: &mut dyn FnMut(usize),
+    ) -> bool {
+        let two = BigUint::from(2u32);
+        if *n <= two {
+            return *n == two;
+        }
+        n.bit(0) && fixed_base_witness(n, &self.bases, on_round).is_none()
+    }
+}
+
+/// Solovay–Strassen with a fixed number of random bases.
+///
+/// Each round catches a composite with probability at least 1/2, against
+/// 3/4 for Miller–Rabin, so it needs about twice the rounds for the same
+/// bound. Mostly useful for cross-checking or stacked after another test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolovayStrassen {
+    rounds: usize,
+}
+
+impl SolovayStrassen {
+    /// Create a Solovay–Strassen test running `rounds` bases.
+    pub fn new(rounds: usize) -> Self {
+        Self { rounds }
+    }
+
+    /// Number of bases tried per candidate.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+}
+
+impl Default for SolovayStrassen {
+    fn default() -> Self {
+        Self::new(DEFAULT_MR_ROUNDS)
+    }
+}
+
+impl PrimalityTest for SolovayStrassen {
+    fn is_probable_prime(&self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool {
+        solovay_strassen(n, self.rounds, rng, &mut |_| {})
+    }
+
+    fn is_probable_prime_observed(
+        &self,
+        n: &BigUint,
+        rng: &mut dyn CryptoRngCore,
+        on_round: &mut dyn FnMut(usize),
+    ) -> bool {
+        solovay_strassen(n, self.rounds, rng, on_round)
+    }
+}
+
+/// Grantham's random quadratic Frobenius test with a fixed number of rounds.
+///
+/// A round costs a few Miller–Rabin rounds but lets a composite
+/// through with probability below 1/7710 rather than 1/4, so far fewer
+/// rounds reach a given bound at large sizes. The bound needs trial
+/// division by the primes up to 50000 first, which the test does itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Frobenius {
+    rounds: usize,
+}
+
+impl Frobenius {
+    /// Create a Frobenius test running `rounds` rounds.
+    pub fn new(rounds: usize) -> Self {
+        Self { rounds }
+    }
+
+    /// Number of rounds run per candidate.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+}
+
+impl Default for Frobenius {
+    fn default() -> Self {
+        Self::new(DEFAULT_FROBENIUS_ROUNDS)
+    }
+}
+
+impl PrimalityTest for Frobenius {
+    fn is_probable_prime(&self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool {
+        frobenius(n, self.rounds, rng, &mut |_| {})
+    }
+
+    fn is_probable_prime_observed(
+        &self,
+        n: &BigUint,
+        rng: &mut dyn CryptoRngCore,
+        on_round: &mut dyn FnMut(usize),
+    ) -> bool {
+        frobenius(n, self.rounds, rng, on_round)
+    }
+}
+
+/// Several tests run in order; a number must pass all of them.
+#[derive(Debug, Default)]
+pub struct Stacked {
+    tests: Vec<Box<dyn PrimalityTest>>,
+}
+
+impl Stacked {
+    /// An empty stack, which accepts every number until tests are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `test` after the tests already added.
+    pub fn then<T: PrimalityTest + 'static>(mut self, test: T) -> Self {
+        self.tests.push(Box::new(test));
+        self
+    }
+}
+
+impl PrimalityTest for Stacked {
+    fn is_probable_prime(&self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool {
+        self.tests.iter().all(|test| test.is_probable_prime(n, rng))
+    }
+
+    fn is_probable_prime_observed(
+        &self,
+        n: &BigUint,
+        rng: &mut dyn CryptoRngCore,
+        on_round: &mut dyn FnMut(usize),
+    ) -> bool {
+        self.tests
+            .iter()
+            .all(|test| test.is_probable_prime_observed(n, rng, on_round))
+    }
+}
+
+/// Baillie–PSW: a strong probable-prime test to base 2 followed by a strong
+/// Lucas test.
+///
+/// It is deterministic, ignores the RNG, and has no known pseudoprimes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BailliePsw;
+
+impl PrimalityTest for BailliePsw {
+    fn is_probable_prime(&self, n: &BigUint, _rng: &mut dyn CryptoRngCore) -> bool {
+        baillie_psw(n)
+    }
+}
+
+/// The Agrawal–Kayal–Saxena test, which proves primality.
+///
+/// It is deterministic and ignores the RNG, but takes minutes on numbers
+/// of a few dozen bits and is hopeless at key sizes. Use it to teach or to
+/// cross-check other tests on small inputs, not to generate primes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Aks;
+
+impl PrimalityTest for Aks {
+    fn is_probable_prime(&self, n: &BigUint, _rng: &mut dyn CryptoRngCore) -> bool {
+        is_prime_aks(n)
+    }
+}
+
+/// Strong Lucas probable-prime test with Selfridge parameters.
+///
+/// Its pseudoprimes are largely disjoint from Miller–Rabin's, so it is
+/// worth running after MR on numbers an adversary may have chosen. It is
+/// deterministic and ignores the RNG.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StrongLucas;
+
+impl PrimalityTest for StrongLucas {
+    fn is_probable_prime(&self, n: &BigUint, _rng: &mut dyn CryptoRngCore) -> bool {
+        is_strong_lucas_probable_prime(n)
+    }
+}
+
+/// A verdict that tells a proven prime apart from a probable one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Primality {
+    /// Certainly composite.
+    Composite,
+    /// Passed a probabilistic test, so composite only with small probability.
+    ProbablyPrime,
+    /// Proven prime by a deterministic test.
+    Prime,
+}
+
+impl Primality {
+    /// Whether this is [`Primality::Prime`] or [`Primality::ProbablyPrime`].
+    pub fn is_probable_prime(self) -> bool {
+        self != Primality::Composite
+    }
+
+    fn proven(prime: bool) -> Self {
+        if prime {
+            Primality::Prime
+        } else {
+            Primality::Composite
+        }
+    }
+}
+
+/// Like [`is_probable_prime_with_rng`], but proves the verdict when `n` is
+/// small enough for deterministic Miller–Rabin or has a special form:
+/// Lucas–Lehmer for 2^p − 1, LLR for k · 2^n − 1, Pépin for Fermat numbers
+/// and Proth's theorem for k · 2^n + 1.
+pub fn check_primality_with_rng<R: CryptoRngCore + ?Sized>(
+    n: &BigUint,
+    rounds: usize,
+    rng: &mut R,
+) -> Primality {
+    let proof = is_prime_deterministic(n)
+        .or_else(|| mersenne_exponent(n).map(lucas_lehmer))
+        .or_else(|| riesel_form(n).map(|(k, e)| lucas_lehmer_riesel(&k, e)))
+        .or_else(|| fermat_index(n).map(pepin))
+        .or_else(|| proth_form(n).map(|(k, e)| proth(&k, e)));
+    match proof {
+        Some(prime) => Primality::proven(prime),
+        None if is_probable_prime_with_rng(n, rounds, rng) => Primality::ProbablyPrime,
+        None => Primality::Composite,
+    }
+}
+
+/// [`check_primality_with_rng`] with witnesses from the OS RNG.
+#[cfg(feature = "os-rng")]
+pub fn check_primality(n: &BigUint, rounds: usize) -> Primality {
+    check_primality_with_rng(n, rounds, &mut OsRng)
+}
+
+/// Miller–Rabin probabilistic primality test.
+#[cfg(feature = "os-rng")]
+pub fn is_probable_prime(n: &BigUint, rounds: usize) -> bool {
+    is_probable_prime_with_rng(n, rounds, &mut OsRng)
+}
+
+/// Find evidence that `n` is composite, or `None` for a probable prime.
+#[cfg(feature = "os-rng")]
+pub fn compositeness_witness(n: &BigUint, rounds: usize) -> Option<Compositeness> {
+    compositeness_witness_with_rng(n, rounds, &mut OsRng)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_rounds_reach_2_to_the_minus_128() {
+        // Every row of the table starts at one of these sizes and ends just
+        // before the next.
+        let breakpoints = [
+            2, 83, 127, 180, 241, 264, 271, 279, 287, 297, 307, 318, 330, 344, 359, 376, 395, 417,
+            442, 471, 505, 546, 595, 655, 730, 827, 958, 1142, 1420, 1889, 2840, 8192,
+        ];
+        for pair in breakpoints.windows(2) {
+            for bits in [pair[0], pair[0] + 1, pair[1] - 1] {
+                let rounds = rounds_for_bits(bits);
+                assert!(
+                    mr_error_log2(bits, rounds) <= -128.0,
+                    "{rounds} rounds at {bits} bits"
+                );
+            }
+        }
+        for bits in 2..8192 {
+            assert!(
+                mr_error_log2(bits, rounds_for_bits(bits)) <= -128.0,
+                "{bits} bits"
+            );
+        }
+    }
+}
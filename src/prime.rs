@@ -0,0 +1,177 @@
+//! A newtype for primes handed to downstream tooling.
+
+use num_bigint::BigUint;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use crate::format::{Format, Formatted, ParseNumberError, parse_number};
+
+/// A prime produced or validated by this crate.
+///
+/// With the `serde` feature it serializes as a lowercase hex string; use
+/// the [`decimal`], [`hex`] or [`base64`] modules with `#[serde(with = ...)]`
+/// to pick another encoding for a field.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Prime(pub BigUint);
+
+impl Prime {
+    /// Consume the wrapper, returning the inner integer.
+    pub fn into_inner(self) -> BigUint {
+        self.0
+    }
+
+    /// Render in `format`; plain `Display` is decimal.
+    pub fn display(&self, format: Format) -> Formatted<'_> {
+        format.display(&self.0)
+    }
+}
+
+impl fmt::Display for Prime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Parses decimal, `0x`-prefixed hex, or base64 (see [`Format::detect`]).
+///
+/// Parsing does not check primality.
+impl FromStr for Prime {
+    type Err = ParseNumberError;
+
+    fn from_str(s: &str) -> Result<Self, ParseNumberError> {
+        parse_number(s).map(Prime)
+    }
+}
+
+impl From<BigUint> for Prime {
+    fn from(n: BigUint) -> Self {
+        Self(n)
+    }
+}
+
+impl From<Prime> for BigUint {
+    fn from(p: Prime) -> Self {
+        p.0
+    }
+}
+
+impl Deref for Prime {
+    type Target = BigUint;
+
+    fn deref(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+impl AsRef<BigUint> for Prime {
+    fn as_ref(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use num_bigint::BigUint;
+    use serde::de::{self, Deserializer, Visitor};
+    use serde::{Deserialize, Serialize, Serializer};
+    use std::fmt;
+
+    use super::Prime;
+
+    impl Serialize for Prime {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::hex::serialize(self, serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Prime {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::hex::deserialize(deserializer)
+        }
+    }
+
+    struct PrimeVisitor {
+        expecting: &'static str,
+        parse: fn(&str) -> Option<BigUint>,
+    }
+
+    impl Visitor<'_> for PrimeVisitor {
+        type Value = Prime;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.expecting)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Prime, E> {
+            (self.parse)(v)
+                .map(Prime)
+                .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+        }
+    }
+
+    pub(super) fn deserialize_str<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        expecting: &'static str,
+        parse: fn(&str) -> Option<BigUint>,
+    ) -> Result<Prime, D::Error> {
+        deserializer.deserialize_str(PrimeVisitor { expecting, parse })
+    }
+}
+
+/// Serialize a [`Prime`] as a lowercase hex string (optionally `0x`-prefixed on input).
+#[cfg(feature = "serde")]
+pub mod hex {
+    use serde::{Deserializer, Serializer};
+
+    use super::Prime;
+    use crate::format::Format;
+
+    pub fn serialize<S: Serializer>(p: &Prime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&p.display(Format::Hex))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Prime, D::Error> {
+        super::serde_impl::deserialize_str(deserializer, "a hex string", |s| {
+            Format::Hex.parse(s).ok()
+        })
+    }
+}
+
+/// Serialize a [`Prime`] as a decimal string.
+#[cfg(feature = "serde")]
+pub mod decimal {
+    use serde::{Deserializer, Serializer};
+
+    use super::Prime;
+    use crate::format::Format;
+
+    pub fn serialize<S: Serializer>(p: &Prime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(p)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Prime, D::Error> {
+        super::serde_impl::deserialize_str(deserializer, "a decimal string", |s| {
+            Format::Decimal.parse(s).ok()
+        })
+    }
+}
+
+/// Serialize a [`Prime`] as standard base64 of its big-endian bytes.
+#[cfg(feature = "serde")]
+pub mod base64 {
+    use serde::{Deserializer, Serializer};
+
+    use super::Prime;
+    use crate::format::Format;
+
+    pub fn serialize<S: Serializer>(p: &Prime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&p.display(Format::Base64))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Prime, D::Error> {
+        super::serde_impl::deserialize_str(deserializer, "a base64 string", |s| {
+            Format::Base64.parse(s).ok()
+        })
+    }
+}
@@ -0,0 +1,50 @@
+//! Progress reporting hooks for embedders.
+
+use num_bigint::BigUint;
+use std::fmt;
+use std::sync::Arc;
+
+/// Something that happened to a candidate during the search.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum CandidateEvent<'a> {
+    /// A fresh random candidate was drawn.
+    Drawn(&'a BigUint),
+    /// The candidate was rejected by trial division.
+    SieveRejected(&'a BigUint),
+    /// A primality-test round (1-based) is about to run on the candidate.
+    Round {
+        candidate: &'a BigUint,
+        round: usize,
+    },
+    /// The candidate failed the base-2 Fermat prefilter.
+    FermatRejected(&'a BigUint),
+    /// The candidate failed the primality test.
+    TestRejected(&'a BigUint),
+    /// The candidate passed every check and is the result.
+    Accepted(&'a BigUint),
+}
+
+/// Shared callback invoked for every [`CandidateEvent`].
+#[derive(Clone)]
+pub struct ProgressHook(Arc<dyn Fn(&CandidateEvent<'_>) + Send + Sync>);
+
+impl ProgressHook {
+    /// Wrap a callback.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&CandidateEvent<'_>) + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn emit(&self, event: CandidateEvent<'_>) {
+        (self.0)(&event);
+    }
+}
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHook(..)")
+    }
+}
@@ -0,0 +1,319 @@
+//! Provable primes, enabled with the `provable` feature.
+//!
+//! Unlike the searches in [`crate::generator`], these constructions prove
+//! what they return: each prime comes with a smaller prime whose size makes
+//! a Pocklington-style check conclusive. Like [`crate::core`] this needs
+//! only `alloc`, apart from [`maurer`], which needs `std`.
+
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::One;
+#[cfg(feature = "std")]
+use num_traits::Zero;
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "std")]
+use crate::cert::PocklingtonCertificate;
+#[cfg(feature = "std")]
+use crate::core::{CryptoRngCore, random_range};
+use crate::core::{is_prime_deterministic, small_prime_precheck};
+#[cfg(feature = "std")]
+use crate::factor::primes_up_to;
+#[cfg(feature = "std")]
+use rand::Rng;
+
+/// Bits of output of the hash the constructions run on, SHA-256.
+const OUTLEN: u64 = 256;
+
+/// A prime from [`shawe_taylor`], with the values FIPS 186-4 needs to
+/// regenerate and validate it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaweTaylorPrime {
+    /// The prime.
+    pub prime: BigUint,
+    /// The seed after the construction; pass it to [`shawe_taylor`] to
+    /// continue with the next prime from the same seed.
+    pub prime_seed: Vec<u8>,
+    /// How many candidates were tried across every level of the recursion.
+    pub prime_gen_counter: u64,
+    /// The prime c0 above √prime that divides prime − 1, from the level
+    /// below; `None` under 33 bits, where there is no recursion.
+    pub factor: Option<BigUint>,
+}
+
+/// A provable `bits`-bit prime by the Shawe-Taylor random prime routine of
+/// FIPS 186-4 appendix C.6, derived from `seed` with SHA-256.
+///
+/// The same seed always gives the same prime. `None` where the standard
+/// reports failure: for fewer than 2 bits, an empty seed, or too many
+/// candidates at one level (which a fresh seed fixes).
+pub fn shawe_taylor(bits: u64, seed: &[u8]) -> Option<ShaweTaylorPrime> {
+    if bits < 2 || seed.is_empty() {
+        return None;
+    }
+    if bits < 33 {
+        return small_shawe_taylor(bits, seed);
+    }
+
+    let half = shawe_taylor(bits.div_ceil(2) + 1, seed)?;
+    let (c0, mut prime_seed, mut counter) = (half.prime, half.prime_seed, half.prime_gen_counter);
+    let iterations = bits.div_ceil(OUTLEN) - 1;
+    let old_counter = counter;
+    let top = BigUint::one() << (bits - 1);
+    let limit = BigUint::one() << bits;
+
+    let x = hash_integer(&mut prime_seed, iterations);
+    let x = &top + x % &top;
+    let step = &c0 << 1u32;
+    let mut t = x.div_ceil(&step);
+    loop {
+        if &step * &t + 1u32 > limit {
+            t = top.div_ceil(&step);
+        }
+        let c = &step * &t + 1u32;
+        counter += 1;
+
+        let a = hash_integer(&mut prime_seed, iterations);
+        // Trial division only skips candidates the test would reject, so
+        // the result matches the standard's exactly.
+        if small_prime_precheck(&c) && pocklington(&c, &c0, &t, a) {
+            return Some(ShaweTaylorPrime {
+                prime: c,
+                prime_seed,
+                prime_gen_counter: counter,
+                factor: Some(c0),
+            });
+        }
+        if counter >= 4 * bits + old_counter {
+            return None;
+        }
+        t += 1u32;
+    }
+}
+
+/// Below this many bits [`maurer`] draws primes directly.
+#[cfg(feature = "std")]
+const MAURER_BASE_BITS: u64 = 20;
+
+/// [`maurer`] trial-divides candidates by the primes up to this.
+#[cfg(feature = "std")]
+const MAURER_TRIAL_BOUND: u64 = 1 << 16;
+
+/// A prime from [`maurer`] and the certificate that proves it.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MaurerPrime {
+    /// The prime.
+    pub prime: BigUint,
+    /// A prime of at most 20 bits, checkable by trial division, that the
+    /// first step builds on.
+    pub base: BigUint,
+    /// One step per level of the recursion, from the base up to `prime`.
+    pub steps: Vec<PocklingtonStep>,
+}
+
+#[cfg(feature = "std")]
+impl MaurerPrime {
+    /// Whether the certificate proves `prime` prime: `base` is a prime
+    /// small enough to settle directly, and each step's n = 2rq + 1 passes
+    /// Pocklington's test on q, the n of the step below.
+    pub fn verify(&self) -> bool {
+        if self.base.bits() > MAURER_BASE_BITS || is_prime_deterministic(&self.base) != Some(true) {
+            return false;
+        }
+        let mut q = self.base.clone();
+        for step in &self.steps {
+            let n = ((&step.r * &q) << 1u32) + 1u32;
+            let certificate = PocklingtonCertificate {
+                n: n.clone(),
+                factors: alloc::vec![q],
+                witness: step.witness.clone(),
+            };
+            if !certificate.verify() {
+                return false;
+            }
+            q = n;
+        }
+        q == self.prime
+    }
+}
+
+/// One level of a [`MaurerPrime`] certificate: n = 2rq + 1, where q is the
+/// prime of the level below, is prime because q > √n, a^(n - 1) = 1 and
+/// gcd(a^2r - 1, n) = 1 modulo n.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PocklingtonStep {
+    /// The cofactor r.
+    pub r: BigUint,
+    /// The witness a.
+    pub witness: BigUint,
+}
+
+/// A random provable `bits`-bit prime by Maurer's recursive algorithm
+/// (HAC 4.62), with its certificate.
+///
+/// The primes are close to uniformly distributed, unlike those of
+/// [`shawe_taylor`], but cannot be regenerated from a seed.
+///
+/// # Panics
+///
+/// If `bits` is below 2.
+#[cfg(feature = "std")]
+pub fn maurer<R: CryptoRngCore + ?Sized>(bits: u64, rng: &mut R) -> MaurerPrime {
+    assert!(bits >= 2, "there are no primes below 2 bits");
+    if bits <= MAURER_BASE_BITS {
+        let top = BigUint::one() << (bits - 1);
+        let high = (&top << 1u32) - 1u32;
+        loop {
+            let n = random_range(&top, &high, rng) | BigUint::one();
+            if is_prime_deterministic(&n) == Some(true) {
+                return MaurerPrime {
+                    prime: n.clone(),
+                    base: n,
+                    steps: Vec::new(),
+                };
+            }
+        }
+    }
+
+    // The size of q relative to n, distributed as the largest prime factor
+    // of a random number so that n comes out nearly uniform.
+    let fraction = if bits > 2 * MAURER_BASE_BITS {
+        loop {
+            let r = (rng.r#gen::<f64>() - 1.0).exp2();
+            if bits as f64 - r * bits as f64 > MAURER_BASE_BITS as f64 {
+                break r;
+            }
+        }
+    } else {
+        0.5
+    };
+    // Rounding up keeps q at least 2^⌈fraction·bits⌉ > √n.
+    let mut certificate = maurer((fraction * bits as f64).ceil() as u64 + 1, rng);
+    let q = certificate.prime.clone();
+    let primes = primes_up_to((bits * bits / 10).min(MAURER_TRIAL_BOUND));
+
+    let half_range = (BigUint::one() << (bits - 1)) / (&q << 1u32);
+    let (low, high) = (&half_range + 1u32, &half_range << 1u32);
+    let two = BigUint::from(2u32);
+    loop {
+        let r = random_range(&low, &high, rng);
+        let n = ((&r * &q) << 1u32) + 1u32;
+        if primes.iter().any(|&p| (&n % p).is_zero()) {
+            continue;
+        }
+        let a = random_range(&two, &(&n - 2u32), rng);
+        if !a.modpow(&(&n - 1u32), &n).is_one() {
+            continue;
+        }
+        if (a.modpow(&(&r << 1u32), &n) - 1u32).gcd(&n).is_one() {
+            certificate.prime = n;
+            certificate.steps.push(PocklingtonStep { r, witness: a });
+            return certificate;
+        }
+    }
+}
+
+/// Step 30: with c = 2tc0 + 1 and c0 a prime above √c, c is prime if
+/// a^(c - 1) = 1 and gcd(a^2t - 1, c) = 1 for a = 2 + (`a` mod (c - 3)).
+fn pocklington(c: &BigUint, c0: &BigUint, t: &BigUint, a: BigUint) -> bool {
+    let a = a % (c - 3u32) + 2u32;
+    let z = a.modpow(&(t << 1u32), c);
+    (&z - 1u32).gcd(c).is_one() && z.modpow(c0, c).is_one()
+}
+
+/// Steps 3 to 13 of the routine: hash until a `bits`-bit prime turns up,
+/// for `bits` below 33, where trial division settles primality.
+fn small_shawe_taylor(bits: u64, seed: &[u8]) -> Option<ShaweTaylorPrime> {
+    let mut prime_seed = seed.to_vec();
+    let top = BigUint::one() << (bits - 1);
+    for counter in 1..=4 * bits + 1 {
+        let c = BigUint::from_bytes_be(&hash(&prime_seed))
+            ^ BigUint::from_bytes_be(&hash(&offset(&prime_seed, 1)));
+        let c = (&top + c % &top) | BigUint::one();
+        prime_seed = offset(&prime_seed, 2);
+        if is_prime_deterministic(&c) == Some(true) {
+            return Some(ShaweTaylorPrime {
+                prime: c,
+                prime_seed,
+                prime_gen_counter: counter,
+                factor: None,
+            });
+        }
+    }
+    None
+}
+
+/// The sum of Hash(seed + i) · 2^(i · outlen) for i = 0 ..= `iterations`,
+/// advancing `seed` past the values hashed.
+fn hash_integer(seed: &mut Vec<u8>, iterations: u64) -> BigUint {
+    let mut x = BigUint::default();
+    for i in 0..=iterations {
+        let digest = BigUint::from_bytes_be(&hash(&offset(seed, i)));
+        x += digest << (i * OUTLEN);
+    }
+    *seed = offset(seed, iterations + 1);
+    x
+}
+
+/// `seed + k` as a big-endian integer, wrapping to the seed's length.
+pub(crate) fn offset(seed: &[u8], k: u64) -> Vec<u8> {
+    let modulus = BigUint::one() << (seed.len() * 8);
+    let sum = (BigUint::from_bytes_be(seed) + k) % modulus;
+    let bytes = sum.to_bytes_be();
+    let mut padded = alloc::vec![0u8; seed.len() - bytes.len().min(seed.len())];
+    padded.extend_from_slice(&bytes);
+    padded
+}
+
+pub(crate) fn hash(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    /// Checked against an independent implementation of C.6 from the text
+    /// of FIPS 186-4.
+    #[test]
+    fn shawe_taylor_regression() {
+        let seed: Vec<u8> = (0..32).collect();
+        let prime = |hex: &str| BigUint::parse_bytes(hex.as_bytes(), 16).unwrap();
+
+        let small = shawe_taylor(32, &seed).unwrap();
+        assert_eq!(small.prime, prime("a56dd4bf"));
+        assert_eq!(small.prime_gen_counter, 5);
+        assert_eq!(small.prime_seed[29..], [0x1d, 0x1e, 0x29]);
+
+        let large = shawe_taylor(1024, &seed).unwrap();
+        assert_eq!(
+            large.prime,
+            prime(concat!(
+                "86618e18d223ff668f244b167e033f8798bf6b9544ec994a7db0273672130250",
+                "841d204cd0e81e6311e367ac46beb7dee91c4f16203d9da1897389bc0009100d",
+                "9c04bcc4f7d1e352569563b9ae695666be6391dfd3c7d24522d1d775ffa32fd9",
+                "02e9789784e6662bc63a6ae9704458c24dab316409209d7272b167b4274499b5",
+            ))
+        );
+        assert_eq!(large.prime_gen_counter, 950);
+        assert_eq!(large.prime_seed[29..], [0x1d, 0x2a, 0xe9]);
+        assert_eq!(large.prime_seed[..29], seed[..29]);
+    }
+
+    #[test]
+    fn maurer_certificates_verify() {
+        let mut rng = StdRng::seed_from_u64(72);
+        for bits in (21..=64).chain([127, 128, 255, 256]) {
+            for _ in 0..8 {
+                let maurer = maurer(bits, &mut rng);
+                assert_eq!(maurer.prime.bits(), bits);
+                assert!(maurer.verify(), "{bits} bits: {}", maurer.prime);
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Python bindings, enabled with the `python` feature.
+//!
+//! Build the `find_big_prime` extension module with `maturin develop`
+//! (see `pyproject.toml`). Python ints convert to and from `BigUint`.
+
+use num_bigint::BigUint;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::{DEFAULT_MR_ROUNDS, PrimeGenerator};
+
+/// generate_prime(bits, safe=False, rounds=64)
+/// --
+///
+/// Generate a probable prime (or safe prime) with `bits` bits.
+#[pyfunction]
+#[pyo3(signature = (bits, safe = false, rounds = DEFAULT_MR_ROUNDS))]
+fn generate_prime(py: Python<'_>, bits: usize, safe: bool, rounds: usize) -> PyResult<BigUint> {
+    let generator = PrimeGenerator::builder()
+        .bits(bits)
+        .rounds(rounds)
+        .safe(safe)
+        .build();
+    py.detach(|| generator.generate())
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+/// is_probable_prime(n, rounds=64)
+/// --
+///
+/// Run the Miller–Rabin test on a non-negative int.
+#[pyfunction]
+#[pyo3(signature = (n, rounds = DEFAULT_MR_ROUNDS))]
+fn is_probable_prime(py: Python<'_>, n: BigUint, rounds: usize) -> bool {
+    py.detach(|| crate::is_probable_prime(&n, rounds))
+}
+
+#[pymodule]
+fn find_big_prime(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(generate_prime, m)?)?;
+    m.add_function(wrap_pyfunction!(is_probable_prime, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,19 @@
+//! Metadata describing how a prime was found.
+
+use num_bigint::BigUint;
+use std::time::Duration;
+
+use crate::SearchStats;
+
+/// A generated prime together with the cost of finding it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenerationReport {
+    /// The prime that was found.
+    pub prime: BigUint,
+    /// Candidates drawn, sieved out, and test rounds executed.
+    pub stats: SearchStats,
+    /// Wall-clock time spent searching.
+    pub elapsed: Duration,
+    /// Type name of the RNG that supplied candidates and witnesses.
+    pub rng: &'static str,
+}
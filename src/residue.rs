@@ -0,0 +1,134 @@
+//! Residue classes that candidates are drawn from.
+//!
+//! A search restricted to p ≡ r (mod m) places each random candidate in
+//! the class as it is drawn, instead of testing candidates and throwing
+//! away the ones outside it, so the constraint costs no primality tests.
+//! Like [`crate::core`] this needs only `alloc`.
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// The odd integers congruent to a residue modulo a modulus.
+///
+/// Oddness is folded in when the class is made, so the modulus is always
+/// even and the residue odd.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ResidueClass {
+    residue: BigUint,
+    modulus: BigUint,
+}
+
+impl ResidueClass {
+    /// The odd p ≡ `residue` (mod `modulus`), or `None` when the class
+    /// holds at most one prime: for a zero modulus, a residue sharing a
+    /// factor with the modulus, or an even residue of an even modulus.
+    pub fn new(residue: impl Into<BigUint>, modulus: impl Into<BigUint>) -> Option<Self> {
+        let modulus = modulus.into();
+        if modulus.is_zero() {
+            return None;
+        }
+        let residue = residue.into() % &modulus;
+        if !residue.gcd(&modulus).is_one() {
+            return None;
+        }
+        if modulus.is_even() {
+            return Some(ResidueClass { residue, modulus });
+        }
+        // Odd modulus: pick the odd one of r and r + m, modulo 2m.
+        let residue = if residue.is_odd() {
+            residue
+        } else {
+            residue + &modulus
+        };
+        Some(ResidueClass {
+            residue,
+            modulus: modulus << 1u32,
+        })
+    }
+
+    /// The Blum primes, p ≡ 3 (mod 4).
+    pub fn blum() -> Self {
+        ResidueClass {
+            residue: BigUint::from(3u32),
+            modulus: BigUint::from(4u32),
+        }
+    }
+
+    /// The odd p whose low `bits` bits are `suffix`, or `None` when the
+    /// suffix is even or does not fit in `bits` bits.
+    pub fn suffix(suffix: impl Into<BigUint>, bits: usize) -> Option<Self> {
+        let suffix = suffix.into();
+        if suffix.is_even() || suffix.bits() > bits as u64 {
+            return None;
+        }
+        Some(ResidueClass {
+            residue: suffix,
+            modulus: BigUint::one() << bits,
+        })
+    }
+
+    /// The class of the p in both `self` and `other`, by the Chinese
+    /// remainder theorem, modulo the lcm of the moduli; `None` when the two
+    /// disagree on their common factor and no number is in both.
+    pub fn combine(&self, other: &ResidueClass) -> Option<Self> {
+        let gcd = self.modulus.gcd(&other.modulus);
+        let (low, high) = if self.residue <= other.residue {
+            (&self.residue, &other.residue)
+        } else {
+            (&other.residue, &self.residue)
+        };
+        let gap = high - low;
+        if !(&gap % &gcd).is_zero() {
+            return None;
+        }
+        // p = r₁ + m₁·t with m₁·t ≡ r₂ − r₁ (mod m₂), so t ≡ (gap/g)·(m₁/g)⁻¹
+        // (mod m₂/g), negated when r₂ < r₁.
+        let reduced = &other.modulus / &gcd;
+        let inverse = (&self.modulus / &gcd).modinv(&reduced)?;
+        let mut t = gap / &gcd * inverse % &reduced;
+        if self.residue > other.residue && !t.is_zero() {
+            t = &reduced - t;
+        }
+        let residue = &self.residue + &self.modulus * t;
+        let modulus = &self.modulus * reduced;
+        Some(ResidueClass { residue, modulus })
+    }
+
+    /// The residue, below the modulus and odd.
+    pub fn residue(&self) -> &BigUint {
+        &self.residue
+    }
+
+    /// The modulus, even.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Whether `n` is in the class.
+    pub fn contains(&self, n: &BigUint) -> bool {
+        n % &self.modulus == self.residue
+    }
+
+    /// The fewest bits a number must have for the class to hold one of
+    /// every size: 2^(bits − 1) must reach the modulus.
+    pub fn min_bits(&self) -> usize {
+        (&self.modulus - 1u32).bits() as usize + 1
+    }
+
+    /// Move `n`, in `low..high`, to a member of the class still in that
+    /// range; at most the modulus away.
+    ///
+    /// The range must be at least the modulus wide, as it is for the
+    /// numbers of a size of at least [`Self::min_bits`] bits.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn place(&self, n: &BigUint, low: &BigUint, high: &BigUint) -> BigUint {
+        let mut placed = n - (n % &self.modulus) + &self.residue;
+        if &placed >= high {
+            placed -= &self.modulus;
+        } else if &placed < low {
+            placed += &self.modulus;
+        }
+        placed
+    }
+}
@@ -0,0 +1,824 @@
+//! The candidate search loop shared by every generation entry point.
+
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive};
+use rand::seq::index;
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::ops::AddAssign;
+use std::time::{Duration, Instant};
+
+use crate::chain::ChainKind;
+use crate::core::is_fermat_probable_prime_base2;
+use crate::factor::primes_up_to;
+use crate::progress::{CandidateEvent, ProgressHook};
+use crate::residue::ResidueClass;
+use crate::{CancellationToken, CryptoRngCore, MillerRabin, PrimalityTest, small_prime_precheck};
+use crate::{PrimeGenError, Result};
+
+/// Twin and chain candidates are sieved by the primes up to this before
+/// any test.
+const PATTERN_SIEVE_BOUND: u64 = 1 << 12;
+
+/// Bits a strong prime needs beyond its two auxiliary factors, to leave
+/// room for the search.
+const STRONG_SLACK_BITS: usize = 16;
+
+/// When at most this many candidates have the requested Hamming weight,
+/// the search remembers those it drew and stops once it has tried them all.
+const WEIGHT_EXHAUSTIVE: u64 = 1 << 12;
+
+/// Counters describing how much work a search has done.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Random candidates drawn.
+    pub candidates: u64,
+    /// Candidates rejected by trial division before the primality test.
+    pub sieved_out: u64,
+    /// Primality-test rounds executed across all candidates.
+    pub rounds: u64,
+    /// Candidates that survived trial division but failed the base-2 Fermat prefilter.
+    pub fermat_rejected: u64,
+    /// Candidates that survived the prefilters but failed the primality test.
+    pub test_rejected: u64,
+    /// Wall-clock time spent inside the primality test.
+    pub test_time: Duration,
+}
+
+impl AddAssign for SearchStats {
+    fn add_assign(&mut self, other: SearchStats) {
+        self.candidates += other.candidates;
+        self.sieved_out += other.sieved_out;
+        self.fermat_rejected += other.fermat_rejected;
+        self.rounds += other.rounds;
+        self.test_rejected += other.test_rejected;
+        self.test_time += other.test_time;
+    }
+}
+
+/// Search state threaded through one generation call.
+pub(crate) struct Search<'a> {
+    test: &'a dyn PrimalityTest,
+    cancel: Option<&'a CancellationToken>,
+    stop: Option<&'a CancellationToken>,
+    hook: Option<&'a ProgressHook>,
+    deadline: Option<Instant>,
+    max_attempts: Option<u64>,
+    fermat: bool,
+    screen: Option<MillerRabin>,
+    class: Option<&'a ResidueClass>,
+    prefix: Option<&'a BigUint>,
+    weight: Option<usize>,
+    #[cfg(feature = "zeroize")]
+    secret: bool,
+    stats: SearchStats,
+}
+
+impl<'a> Search<'a> {
+    pub(crate) fn new(test: &'a dyn PrimalityTest) -> Self {
+        Self {
+            test,
+            cancel: None,
+            stop: None,
+            hook: None,
+            deadline: None,
+            max_attempts: None,
+            fermat: true,
+            screen: None,
+            class: None,
+            prefix: None,
+            weight: None,
+            #[cfg(feature = "zeroize")]
+            secret: false,
+            stats: SearchStats::default(),
+        }
+    }
+
+    /// Wipe rejected candidates instead of just dropping them.
+    #[cfg(feature = "zeroize")]
+    pub(crate) fn secret(mut self, secret: bool) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    /// Drop a rejected value, wiping it first in secret mode.
+    #[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+    fn discard(&self, mut n: BigUint) {
+        #[cfg(feature = "zeroize")]
+        if self.secret {
+            crate::secret::wipe(&mut n);
+        }
+        drop(n);
+    }
+
+    pub(crate) fn cancel(mut self, cancel: Option<&'a CancellationToken>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Also stop when `stop` is cancelled, e.g. because another worker won.
+    #[cfg_attr(not(feature = "os-rng"), allow(dead_code))]
+    pub(crate) fn stop(mut self, stop: Option<&'a CancellationToken>) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    pub(crate) fn hook(mut self, hook: Option<&'a ProgressHook>) -> Self {
+        self.hook = hook;
+        self
+    }
+
+    /// Give up with [`PrimeGenError::TimedOut`] once `deadline` has passed.
+    pub(crate) fn deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Give up with [`PrimeGenError::AttemptsExhausted`] after drawing `max` candidates.
+    pub(crate) fn max_attempts(mut self, max: Option<u64>) -> Self {
+        self.max_attempts = max;
+        self
+    }
+
+    /// Run the base-2 Fermat prefilter before the primality test (the default).
+    pub(crate) fn fermat(mut self, fermat: bool) -> Self {
+        self.fermat = fermat;
+        self
+    }
+
+    /// Screen candidates with `rounds` Miller–Rabin rounds and run the full
+    /// test only on the final result (both halves of a safe prime).
+    pub(crate) fn screen(mut self, rounds: Option<usize>) -> Self {
+        self.screen = rounds.map(MillerRabin::new);
+        self
+    }
+
+    /// Draw plain probable primes from a residue class, placing every
+    /// candidate in it as it is drawn.
+    pub(crate) fn class(mut self, class: Option<&'a ResidueClass>) -> Self {
+        self.class = class;
+        self
+    }
+
+    /// Start plain and safe prime candidates with the bits of `prefix`
+    /// instead of just a one.
+    pub(crate) fn prefix(mut self, prefix: Option<&'a BigUint>) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Draw plain probable primes with exactly `weight` bits set, in place
+    /// of any residue class.
+    pub(crate) fn weight(mut self, weight: Option<usize>) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    fn emit(&self, event: CandidateEvent<'_>) {
+        if let Some(hook) = self.hook {
+            hook.emit(event);
+        }
+    }
+
+    /// Statistics gathered so far.
+    pub(crate) fn stats(&self) -> SearchStats {
+        self.stats
+    }
+
+    /// Whether `n` survives the Fermat prefilter, if enabled.
+    fn prefilter(&mut self, n: &BigUint) -> bool {
+        if !self.fermat || is_fermat_probable_prime_base2(n) {
+            return true;
+        }
+        self.stats.fermat_rejected += 1;
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            candidate = self.stats.candidates,
+            fermat_rejected = self.stats.fermat_rejected,
+            "fermat prefilter rejected candidate"
+        );
+        self.emit(CandidateEvent::FermatRejected(n));
+        false
+    }
+
+    /// Run the screening test on `n`, or the full test when not screening.
+    fn screen_test(&mut self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool {
+        match self.screen {
+            Some(screen) => self.run(&screen, n, rng),
+            None => self.run(self.test, n, rng),
+        }
+    }
+
+    /// Run the full test on a screened survivor; a no-op when not screening.
+    fn confirm(&mut self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool {
+        self.screen.is_none() || self.run(self.test, n, rng)
+    }
+
+    /// Run `test` on `n`, counting rounds and reporting them to the hook.
+    fn run(&mut self, test: &dyn PrimalityTest, n: &BigUint, rng: &mut dyn CryptoRngCore) -> bool {
+        let start = Instant::now();
+        let hook = self.hook;
+        let stats = &mut self.stats;
+        let passed = test.is_probable_prime_observed(n, rng, &mut |round| {
+            stats.rounds += 1;
+            if let Some(hook) = hook {
+                hook.emit(CandidateEvent::Round {
+                    candidate: n,
+                    round,
+                });
+            }
+        });
+        let elapsed = start.elapsed();
+        self.stats.test_time += elapsed;
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            bits = n.bits(),
+            passed,
+            elapsed_us = elapsed.as_micros() as u64,
+            "primality test"
+        );
+        if !passed {
+            self.stats.test_rejected += 1;
+            self.emit(CandidateEvent::TestRejected(n));
+        }
+        passed
+    }
+
+    /// Stop the search if it was cancelled or ran out of time or attempts.
+    fn check_limits(&self) -> Result<()> {
+        let cancelled = [self.cancel, self.stop]
+            .into_iter()
+            .flatten()
+            .any(CancellationToken::is_cancelled);
+        let err = if cancelled {
+            PrimeGenError::Cancelled(self.stats)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            PrimeGenError::TimedOut(self.stats)
+        } else if self
+            .max_attempts
+            .is_some_and(|max| self.stats.candidates >= max)
+        {
+            PrimeGenError::AttemptsExhausted(self.stats)
+        } else {
+            return Ok(());
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(%err, stats = ?self.stats, "search stopped");
+        Err(err)
+    }
+
+    #[cfg(feature = "tracing")]
+    fn trace_found(&self, kind: &'static str, bits: usize) {
+        tracing::debug!(
+            kind,
+            bits,
+            candidates = self.stats.candidates,
+            sieved_out = self.stats.sieved_out,
+            rounds = self.stats.rounds,
+            "search finished"
+        );
+    }
+
+    /// Find a random probable prime and report it as accepted.
+    pub(crate) fn find_probable_prime(
+        &mut self,
+        bits: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        let prime = self.probable_prime(bits, rng)?;
+        #[cfg(feature = "tracing")]
+        self.trace_found("probable prime", bits);
+        self.emit(CandidateEvent::Accepted(&prime));
+        Ok(prime)
+    }
+
+    /// Find a safe prime and report it as accepted.
+    pub(crate) fn find_safe_prime(
+        &mut self,
+        bits: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        let prime = self.safe_prime(bits, rng)?;
+        #[cfg(feature = "tracing")]
+        self.trace_found("safe prime", bits);
+        self.emit(CandidateEvent::Accepted(&prime));
+        Ok(prime)
+    }
+
+    /// Find the smaller prime of a twin pair and report it as accepted.
+    pub(crate) fn find_twin_prime(
+        &mut self,
+        bits: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        let prime = self.twin_prime(bits, rng)?;
+        #[cfg(feature = "tracing")]
+        self.trace_found("twin prime", bits);
+        self.emit(CandidateEvent::Accepted(&prime));
+        Ok(prime)
+    }
+
+    /// Find a strong prime and report it as accepted.
+    pub(crate) fn find_strong_prime(
+        &mut self,
+        bits: usize,
+        factor_bits: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        let prime = self.strong_prime(bits, factor_bits, rng)?;
+        #[cfg(feature = "tracing")]
+        self.trace_found("strong prime", bits);
+        self.emit(CandidateEvent::Accepted(&prime));
+        Ok(prime)
+    }
+
+    /// Find the bottom prime n − 1 of a bi-twin chain and report it as
+    /// accepted.
+    pub(crate) fn find_bi_twin(
+        &mut self,
+        bits: usize,
+        links: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        let prime = self.bi_twin(bits, links, rng)?;
+        #[cfg(feature = "tracing")]
+        self.trace_found("bi-twin chain", bits);
+        self.emit(CandidateEvent::Accepted(&prime));
+        Ok(prime)
+    }
+
+    /// Find the first prime of a Cunningham chain and report it as accepted.
+    pub(crate) fn find_chain(
+        &mut self,
+        bits: usize,
+        kind: ChainKind,
+        length: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        let prime = self.chain(bits, kind, length, rng)?;
+        #[cfg(feature = "tracing")]
+        self.trace_found("cunningham chain", bits);
+        self.emit(CandidateEvent::Accepted(&prime));
+        Ok(prime)
+    }
+
+    /// Find a random probable prime of exactly `bits` bits, in the residue
+    /// class or of the Hamming weight if the search has one.
+    fn probable_prime(&mut self, bits: usize, rng: &mut dyn CryptoRngCore) -> Result<BigUint> {
+        match self.weight {
+            Some(weight) => self.weighted_prime(bits, weight, rng),
+            None => {
+                let class = self.class;
+                self.plain_prime(bits, class, rng)
+            }
+        }
+    }
+
+    /// Find a random probable prime of exactly `bits` bits, in `class` when
+    /// one is given.
+    fn plain_prime(
+        &mut self,
+        bits: usize,
+        class: Option<&ResidueClass>,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        loop {
+            let n = self.survivor(bits, class, rng)?;
+            if self.confirm(&n, rng) {
+                return Ok(n);
+            }
+            self.discard(n);
+        }
+    }
+
+    /// Draw `bits`-bit candidates until one passes the prefilters and the
+    /// screening test, moving each into `class` when one is given.
+    fn survivor(
+        &mut self,
+        bits: usize,
+        class: Option<&ResidueClass>,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        let one = BigUint::one();
+        let prefix = self.prefix.unwrap_or(&one);
+        let prefix_bits = prefix.bits();
+        let top = usize::try_from(prefix_bits).map_err(|_| PrimeGenError::BitsTooLarge(bits))?;
+        let min = class.map_or(top + 1, |class| class.min_bits() + top - 1);
+        if bits < min {
+            return Err(PrimeGenError::BitsTooSmall { bits, min });
+        }
+        let bits_u64 = u64::try_from(bits).map_err(|_| PrimeGenError::BitsTooLarge(bits))?;
+        // Candidates lie in [prefix·2^free, (prefix + 1)·2^free).
+        let free = bits_u64 - prefix_bits;
+        let low = prefix << free;
+        let high = (prefix + 1u32) << free;
+        loop {
+            self.check_limits()?;
+            let mut n = rng.gen_biguint(bits_u64);
+            self.stats.candidates += 1;
+
+            // Force the prefix to ensure bit length and make the candidate odd.
+            for bit in 0..prefix_bits {
+                n.set_bit(free + bit, prefix.bit(bit));
+            }
+            if n.is_even() {
+                n |= &one;
+            }
+            if let Some(class) = class {
+                n = class.place(&n, &low, &high);
+            }
+
+            self.emit(CandidateEvent::Drawn(&n));
+
+            if !small_prime_precheck(&n) {
+                self.stats.sieved_out += 1;
+                #[cfg(feature = "tracing")]
+                tracing::trace!(
+                    candidate = self.stats.candidates,
+                    sieved_out = self.stats.sieved_out,
+                    "sieve rejected candidate"
+                );
+                self.emit(CandidateEvent::SieveRejected(&n));
+                self.discard(n);
+                continue;
+            }
+
+            if self.prefilter(&n) && self.screen_test(&n, rng) {
+                return Ok(n);
+            }
+            self.discard(n);
+        }
+    }
+
+    /// Find a random probable prime of exactly `bits` bits with `weight`
+    /// of them set: the prefix and the lowest bit, and the rest at
+    /// positions drawn uniformly from the bits between.
+    ///
+    /// With few such candidates, each is tested once and the search fails
+    /// with [`PrimeGenError::AttemptsExhausted`] when none is prime.
+    fn weighted_prime(
+        &mut self,
+        bits: usize,
+        weight: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        let one = BigUint::one();
+        let prefix = self.prefix.unwrap_or(&one);
+        let top = usize::try_from(prefix.bits()).map_err(|_| PrimeGenError::BitsTooLarge(bits))?;
+        if bits <= top {
+            return Err(PrimeGenError::BitsTooSmall { bits, min: top + 1 });
+        }
+        let bits_u64 = u64::try_from(bits).map_err(|_| PrimeGenError::BitsTooLarge(bits))?;
+        // The prefix's ones and the low bit are fixed; the bits between are free.
+        let fixed = prefix.count_ones() as usize + 1;
+        let between = bits - top - 1;
+        if weight < fixed || weight > fixed + between {
+            return Err(PrimeGenError::WeightOutOfRange {
+                bits,
+                weight,
+                min: fixed,
+                max: fixed + between,
+            });
+        }
+        let base = (prefix << (bits_u64 - prefix.bits())) | &one;
+        let total = binomial_at_most(between, weight - fixed, WEIGHT_EXHAUSTIVE);
+        let mut tried = BTreeSet::new();
+        loop {
+            self.check_limits()?;
+            if total.is_some_and(|total| tried.len() as u64 == total) {
+                return Err(PrimeGenError::AttemptsExhausted(self.stats));
+            }
+            let mut n = base.clone();
+            for position in index::sample(rng, between, weight - fixed) {
+                n.set_bit(position as u64 + 1, true);
+            }
+            if tried.contains(&n) {
+                self.discard(n);
+                continue;
+            }
+            self.stats.candidates += 1;
+            self.emit(CandidateEvent::Drawn(&n));
+
+            if !small_prime_precheck(&n) {
+                self.stats.sieved_out += 1;
+                self.emit(CandidateEvent::SieveRejected(&n));
+            } else if self.prefilter(&n) && self.screen_test(&n, rng) && self.confirm(&n, rng) {
+                return Ok(n);
+            }
+            // Only composites are kept, so no copy of the prime outlives the
+            // search.
+            if total.is_some() {
+                tried.insert(n.clone());
+            }
+            self.discard(n);
+        }
+    }
+
+    /// Find a safe prime p = 2q + 1 of exactly `bits` bits.
+    fn safe_prime(&mut self, bits: usize, rng: &mut dyn CryptoRngCore) -> Result<BigUint> {
+        let min = self.prefix.map_or(3, |prefix| prefix.bits() as usize + 2);
+        if bits < min {
+            return Err(PrimeGenError::BitsTooSmall { bits, min });
+        }
+        let q_bits = bits - 1;
+        loop {
+            let q = self.survivor(q_bits, None, rng)?;
+            let p = (&q << 1usize) + BigUint::one();
+            let found = self.prefilter(&p)
+                && self.screen_test(&p, rng)
+                && self.confirm(&q, rng)
+                && self.confirm(&p, rng);
+            self.discard(q);
+            if found {
+                return Ok(p);
+            }
+            self.discard(p);
+        }
+    }
+
+    /// Find p of exactly `bits` bits with p and p + 2 both probable primes:
+    /// a bi-twin chain of one link.
+    fn twin_prime(&mut self, bits: usize, rng: &mut dyn CryptoRngCore) -> Result<BigUint> {
+        self.pattern(bits, 1, &[-1, 1], rng)
+    }
+
+    /// Find p of exactly `bits` bits starting a Cunningham chain of `kind`
+    /// with `length` probable primes; a length below two is any prime.
+    ///
+    /// With n the [base](ChainKind::base) of p the members are 2^i·n ∓ 1.
+    fn chain(
+        &mut self,
+        bits: usize,
+        kind: ChainKind,
+        length: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        if length < 2 {
+            return self.probable_prime(bits, rng);
+        }
+        let offset = match kind {
+            ChainKind::First => -1,
+            ChainKind::Second => 1,
+        };
+        self.pattern(bits, length, &[offset], rng)
+    }
+
+    /// Find p = n − 1 of exactly `bits` bits at the bottom of a bi-twin
+    /// chain of `links` links, with 2^i·n ± 1 probable primes for every
+    /// i below `links`.
+    fn bi_twin(
+        &mut self,
+        bits: usize,
+        links: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        self.pattern(bits, links.max(1), &[-1, 1], rng)
+    }
+
+    /// Find a base n with 2^i·n + e a probable prime for every i below
+    /// `length` and e in `offsets`, each 1 or −1, returning the smallest of
+    /// them, which has exactly `bits` bits.
+    ///
+    /// Twin pairs and chains of two or more past 3 all have n ≡ 0 (mod 6),
+    /// so only those n are drawn. Each is sieved once for every member: a
+    /// small prime r rules n out when some 2^i·n lands on −e (mod r).
+    fn pattern(
+        &mut self,
+        bits: usize,
+        length: usize,
+        offsets: &[i8],
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        if bits < 3 {
+            return Err(PrimeGenError::BitsTooSmall { bits, min: 3 });
+        }
+        let bits_u64 = u64::try_from(bits).map_err(|_| PrimeGenError::BitsTooLarge(bits))?;
+        let low = BigUint::one() << (bits_u64 - 1);
+        let below = offsets.contains(&-1);
+        // 2 and 3 are already ruled out by the residue mod 6.
+        let sieve: Vec<u64> = primes_up_to(PATTERN_SIEVE_BOUND)
+            .into_iter()
+            .skip(2)
+            .collect();
+        loop {
+            self.check_limits()?;
+            let mut n = rng.gen_biguint(bits_u64);
+            self.stats.candidates += 1;
+            n.set_bit(bits_u64 - 1, true);
+            n -= &n % 6u32;
+            // Keep the smallest member n ∓ 1 at exactly `bits` bits; 2^bits − 1
+            // is never a multiple of 6, so n + 1 cannot reach 2^bits.
+            if n < low {
+                n += 6u32;
+            }
+            let p = if below { &n - 1u32 } else { &n + 1u32 };
+
+            self.emit(CandidateEvent::Drawn(&p));
+
+            // Tiny sizes, where a member can be r itself, are not sieved.
+            if bits > 12 && doubling_sieved(&sieve, &n, length, offsets) {
+                self.stats.sieved_out += 1;
+                #[cfg(feature = "tracing")]
+                tracing::trace!(
+                    candidate = self.stats.candidates,
+                    sieved_out = self.stats.sieved_out,
+                    "sieve rejected candidate"
+                );
+                self.emit(CandidateEvent::SieveRejected(&p));
+                self.discard(p);
+                continue;
+            }
+
+            let mut members: Vec<BigUint> = Vec::with_capacity(length * offsets.len());
+            for i in 0..length {
+                let doubled = &n << i;
+                for &e in offsets {
+                    members.push(if e > 0 {
+                        &doubled + 1u32
+                    } else {
+                        &doubled - 1u32
+                    });
+                }
+            }
+            let found = members.iter().all(|m| self.prefilter(m))
+                && members.iter().all(|m| self.screen_test(m, rng))
+                && members.iter().all(|m| self.confirm(m, rng));
+            self.discard(n);
+            for member in members {
+                self.discard(member);
+            }
+            if found {
+                return Ok(p);
+            }
+            self.discard(p);
+        }
+    }
+
+    /// Count `n` as a candidate and test it like a drawn one: trial
+    /// division, the prefilter, then the screening and full tests.
+    fn test_candidate(&mut self, n: &BigUint, rng: &mut dyn CryptoRngCore) -> Result<bool> {
+        self.check_limits()?;
+        self.stats.candidates += 1;
+        self.emit(CandidateEvent::Drawn(n));
+        if !small_prime_precheck(n) {
+            self.stats.sieved_out += 1;
+            self.emit(CandidateEvent::SieveRejected(n));
+            return Ok(false);
+        }
+        Ok(self.prefilter(n) && self.screen_test(n, rng) && self.confirm(n, rng))
+    }
+
+    /// Find a strong prime p of exactly `bits` bits by Gordon's algorithm:
+    /// p − 1 has a prime factor r, p + 1 one s, and r − 1 one t, with s and
+    /// t of `factor_bits` bits and r a little larger.
+    ///
+    /// With s and t drawn at random, r is the first prime 2it + 1,
+    /// p₀ = 2(s^(r−2) mod r)·s − 1 is 1 mod r and −1 mod s, and p is the
+    /// first prime p₀ + 2jrs at or past a random start of `bits` bits. A
+    /// search that runs past `bits` bits starts over with new s and t.
+    fn strong_prime(
+        &mut self,
+        bits: usize,
+        factor_bits: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<BigUint> {
+        if factor_bits < 2 {
+            return Err(PrimeGenError::BitsTooSmall {
+                bits: factor_bits,
+                min: 2,
+            });
+        }
+        let min = 2 * factor_bits + STRONG_SLACK_BITS;
+        if bits < min {
+            return Err(PrimeGenError::BitsTooSmall { bits, min });
+        }
+        let bits_u64 = u64::try_from(bits).map_err(|_| PrimeGenError::BitsTooLarge(bits))?;
+        let top = BigUint::one() << bits_u64;
+        loop {
+            let s = self.plain_prime(factor_bits, None, rng)?;
+            let t = self.plain_prime(factor_bits, None, rng)?;
+            let double_t = &t << 1u32;
+            let mut r = &double_t + 1u32;
+            while !self.test_candidate(&r, rng)? {
+                r += &double_t;
+            }
+            let inverse = s.modpow(&(&r - 2u32), &r);
+            let p0 = ((inverse * &s) << 1u32) - 1u32;
+            let step = (&r * &s) << 1u32;
+            let mut start = rng.gen_biguint(bits_u64);
+            start.set_bit(bits_u64 - 1, true);
+            // p₀ < 2rs, far below a start of `bits` bits.
+            let mut p = &p0 + (&start - &p0).div_ceil(&step) * &step;
+            let mut found = false;
+            while p < top {
+                if self.test_candidate(&p, rng)? {
+                    found = true;
+                    break;
+                }
+                p += &step;
+            }
+            for n in [s, t, r] {
+                self.discard(n);
+            }
+            if found {
+                return Ok(p);
+            }
+            self.discard(p);
+        }
+    }
+}
+
+/// Whether a prime r in `sieve` divides some 2^i·n + e, for i below
+/// `length` and e in `offsets`, each 1 or −1.
+fn doubling_sieved(sieve: &[u64], n: &BigUint, length: usize, offsets: &[i8]) -> bool {
+    sieve.iter().any(|&r| {
+        let mut x = (n % r).to_u64().expect("below r");
+        (0..length).any(|_| {
+            let hit = offsets.iter().any(|&e| x == if e > 0 { r - 1 } else { 1 });
+            x = x * 2 % r;
+            hit
+        })
+    })
+}
+
+/// C(`n`, `k`), or `None` if it is above `cap`.
+fn binomial_at_most(n: usize, k: usize, cap: u64) -> Option<u64> {
+    let k = k.min(n.checked_sub(k)?);
+    let mut count: u128 = 1;
+    for i in 0..k {
+        // C(n, i + 1) = C(n, i)·(n − i)/(i + 1), exactly, and rising.
+        count = count * (n - i) as u128 / (i + 1) as u128;
+        if count > u128::from(cap) {
+            return None;
+        }
+    }
+    Some(count as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrimeGenerator;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn weighted(bits: usize, weight: usize) -> Result<BigUint> {
+        PrimeGenerator::builder()
+            .bits(bits)
+            .hamming_weight(weight)
+            .build()
+            .generate_with_rng(&mut StdRng::seed_from_u64(97))
+    }
+
+    #[test]
+    fn lone_candidates_are_tested_once() {
+        // Only the top and bottom bits, or every bit, set.
+        assert_eq!(weighted(17, 2), Ok(BigUint::from(65537u32)));
+        assert_eq!(weighted(61, 61), Ok(BigUint::from((1u64 << 61) - 1)));
+        for (bits, weight) in [(64, 2), (62, 62)] {
+            match weighted(bits, weight) {
+                Err(PrimeGenError::AttemptsExhausted(stats)) => assert_eq!(stats.candidates, 1),
+                other => panic!("{bits} bits of weight {weight}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn few_candidates_are_each_tried() {
+        // The 64-bit numbers 2^63 + 2^i + 2^j + 1 and 2^63 + 2^i + 1.
+        for (weight, total) in [(4, 62 * 61 / 2), (3, 62)] {
+            match weighted(64, weight) {
+                Ok(p) => assert_eq!(p.count_ones(), weight as u64),
+                Err(PrimeGenError::AttemptsExhausted(stats)) => {
+                    assert_eq!(stats.candidates, total)
+                }
+                Err(err) => panic!("{err}"),
+            }
+        }
+    }
+
+    #[test]
+    fn unreachable_weights_are_errors() {
+        assert!(matches!(
+            weighted(64, 65),
+            Err(PrimeGenError::WeightOutOfRange {
+                min: 2,
+                max: 64,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn binomials_stop_at_the_cap() {
+        assert_eq!(binomial_at_most(62, 0, 10), Some(1));
+        assert_eq!(binomial_at_most(62, 62, 10), Some(1));
+        assert_eq!(binomial_at_most(62, 2, 1891), Some(1891));
+        assert_eq!(binomial_at_most(62, 2, 1890), None);
+        assert_eq!(binomial_at_most(10_000, 5_000, 1 << 12), None);
+    }
+}
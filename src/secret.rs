@@ -0,0 +1,63 @@
+//! Best-effort wiping of secret intermediates, enabled with the `zeroize`
+//! feature.
+//!
+//! `BigUint` keeps its limbs private, so [`wipe`] overwrites them through
+//! the public API before the value is dropped. That reaches only the limbs
+//! in use: spare capacity, buffers freed when `num-bigint` reallocates or
+//! shrinks a value, and temporaries allocated inside it (for example during
+//! `modpow`) are outside our reach, and the compiler may still elide the
+//! stores. Do not rely on this where a guaranteed wipe is required.
+
+use alloc::vec;
+use core::sync::atomic::{Ordering, compiler_fence};
+use num_bigint::BigUint;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Overwrite the limbs `n` currently uses with zeros, leaving it equal to
+/// zero. Best effort; see the [module docs](self).
+pub fn wipe(n: &mut BigUint) {
+    let limbs = usize::try_from(n.bits().div_ceil(32)).unwrap_or(usize::MAX);
+    let mut zeros = vec![0u32; limbs];
+    n.assign_from_slice(&zeros);
+    zeros.zeroize();
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// A prime that is wiped, on a best-effort basis, when dropped.
+///
+/// Copies made while the prime was computed are not tracked; see the
+/// [module docs](self) for what the wipe does not cover.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretPrime(BigUint);
+
+impl SecretPrime {
+    /// Take ownership of `n`, wiping it on drop.
+    pub fn new(n: BigUint) -> Self {
+        Self(n)
+    }
+
+    /// Borrow the secret value.
+    pub fn expose(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+impl Zeroize for SecretPrime {
+    fn zeroize(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+impl Drop for SecretPrime {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretPrime {}
+
+impl core::fmt::Debug for SecretPrime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("SecretPrime(..)")
+    }
+}
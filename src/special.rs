@@ -0,0 +1,256 @@
+//! Searches for primes of special form, whose shape makes arithmetic
+//! modulo them fast.
+//!
+//! Each search walks the few parameters of its form in a fixed order and
+//! stops at the first probable prime, so what it finds depends only on the
+//! form and the size; the RNG only feeds the primality test. Candidates
+//! are sieved by the small primes first, with one residue per prime being
+//! enough to step through the form. Like [`crate::core`] this needs only
+//! `alloc`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::factor::primes_up_to;
+use crate::forms::proth;
+use crate::{CryptoRngCore, PrimalityTest, small_prime_precheck};
+
+/// Candidates are sieved by the primes up to this before any test.
+const SIEVE_BOUND: u64 = 1 << 12;
+
+/// Proth multipliers sieved at a time.
+const PROTH_BLOCK: u64 = 1 << 16;
+
+/// The odd primes up to [`SIEVE_BOUND`], each with 2^`n` modulo it.
+fn power_residues(n: u64) -> Vec<(u64, u64)> {
+    let power = BigUint::one() << n;
+    primes_up_to(SIEVE_BOUND)
+        .into_iter()
+        .skip(1)
+        .map(|r| (r, (&power % r).to_u64().expect("below r")))
+        .collect()
+}
+
+/// The least odd c ≥ `from` with 2^`n` − c a probable prime under `test`,
+/// or `None` if there is none; c stays at most 2^(n − 1), so the prime
+/// has `n` bits.
+///
+/// Such pseudo-Mersenne primes, 2^255 − 19 among them, reduce modulo
+/// themselves with a shift and a multiply by c. A sieving prime r divides
+/// 2^n − c exactly when c ≡ 2^n (mod r), so each c is sieved without
+/// forming the candidate.
+pub fn pseudo_mersenne(
+    n: u64,
+    from: u64,
+    test: &dyn PrimalityTest,
+    rng: &mut dyn CryptoRngCore,
+) -> Option<u64> {
+    if n < 2 {
+        return None;
+    }
+    let power = BigUint::one() << n;
+    let sieve = power_residues(n);
+    let limit = if n > 64 { u64::MAX } else { 1 << (n - 1) };
+    let mut c = from | 1;
+    while c <= limit {
+        // Tiny sizes, where the candidate can be r itself, are not sieved.
+        let sieved = n > 13 && sieve.iter().any(|&(r, residue)| c % r == residue);
+        if !sieved && test.is_probable_prime(&(&power - c), rng) {
+            return Some(c);
+        }
+        c = c.checked_add(2)?;
+    }
+    None
+}
+
+/// A signed sum of powers of two, 2^n ± 2^e ± … ± 1, the exponents
+/// falling.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SignedPowers {
+    n: u64,
+    terms: Vec<(bool, u64)>,
+}
+
+impl SignedPowers {
+    /// The exponent of the leading power, 2^n.
+    pub fn n(&self) -> u64 {
+        self.n
+    }
+
+    /// The terms after 2^n, each whether it is subtracted and its
+    /// exponent, in falling order down to 0.
+    pub fn terms(&self) -> &[(bool, u64)] {
+        &self.terms
+    }
+
+    /// The number the sum comes to.
+    pub fn value(&self) -> BigUint {
+        let mut added = BigUint::one() << self.n;
+        let mut subtracted = BigUint::zero();
+        for &(negative, exponent) in &self.terms {
+            let term = BigUint::one() << exponent;
+            if negative {
+                subtracted += term;
+            } else {
+                added += term;
+            }
+        }
+        added - subtracted
+    }
+}
+
+impl fmt::Display for SignedPowers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "2^{}", self.n)?;
+        for &(negative, exponent) in &self.terms {
+            let sign = if negative { '-' } else { '+' };
+            match exponent {
+                0 => write!(f, " {sign} 1")?,
+                _ => write!(f, " {sign} 2^{exponent}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The sums 2^`n` ± 2^e ± … ± 1 of `terms` powers of two that are probable
+/// primes of `n` bits under `test`, in a fixed order: by the middle
+/// exponents from the smallest largest one up, then by signs.
+///
+/// These generalized Mersenne primes, as Solinas named them, reduce with a
+/// few shifted additions; NIST's P-256 prime is 2^256 − 2^224 + 2^192 +
+/// 2^96 − 1. Three terms give 2^n ± 2^e ± 1, two just 2^n ± 1. No two
+/// exponents are adjacent, which makes each sum the number's non-adjacent
+/// form: unique, so no prime comes up twice, and with the fewest terms.
+/// Candidates go through trial division by the small primes before `test`.
+pub fn solinas<'a>(
+    n: u64,
+    terms: usize,
+    test: &'a dyn PrimalityTest,
+    rng: &'a mut dyn CryptoRngCore,
+) -> impl Iterator<Item = SignedPowers> + 'a {
+    let middle = terms.saturating_sub(2);
+    // The middle exponents, rising, and which of the terms are subtracted.
+    let mut exponents: Option<Vec<u64>> = (terms >= 2 && middle < 63 && n >= 2 * middle as u64 + 2)
+        .then(|| (1..=middle as u64).map(|i| 2 * i).collect());
+    let mut signs: u64 = 0;
+    core::iter::from_fn(move || {
+        let current = exponents.as_mut()?;
+        let sum = SignedPowers {
+            n,
+            terms: current
+                .iter()
+                .rev()
+                .chain(core::iter::once(&0))
+                .enumerate()
+                .map(|(i, &exponent)| (signs >> i & 1 == 1, exponent))
+                .collect(),
+        };
+        signs += 1;
+        if signs == 1 << (middle + 1) {
+            signs = 0;
+            if !next_combination(current, n) {
+                exponents = None;
+            }
+        }
+        Some(sum)
+    })
+    .filter(move |sum| {
+        let value = sum.value();
+        value.bits() == n && small_prime_precheck(&value) && test.is_probable_prime(&value, rng)
+    })
+}
+
+/// Step `exponents`, rising and each at least two past the one before, 0
+/// and two short of `n`, to the next such set in colexicographic order;
+/// `false` after the last.
+fn next_combination(exponents: &mut [u64], n: u64) -> bool {
+    for i in 0..exponents.len() {
+        let bound = exponents.get(i + 1).copied().unwrap_or(n);
+        if exponents[i] + 3 <= bound {
+            exponents[i] += 1;
+            for (j, exponent) in exponents[..i].iter_mut().enumerate() {
+                *exponent = 2 * (j as u64 + 1);
+            }
+            return true;
+        }
+    }
+    false
+}
+
+/// The odd k up to `k_max` and below 2^`n` for which k·2^`n` + 1 is prime,
+/// rising; with Proth's theorem deciding each, these are proven primes.
+///
+/// Proth primes are what number-theoretic transforms want for moduli, and
+/// what divisors of Fermat numbers look like. The multipliers are sieved
+/// a block at a time: a small prime r divides k·2^n + 1 exactly when
+/// k ≡ −2^−n (mod r), so it strikes out every r-th odd k.
+pub fn proth_primes(n: u64, k_max: u64) -> impl Iterator<Item = u64> {
+    proth_primes_between(n, 1, k_max)
+}
+
+/// Like [`proth_primes`], for the odd k from `k_min` to `k_max`, so a
+/// long search can be taken a range at a time.
+pub fn proth_primes_between(n: u64, k_min: u64, k_max: u64) -> impl Iterator<Item = u64> {
+    let k_max = match n {
+        0 => 0,
+        1..64 => k_max.min((1 << n) - 1),
+        _ => k_max,
+    };
+    // Tiny sizes, where k·2^n + 1 can be r itself, are not sieved.
+    let sieve: Vec<(u64, u64)> = if n > 12 {
+        power_residues(n)
+            .into_iter()
+            .map(|(r, power)| (r, r - pow_mod(power, r - 2, r)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut next = Some(k_min | 1);
+    let mut survivors = Vec::new().into_iter();
+    core::iter::from_fn(move || {
+        loop {
+            if let Some(k) = survivors.next() {
+                return Some(k);
+            }
+            let low = next.filter(|&low| low <= k_max)?;
+            // The odd k from `low` on, the i-th being low + 2i.
+            let count = ((k_max - low) / 2 + 1).min(PROTH_BLOCK);
+            let mut composite = vec![false; count as usize];
+            for &(r, struck) in &sieve {
+                // low + 2i ≡ struck (mod r) for i ≡ (struck − low)/2 (mod r).
+                // (r + 1)/2 is the inverse of 2 modulo r.
+                let half = r / 2 + 1;
+                let mut i = (struck + r - low % r) % r * half % r;
+                while i < count {
+                    composite[i as usize] = true;
+                    i += r;
+                }
+            }
+            survivors = (0..count)
+                .filter(|&i| !composite[i as usize])
+                .map(|i| low + 2 * i)
+                .collect::<Vec<u64>>()
+                .into_iter();
+            next = low.checked_add(2 * count);
+        }
+    })
+    .filter(move |&k| proth(&BigUint::from(k), n))
+}
+
+/// `base`^`exp` mod `modulus`, for a modulus below 2^32.
+fn pow_mod(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let mut base = base % modulus;
+    let mut result = 1 % modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    result
+}
@@ -0,0 +1,30 @@
+//! JavaScript bindings, enabled with the `wasm` feature.
+//!
+//! Build with `cargo rustc --lib --release --target wasm32-unknown-unknown
+//! --no-default-features --features wasm --crate-type cdylib` and run
+//! `wasm-bindgen` on the output. Randomness
+//! comes from `crypto.getRandomValues` through getrandom's `js` backend.
+//! Integers are exchanged as strings: decimal on output, any format accepted
+//! by [`parse_number`] on input.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{PrimeGenerator, parse_number};
+
+/// Generate a prime of `bits` bits and return it as a decimal string.
+#[wasm_bindgen(js_name = generatePrime)]
+pub fn generate_prime(bits: usize, rounds: usize, safe: bool) -> Result<String, JsError> {
+    let p = PrimeGenerator::builder()
+        .bits(bits)
+        .rounds(rounds)
+        .safe(safe)
+        .generate()?;
+    Ok(p.to_string())
+}
+
+/// Miller–Rabin test on a decimal, `0x` hex or base64 string.
+#[wasm_bindgen(js_name = isProbablePrime)]
+pub fn is_probable_prime(n: &str, rounds: usize) -> Result<bool, JsError> {
+    let n = parse_number(n)?;
+    Ok(crate::is_probable_prime(&n, rounds))
+}